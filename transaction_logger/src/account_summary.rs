@@ -0,0 +1,197 @@
+// Portfolio summary of a principal, aggregated from its whole bridge history.
+// Bridged amounts are in the bridged token, minter fees and gas in the native token of the
+// chain, like the fee revenue reports. USD values use the current price of the icrc twins.
+
+use candid::{CandidType, Int, Nat, Principal};
+use ic_ethereum_types::Address;
+use num_bigint::BigInt;
+use serde::{Deserialize, Serialize};
+
+use crate::aggregate::Summable;
+use crate::endpoints::CandidChainId;
+use crate::numeric::Erc20TokenAmount;
+use crate::state::{
+    is_native_token, ChainId, EvmToIcpStatus, EvmToIcpTx, IcpToEvmStatus, IcpToEvmTx, Operator,
+};
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct AccountTokenKey {
+    pub chain_id: ChainId,
+    pub operator: Operator,
+    pub token: Address,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AccountTokenTotals {
+    pub icrc_ledger_id: Option<Principal>,
+    pub deposit_count: u64,
+    pub withdrawal_count: u64,
+    pub bridged_in: Erc20TokenAmount,
+    pub bridged_out: Erc20TokenAmount,
+    pub minter_fees: Erc20TokenAmount,
+    pub gas_fees: Erc20TokenAmount,
+    pub failed_withdrawals: u64,
+    pub reimbursed_withdrawals: u64,
+}
+
+impl Default for AccountTokenTotals {
+    fn default() -> Self {
+        Self {
+            icrc_ledger_id: None,
+            deposit_count: 0,
+            withdrawal_count: 0,
+            bridged_in: Erc20TokenAmount::ZERO,
+            bridged_out: Erc20TokenAmount::ZERO,
+            minter_fees: Erc20TokenAmount::ZERO,
+            gas_fees: Erc20TokenAmount::ZERO,
+            failed_withdrawals: 0,
+            reimbursed_withdrawals: 0,
+        }
+    }
+}
+
+impl AccountTokenTotals {
+    // Only minted deposits count as bridged in
+    pub fn add_deposit(&mut self, tx: &EvmToIcpTx) {
+        self.deposit_count += 1;
+        self.icrc_ledger_id = self.icrc_ledger_id.or(tx.icrc_ledger_id);
+        if tx.status != EvmToIcpStatus::Minted {
+            return;
+        }
+        self.bridged_in = self.bridged_in.saturating_add(tx.value);
+
+        // Only native deposits are charged a fee, deducted from what was received
+        if is_native_token(&tx.erc20_contract_address) {
+            let charged_fee = tx
+                .actual_received
+                .and_then(|actual_received| tx.value.checked_sub(actual_received))
+                .unwrap_or(Erc20TokenAmount::ZERO);
+            self.minter_fees = self.minter_fees.saturating_add(charged_fee);
+        }
+    }
+
+    // Only successful withdrawals count as bridged out, while fees and gas are paid
+    // by failed ones as well
+    pub fn add_withdrawal(&mut self, tx: &IcpToEvmTx) {
+        self.withdrawal_count += 1;
+        self.icrc_ledger_id = self.icrc_ledger_id.or(tx.icrc_ledger_id);
+        match tx.status {
+            IcpToEvmStatus::Successful => {
+                self.bridged_out = self.bridged_out.saturating_add(tx.withdrawal_amount);
+            }
+            IcpToEvmStatus::Failed => self.failed_withdrawals += 1,
+            IcpToEvmStatus::Reimbursed | IcpToEvmStatus::QuarantinedReimbursement => {
+                self.reimbursed_withdrawals += 1
+            }
+            _ => {}
+        }
+
+        if let (Some(total_gas_spent), Some(gas_used), Some(effective_gas_price)) =
+            (tx.total_gas_spent, tx.gas_used, tx.effective_gas_price)
+        {
+            let gas_fee = gas_used
+                .checked_mul(effective_gas_price)
+                .unwrap_or(Erc20TokenAmount::ZERO);
+            let minter_fee = total_gas_spent
+                .checked_sub(gas_fee)
+                .unwrap_or(Erc20TokenAmount::ZERO);
+            self.gas_fees = self.gas_fees.saturating_add(gas_fee);
+            self.minter_fees = self.minter_fees.saturating_add(minter_fee);
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Serialize)]
+pub struct AccountTokenSummary {
+    pub chain_id: CandidChainId,
+    pub operator: Operator,
+    pub token: String,
+    pub icrc_ledger_id: Option<Principal>,
+    pub deposit_count: u64,
+    pub withdrawal_count: u64,
+    pub bridged_in: Nat,
+    pub bridged_out: Nat,
+    // Bridged in minus bridged out
+    pub net_position: Int,
+    // Amounts in the smallest denomination of the chain's native token
+    pub minter_fees: Nat,
+    pub gas_fees: Nat,
+    pub failed_withdrawals: u64,
+    pub reimbursed_withdrawals: u64,
+    // None if the price of the token, or of the native token for fees, is not known
+    pub bridged_in_usd: Option<f64>,
+    pub bridged_out_usd: Option<f64>,
+    pub fees_usd: Option<f64>,
+}
+
+impl AccountTokenSummary {
+    pub fn new(
+        key: AccountTokenKey,
+        totals: AccountTokenTotals,
+        // USD value of an amount of the bridged token and of the native token
+        token_usd_value: impl Fn(Erc20TokenAmount) -> Option<f64>,
+        native_usd_value: impl Fn(Erc20TokenAmount) -> Option<f64>,
+    ) -> Self {
+        let bridged_in = Nat::from(totals.bridged_in);
+        let bridged_out = Nat::from(totals.bridged_out);
+        let fees_usd = native_usd_value(totals.minter_fees)
+            .zip(native_usd_value(totals.gas_fees))
+            .map(|(minter_fees, gas_fees)| minter_fees + gas_fees);
+        Self {
+            chain_id: key.chain_id.into(),
+            operator: key.operator,
+            token: key.token.to_string(),
+            icrc_ledger_id: totals.icrc_ledger_id,
+            deposit_count: totals.deposit_count,
+            withdrawal_count: totals.withdrawal_count,
+            net_position: Int(
+                BigInt::from(bridged_in.0.clone()) - BigInt::from(bridged_out.0.clone())
+            ),
+            bridged_in,
+            bridged_out,
+            minter_fees: totals.minter_fees.into(),
+            gas_fees: totals.gas_fees.into(),
+            failed_withdrawals: totals.failed_withdrawals,
+            reimbursed_withdrawals: totals.reimbursed_withdrawals,
+            bridged_in_usd: token_usd_value(totals.bridged_in),
+            bridged_out_usd: token_usd_value(totals.bridged_out),
+            fees_usd,
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Serialize)]
+pub struct AccountSummary {
+    pub principal: Principal,
+    pub tokens: Vec<AccountTokenSummary>,
+    pub failed_withdrawals: u64,
+    pub reimbursed_withdrawals: u64,
+    // Sums of the tokens whose USD value is known
+    pub bridged_in_usd: f64,
+    pub bridged_out_usd: f64,
+    pub net_position_usd: f64,
+    pub fees_usd: f64,
+}
+
+impl AccountSummary {
+    pub fn new(principal: Principal, tokens: Vec<AccountTokenSummary>) -> Self {
+        let bridged_in_usd: f64 = tokens.iter().filter_map(|token| token.bridged_in_usd).sum();
+        let bridged_out_usd: f64 = tokens
+            .iter()
+            .filter_map(|token| token.bridged_out_usd)
+            .sum();
+        Self {
+            principal,
+            failed_withdrawals: tokens.iter().map(|token| token.failed_withdrawals).sum(),
+            reimbursed_withdrawals: tokens
+                .iter()
+                .map(|token| token.reimbursed_withdrawals)
+                .sum(),
+            bridged_in_usd,
+            bridged_out_usd,
+            net_position_usd: bridged_in_usd - bridged_out_usd,
+            fees_usd: tokens.iter().filter_map(|token| token.fees_usd).sum(),
+            tokens,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+// Running totals of the transactions of a time bucket, shared by the bridge volume and the
+// fee revenue. Both count transactions, sum token amounts and sum the USD value of the
+// transactions whose price was known; they only differ in how many amounts they sum.
+
+use serde::{Deserialize, Serialize};
+
+use crate::numeric::Erc20TokenAmount;
+
+/// A value that can be summed into a running total.
+pub trait Summable: Copy {
+    const ZERO: Self;
+
+    /// Adds `other`, staying at the largest value instead of overflowing.
+    fn saturating_add(self, other: Self) -> Self;
+}
+
+impl Summable for Erc20TokenAmount {
+    const ZERO: Self = Erc20TokenAmount::ZERO;
+
+    fn saturating_add(self, other: Self) -> Self {
+        self.checked_add(other).unwrap_or(Erc20TokenAmount::MAX)
+    }
+}
+
+impl Summable for f64 {
+    const ZERO: Self = 0.0;
+
+    fn saturating_add(self, other: Self) -> Self {
+        (self + other).min(f64::MAX)
+    }
+}
+
+impl<A: Summable, B: Summable> Summable for (A, B) {
+    const ZERO: Self = (A::ZERO, B::ZERO);
+
+    fn saturating_add(self, other: Self) -> Self {
+        (
+            self.0.saturating_add(other.0),
+            self.1.saturating_add(other.1),
+        )
+    }
+}
+
+// Fields are stored in this order, so a single amount keeps the layout of the bridge volume
+// buckets and a pair of amounts the layout of the fee revenue rows written before this type.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Aggregate<A, U> {
+    pub count: u64,
+    pub amounts: A,
+    // Sum of the USD values of the transactions whose price was known
+    pub usd_values: U,
+    pub priced_count: u64,
+}
+
+impl<A: Summable, U: Summable> Default for Aggregate<A, U> {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            amounts: A::ZERO,
+            usd_values: U::ZERO,
+            priced_count: 0,
+        }
+    }
+}
+
+impl<A: Summable, U: Summable> Aggregate<A, U> {
+    pub fn add(self, amounts: A, usd_values: Option<U>) -> Self {
+        Self {
+            count: self.count.saturating_add(1),
+            amounts: self.amounts.saturating_add(amounts),
+            usd_values: self
+                .usd_values
+                .saturating_add(usd_values.unwrap_or(U::ZERO)),
+            priced_count: self
+                .priced_count
+                .saturating_add(usd_values.map_or(0, |_| 1)),
+        }
+    }
+
+    /// The USD totals, none if the price was not known for any of the transactions.
+    pub fn priced_usd_values(&self) -> Option<U> {
+        (self.priced_count > 0).then_some(self.usd_values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_saturate_instead_of_overflowing() {
+        let aggregate = Aggregate::<Erc20TokenAmount, f64>::default()
+            .add(Erc20TokenAmount::MAX, Some(f64::MAX))
+            .add(Erc20TokenAmount::from(1_u64), Some(f64::MAX));
+
+        assert_eq!(aggregate.count, 2);
+        assert_eq!(aggregate.amounts, Erc20TokenAmount::MAX);
+        assert_eq!(aggregate.usd_values, f64::MAX);
+        assert_eq!(aggregate.priced_count, 2);
+    }
+
+    #[test]
+    fn should_keep_the_layout_of_a_flat_record() {
+        #[derive(Serialize)]
+        struct FlatRecord {
+            count: u64,
+            first_amount: Erc20TokenAmount,
+            second_amount: Erc20TokenAmount,
+            first_usd_value: f64,
+            second_usd_value: f64,
+            priced_count: u64,
+        }
+
+        let aggregate = Aggregate::<(Erc20TokenAmount, Erc20TokenAmount), (f64, f64)>::default()
+            .add(
+                (
+                    Erc20TokenAmount::from(10_u64),
+                    Erc20TokenAmount::from(100_u64),
+                ),
+                Some((1.0, 10.0)),
+            );
+        let flat = FlatRecord {
+            count: 1,
+            first_amount: Erc20TokenAmount::from(10_u64),
+            second_amount: Erc20TokenAmount::from(100_u64),
+            first_usd_value: 1.0,
+            second_usd_value: 10.0,
+            priced_count: 1,
+        };
+
+        assert_eq!(
+            bincode::serialize(&aggregate).unwrap(),
+            bincode::serialize(&flat).unwrap()
+        );
+    }
+}
@@ -0,0 +1,167 @@
+// Running aggregates of bridge volume, bucketed by hour and by day
+
+use candid::{CandidType, Nat};
+use ic_ethereum_types::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::aggregate::Aggregate;
+use crate::endpoints::CandidChainId;
+use crate::numeric::Erc20TokenAmount;
+use crate::state::{ChainId, Operator};
+
+const HOUR_NS: u64 = 60 * 60 * 1_000_000_000;
+const DAY_NS: u64 = 24 * HOUR_NS;
+
+// Maximum number of buckets returned by a single get_bridge_stats call
+pub const MAX_BRIDGE_STATS_BUCKETS: usize = 5_000;
+
+#[derive(
+    Clone, Copy, CandidType, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize,
+)]
+pub enum Granularity {
+    Hourly,
+    Daily,
+}
+
+impl Granularity {
+    pub const ALL: [Granularity; 2] = [Granularity::Hourly, Granularity::Daily];
+
+    pub fn bucket_size_ns(&self) -> u64 {
+        match self {
+            Granularity::Hourly => HOUR_NS,
+            Granularity::Daily => DAY_NS,
+        }
+    }
+
+    /// Start of the bucket containing `timestamp`.
+    pub fn bucket_start(&self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.bucket_size_ns()
+    }
+}
+
+#[derive(
+    Clone, Copy, CandidType, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize,
+)]
+pub enum BridgeDirection {
+    EvmToIcp,
+    IcpToEvm,
+}
+
+// Buckets of the same granularity are stored next to each other, ordered by time
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub struct BridgeStatsKey {
+    pub granularity: Granularity,
+    pub bucket_start: u64,
+    pub chain_id: ChainId,
+    pub token: Address,
+    pub operator: Operator,
+    pub direction: BridgeDirection,
+}
+
+impl BridgeStatsKey {
+    /// Smallest key of the bucket starting at `bucket_start`.
+    pub fn first_of_bucket(granularity: Granularity, bucket_start: u64) -> Self {
+        Self {
+            granularity,
+            bucket_start,
+            chain_id: ChainId(0),
+            token: Address::ZERO,
+            operator: Operator::DfinityCkEthMinter,
+            direction: BridgeDirection::EvmToIcp,
+        }
+    }
+}
+
+// Number of transactions of a bucket, their volume and its USD value
+pub type BridgeStats = Aggregate<Erc20TokenAmount, f64>;
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BridgeStatsFilters {
+    pub chain_id: Option<CandidChainId>,
+    pub token: Option<String>,
+    pub operator: Option<Operator>,
+    pub direction: Option<BridgeDirection>,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BridgeStatsRange {
+    // Inclusive start and exclusive end in nanoseconds since the epoch
+    pub from: u64,
+    pub to: u64,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Serialize)]
+pub struct BridgeStatsBucket {
+    pub bucket_start: u64,
+    pub chain_id: CandidChainId,
+    pub token: String,
+    pub operator: Operator,
+    pub direction: BridgeDirection,
+    pub count: u64,
+    pub amount: Nat,
+    // None if the price of the token was not known for any of the transactions
+    pub usd_value: Option<f64>,
+}
+
+impl BridgeStatsFilters {
+    pub fn matches(&self, key: &BridgeStatsKey, token: &Option<Address>) -> bool {
+        self.chain_id
+            .as_ref()
+            .map_or(true, |chain_id| ChainId::from(chain_id) == key.chain_id)
+            && token.map_or(true, |token| token == key.token)
+            && self
+                .operator
+                .map_or(true, |operator| operator == key.operator)
+            && self
+                .direction
+                .map_or(true, |direction| direction == key.direction)
+    }
+}
+
+impl From<(BridgeStatsKey, BridgeStats)> for BridgeStatsBucket {
+    fn from((key, stats): (BridgeStatsKey, BridgeStats)) -> Self {
+        Self {
+            bucket_start: key.bucket_start,
+            chain_id: key.chain_id.into(),
+            token: key.token.to_string(),
+            operator: key.operator,
+            direction: key.direction,
+            count: stats.count,
+            amount: stats.amounts.into(),
+            usd_value: stats.priced_usd_values(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_align_buckets() {
+        let timestamp = 3 * DAY_NS + 5 * HOUR_NS + 17;
+
+        assert_eq!(
+            Granularity::Hourly.bucket_start(timestamp),
+            3 * DAY_NS + 5 * HOUR_NS
+        );
+        assert_eq!(Granularity::Daily.bucket_start(timestamp), 3 * DAY_NS);
+        assert_eq!(Granularity::Daily.bucket_start(3 * DAY_NS), 3 * DAY_NS);
+    }
+
+    #[test]
+    fn should_accumulate_usd_value_only_when_priced() {
+        let stats = BridgeStats::default()
+            .add(Erc20TokenAmount::from(10_u64), Some(2.5))
+            .add(Erc20TokenAmount::from(5_u64), None);
+
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.amounts, Erc20TokenAmount::from(15_u64));
+        assert_eq!(stats.usd_values, 2.5);
+        assert_eq!(stats.priced_count, 1);
+
+        let key = BridgeStatsKey::first_of_bucket(Granularity::Hourly, 0);
+        let unpriced = BridgeStats::default().add(Erc20TokenAmount::from(1_u64), None);
+        assert_eq!(BridgeStatsBucket::from((key, unpriced)).usd_value, None);
+    }
+}
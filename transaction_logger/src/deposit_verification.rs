@@ -0,0 +1,144 @@
+// Verification of deposits submitted by users against the minter event that accepted them.
+// The fields a user submitted are compared with the event once it is scraped, mismatches are
+// kept in a discrepancy report together with the caller that submitted them, and the
+// transaction is rebuilt from the event so no client supplied value outlives verification.
+
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::CandidChainId;
+use crate::state::{ChainId, EvmToIcpTx, EvmToIcpTxIdentifier, Operator};
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct DepositSubmission {
+    pub caller: Principal,
+    pub submitted_at: u64,
+}
+
+#[derive(
+    Clone,
+    Copy,
+    CandidType,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Debug,
+    Deserialize,
+    Serialize,
+    strum_macros::IntoStaticStr,
+)]
+pub enum DepositField {
+    FromAddress,
+    Value,
+    Principal,
+    Subaccount,
+    Erc20ContractAddress,
+    IcrcLedgerId,
+    Operator,
+    // Not part of the minter event, so any submitted value is reported and dropped
+    TotalGasSpent,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldMismatch {
+    pub field: DepositField,
+    pub submitted: Option<String>,
+    pub verified: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct DepositDiscrepancy {
+    pub submitted_by: Principal,
+    pub chain_id: ChainId,
+    pub operator: Operator,
+    pub submitted_at: u64,
+    pub detected_at: u64,
+    pub mismatches: Vec<FieldMismatch>,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CandidDepositDiscrepancy {
+    pub transaction_hash: String,
+    pub chain_id: CandidChainId,
+    pub operator: Operator,
+    pub submitted_by: Principal,
+    pub submitted_at: u64,
+    pub detected_at: u64,
+    pub mismatches: Vec<FieldMismatch>,
+}
+
+impl CandidDepositDiscrepancy {
+    pub fn new(identifier: EvmToIcpTxIdentifier, discrepancy: DepositDiscrepancy) -> Self {
+        Self {
+            transaction_hash: identifier.transaction_hash().to_string(),
+            chain_id: discrepancy.chain_id.into(),
+            operator: discrepancy.operator,
+            submitted_by: discrepancy.submitted_by,
+            submitted_at: discrepancy.submitted_at,
+            detected_at: discrepancy.detected_at,
+            mismatches: discrepancy.mismatches,
+        }
+    }
+}
+
+// Compares every field a user submitted with the values taken from the minter event
+pub fn compare_deposit(submitted: &EvmToIcpTx, verified: &EvmToIcpTx) -> Vec<FieldMismatch> {
+    let mut mismatches = vec![];
+    let mut compare = |field: DepositField, submitted: Option<String>, verified: Option<String>| {
+        if submitted != verified {
+            mismatches.push(FieldMismatch {
+                field,
+                submitted,
+                verified,
+            });
+        }
+    };
+
+    compare(
+        DepositField::FromAddress,
+        Some(submitted.from_address.to_string()),
+        Some(verified.from_address.to_string()),
+    );
+    compare(
+        DepositField::Value,
+        Some(submitted.value.to_string_inner()),
+        Some(verified.value.to_string_inner()),
+    );
+    compare(
+        DepositField::Principal,
+        Some(submitted.principal.to_text()),
+        Some(verified.principal.to_text()),
+    );
+    compare(
+        DepositField::Subaccount,
+        submitted.subaccount.map(hex::encode),
+        verified.subaccount.map(hex::encode),
+    );
+    compare(
+        DepositField::Erc20ContractAddress,
+        Some(submitted.erc20_contract_address.to_string()),
+        Some(verified.erc20_contract_address.to_string()),
+    );
+    compare(
+        DepositField::IcrcLedgerId,
+        submitted.icrc_ledger_id.map(|id| id.to_text()),
+        verified.icrc_ledger_id.map(|id| id.to_text()),
+    );
+    compare(
+        DepositField::Operator,
+        Some(format!("{:?}", submitted.operator)),
+        Some(format!("{:?}", verified.operator)),
+    );
+    compare(
+        DepositField::TotalGasSpent,
+        submitted
+            .total_gas_spent
+            .map(|amount| amount.to_string_inner()),
+        verified
+            .total_gas_spent
+            .map(|amount| amount.to_string_inner()),
+    );
+
+    mismatches
+}
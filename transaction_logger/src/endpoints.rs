@@ -1,8 +1,13 @@
+use crate::guard::TaskType;
+use crate::numeric::Erc20TokenAmount;
 use crate::state::{
-    EvmToIcpStatus, EvmToIcpTx, EvmToken, IcpToEvmStatus, IcpToEvmTx, IcpToken, IcpTokenType,
-    Operator,
+    is_native_token, EvmToIcpStatus, EvmToIcpTx, EvmToken, FeeScheduleEntry, IcpToEvmStatus,
+    IcpToEvmTx, IcpToken, IcpTokenType, Minter, MinterHealth, Operator, Reimbursement,
+    TransactionId,
 };
+use crate::stuck_transactions::StuckThresholdArgs;
 use candid::{CandidType, Deserialize, Nat, Principal};
+use icrc_ledger_types::icrc1::account::Account;
 use serde::Serialize;
 
 #[derive(Debug, CandidType, Deserialize)]
@@ -23,6 +28,7 @@ pub struct AddEvmToIcpTx {
     pub total_gas_spent: Nat,
     pub erc20_contract_address: String,
     pub icrc_ledger_id: Principal,
+    // Ignored, the logger records the time it received the submission
     pub time: Nat,
     pub operator: Operator,
 }
@@ -35,6 +41,7 @@ pub enum AddEvmToIcpTxError {
     ChinNotSupported,
     InvalidTokenContract,
     InvalidAddress,
+    InvalidTransactionHash,
 }
 
 // Transactions for icp to evm
@@ -46,6 +53,7 @@ pub struct AddIcpToEvmTx {
     pub destination: String,
     pub from: Principal,
     pub from_subaccount: Option<[u8; 32]>,
+    // Ignored, the logger records the time it received the submission
     pub time: Nat,
     pub max_transaction_fee: Nat,
     pub erc20_contract_address: String,
@@ -63,6 +71,24 @@ pub enum AddIcpToEvmTxError {
     InvalidTokenContract,
 }
 
+// Errors of the versioned query endpoints
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum LoggerQueryError {
+    InvalidAddress,
+    InvalidAccount,
+    InvalidHash,
+    InvalidTransactionId,
+    InvalidCursor,
+    UnsupportedChain,
+    NotFound,
+    // The caller is not a controller of the canister
+    NotAuthorized,
+    // The search query is neither a hash, an address, a principal, an account nor an index
+    UnrecognizedQuery,
+    // The requested page is longer than the endpoint allows
+    InvalidLength,
+}
+
 #[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub struct MinterArgs {
     pub chain_id: CandidChainId,
@@ -74,6 +100,20 @@ pub struct MinterArgs {
     pub icp_to_evm_fee: Nat,
 }
 
+impl From<&Minter> for MinterArgs {
+    fn from(value: &Minter) -> Self {
+        Self {
+            chain_id: value.chain_id.into(),
+            minter_id: value.id,
+            operator: value.operator,
+            last_observed_event: value.last_observed_event.into(),
+            last_scraped_event: value.last_scraped_event.into(),
+            evm_to_icp_fee: value.evm_to_icp_fee.into(),
+            icp_to_evm_fee: value.icp_to_evm_fee.into(),
+        }
+    }
+}
+
 #[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub struct UpdateMinterArgs {
     pub chain_id: CandidChainId,
@@ -81,6 +121,36 @@ pub struct UpdateMinterArgs {
     pub evm_to_icp_fee: Nat,
     pub icp_to_evm_fee: Nat,
     pub operator: Operator,
+    // Time in nanoseconds from which the new fees apply and the first minter event charged
+    // with them. Both are set together, or neither to apply the fees from the upgrade on.
+    pub fees_effective_from: Option<u64>,
+    pub fees_effective_from_event: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CandidFeeScheduleEntry {
+    pub effective_from_event: u64,
+    pub effective_from_timestamp: u64,
+    pub evm_to_icp_fee: Nat,
+    pub icp_to_evm_fee: Nat,
+}
+
+impl From<FeeScheduleEntry> for CandidFeeScheduleEntry {
+    fn from(value: FeeScheduleEntry) -> Self {
+        Self {
+            effective_from_event: value.effective_from_event,
+            effective_from_timestamp: value.effective_from_timestamp,
+            evm_to_icp_fee: value.evm_to_icp_fee.into(),
+            icp_to_evm_fee: value.icp_to_evm_fee.into(),
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MinterFeeSchedule {
+    pub chain_id: CandidChainId,
+    pub operator: Operator,
+    pub entries: Vec<CandidFeeScheduleEntry>,
 }
 
 #[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
@@ -92,6 +162,7 @@ pub struct InitArgs {
 pub struct UpgradeArg {
     pub new_minters: Option<Vec<MinterArgs>>,
     pub update_minters: Option<Vec<UpdateMinterArgs>>,
+    pub stuck_thresholds: Option<Vec<StuckThresholdArgs>>,
 }
 
 #[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
@@ -134,6 +205,8 @@ impl From<CandidEvmToIcp> for Transaction {
 
 #[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub struct CandidIcpToEvm {
+    // Stable id to refresh this withdrawal with `get_transaction_by_id`
+    pub id: String,
     pub transaction_hash: Option<String>,
     pub native_ledger_burn_index: Nat,
     pub withdrawal_amount: Nat,
@@ -141,7 +214,12 @@ pub struct CandidIcpToEvm {
     pub destination: String,
     pub from: Principal,
     pub from_subaccount: Option<[u8; 32]>,
-    pub time: u64,
+    // `from` and `from_subaccount` in the textual ICRC-1 form
+    pub from_account: String,
+    pub submitted_at: Option<u64>,
+    pub accepted_at: Option<u64>,
+    pub finalized_at: Option<u64>,
+    pub reimbursed_at: Option<u64>,
     pub max_transaction_fee: Option<Nat>,
     pub effective_gas_price: Option<Nat>,
     pub gas_used: Option<Nat>,
@@ -153,10 +231,49 @@ pub struct CandidIcpToEvm {
     pub status: IcpToEvmStatus,
     pub operator: Operator,
     pub chain_id: Nat,
+    // USD price of the token when the minter accepted the transaction and the value at that
+    // price. None if the price was unknown or the event was scraped more than the price
+    // refresh interval after it happened.
+    pub accepted_usd_price: Option<String>,
+    pub accepted_usd_value: Option<String>,
+    // For ERC-20 withdrawals the ERC-20 twin burn and the separate native burn paying for gas
+    pub erc20_burn: Option<CandidLedgerBurn>,
+    pub native_fee_burn: Option<CandidLedgerBurn>,
+    pub native_reimbursement: Option<CandidReimbursement>,
+    pub erc20_reimbursement: Option<CandidReimbursement>,
+    // Native fee burned for an ERC-20 withdrawal minus the part that was reimbursed
+    pub native_fee_paid: Option<Nat>,
+    // Expected completion time of a pending withdrawal,
+    // only set by the principal, account and id queries
+    pub estimated_completion_at: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CandidLedgerBurn {
+    pub burn_index: Nat,
+    pub amount: Nat,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CandidReimbursement {
+    pub ledger_id: Option<Principal>,
+    pub amount: Nat,
+    pub reimbursed_in_block: Option<Nat>,
+}
+
+impl From<Reimbursement> for CandidReimbursement {
+    fn from(value: Reimbursement) -> Self {
+        Self {
+            ledger_id: value.ledger_id,
+            amount: value.amount.into(),
+            reimbursed_in_block: value.reimbursed_in_block.map(|index| index.get().into()),
+        }
+    }
 }
 
 impl From<IcpToEvmTx> for CandidIcpToEvm {
     fn from(value: IcpToEvmTx) -> Self {
+        let id = TransactionId::from(&value).to_string();
         let IcpToEvmTx {
             transaction_hash,
             native_ledger_burn_index,
@@ -165,7 +282,10 @@ impl From<IcpToEvmTx> for CandidIcpToEvm {
             destination,
             from,
             from_subaccount,
-            time,
+            submitted_at,
+            accepted_at,
+            finalized_at,
+            reimbursed_at,
             max_transaction_fee,
             effective_gas_price,
             gas_used,
@@ -177,17 +297,58 @@ impl From<IcpToEvmTx> for CandidIcpToEvm {
             status,
             operator,
             chain_id,
+            accepted_usd_price,
+            accepted_usd_value,
+            native_reimbursement,
+            erc20_reimbursement,
         } = value;
 
+        let is_erc20_withdrawal = !is_native_token(&erc20_contract_address);
+        let erc20_burn =
+            erc20_ledger_burn_index
+                .filter(|_| is_erc20_withdrawal)
+                .map(|burn_index| CandidLedgerBurn {
+                    burn_index: burn_index.get().into(),
+                    amount: withdrawal_amount.into(),
+                });
+        let native_fee_burn =
+            max_transaction_fee
+                .filter(|_| is_erc20_withdrawal)
+                .map(|max_transaction_fee| CandidLedgerBurn {
+                    burn_index: native_ledger_burn_index.get().into(),
+                    amount: max_transaction_fee.into(),
+                });
+        let native_fee_paid =
+            max_transaction_fee
+                .filter(|_| is_erc20_withdrawal)
+                .map(|max_transaction_fee| {
+                    let reimbursed = native_reimbursement
+                        .as_ref()
+                        .map_or(Erc20TokenAmount::ZERO, |reimbursement| reimbursement.amount);
+                    max_transaction_fee
+                        .checked_sub(reimbursed)
+                        .unwrap_or(Erc20TokenAmount::ZERO)
+                        .into()
+                });
+
         Self {
-            transaction_hash,
+            id,
+            transaction_hash: transaction_hash.map(|transaction_hash| transaction_hash.to_string()),
             native_ledger_burn_index: native_ledger_burn_index.get().into(),
             withdrawal_amount: withdrawal_amount.into(),
             actual_received: actual_received.map(|actual_received| actual_received.into()),
             destination: destination.to_string(),
             from,
             from_subaccount,
-            time,
+            from_account: Account {
+                owner: from,
+                subaccount: from_subaccount,
+            }
+            .to_string(),
+            submitted_at,
+            accepted_at,
+            finalized_at,
+            reimbursed_at,
             max_transaction_fee: max_transaction_fee
                 .map(|max_transaction_fee| max_transaction_fee.into()),
             effective_gas_price: effective_gas_price
@@ -201,12 +362,22 @@ impl From<IcpToEvmTx> for CandidIcpToEvm {
             status,
             operator,
             chain_id: Nat::from(chain_id),
+            accepted_usd_price,
+            accepted_usd_value,
+            erc20_burn,
+            native_fee_burn,
+            native_reimbursement: native_reimbursement.map(CandidReimbursement::from),
+            erc20_reimbursement: erc20_reimbursement.map(CandidReimbursement::from),
+            native_fee_paid,
+            estimated_completion_at: None,
         }
     }
 }
 
 #[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub struct CandidEvmToIcp {
+    // Stable id to refresh this deposit with `get_transaction_by_id`
+    pub id: String,
     pub from_address: String,
     pub transaction_hash: String,
     pub value: Nat,
@@ -215,18 +386,31 @@ pub struct CandidEvmToIcp {
     pub actual_received: Option<Nat>,
     pub principal: Principal,
     pub subaccount: Option<[u8; 32]>,
+    // `principal` and `subaccount` in the textual ICRC-1 form
+    pub account: String,
     pub chain_id: Nat,
     pub total_gas_spent: Option<Nat>,
     pub erc20_contract_address: String,
     pub icrc_ledger_id: Option<Principal>,
     pub status: EvmToIcpStatus,
     pub verified: bool,
-    pub time: u64,
+    pub submitted_at: Option<u64>,
+    pub accepted_at: Option<u64>,
+    pub minted_at: Option<u64>,
     pub operator: Operator,
+    // USD price of the token when the minter accepted the transaction and the value at that
+    // price. None if the price was unknown or the event was scraped more than the price
+    // refresh interval after it happened.
+    pub accepted_usd_price: Option<String>,
+    pub accepted_usd_value: Option<String>,
+    // Expected completion time of a pending deposit,
+    // only set by the principal, account and id queries
+    pub estimated_completion_at: Option<u64>,
 }
 
 impl From<EvmToIcpTx> for CandidEvmToIcp {
     fn from(value: EvmToIcpTx) -> Self {
+        let id = TransactionId::from(&value).to_string();
         let EvmToIcpTx {
             from_address,
             transaction_hash,
@@ -241,13 +425,18 @@ impl From<EvmToIcpTx> for CandidEvmToIcp {
             icrc_ledger_id,
             status,
             verified,
-            time,
+            submitted_at,
+            accepted_at,
+            minted_at,
             operator,
             ledger_mint_index,
+            accepted_usd_price,
+            accepted_usd_value,
         } = value;
         Self {
+            id,
             from_address: from_address.to_string(),
-            transaction_hash,
+            transaction_hash: transaction_hash.to_string(),
             value: value.into(),
             block_number: block_number.map(|blokc_number| blokc_number.into()),
             ledger_mint_index: ledger_mint_index
@@ -255,14 +444,24 @@ impl From<EvmToIcpTx> for CandidEvmToIcp {
             actual_received: actual_received.map(|actual_received| actual_received.into()),
             principal,
             subaccount,
+            account: Account {
+                owner: principal,
+                subaccount,
+            }
+            .to_string(),
             chain_id: Nat::from(chain_id),
             total_gas_spent: total_gas_spent.map(|total_gas_spent| total_gas_spent.into()),
             erc20_contract_address: erc20_contract_address.to_string(),
             icrc_ledger_id,
             status,
             verified,
-            time,
+            submitted_at,
+            accepted_at,
+            minted_at,
             operator,
+            accepted_usd_price,
+            accepted_usd_value,
+            estimated_completion_at: None,
         }
     }
 }
@@ -336,3 +535,31 @@ pub struct TokenPair {
     pub icp_token: CandidIcpToken,
     pub operator: Operator,
 }
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ActiveTask {
+    pub task: TaskType,
+    pub acquired_at: u64,
+    // Bumped by the task after every await
+    pub renewed_at: u64,
+    pub held_for_ns: u64,
+    pub max_hold_time_ns: u64,
+    // Stale guards were not renewed in time and will be taken over on the next run
+    pub stale: bool,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MinterStatus {
+    pub minter_id: Principal,
+    pub operator: Operator,
+    pub chain_id: CandidChainId,
+    pub evm_to_icp_fee: Nat,
+    pub icp_to_evm_fee: Nat,
+    pub last_observed_event: u64,
+    pub last_scraped_event: u64,
+    pub lag: u64,
+    pub last_event_applied_at: Option<u64>,
+    pub last_scrape_success_at: Option<u64>,
+    pub consecutive_scrape_failures: u32,
+    pub health: MinterHealth,
+}
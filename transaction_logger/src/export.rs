@@ -0,0 +1,357 @@
+// Bulk export of the stored transactions, tokens and bridge pairs for data warehousing.
+// Maps are exported in key order, one CBOR item or JSON line per entry. Every change to an
+// exported map bumps the version of the state, which is used as a snapshot sequence number:
+// pages read at the same sequence come from the same state.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap as HeapBTreeMap;
+
+use candid::CandidType;
+use ic_stable_structures::{BTreeMap, Storable};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+
+use crate::endpoints::LoggerQueryError;
+use crate::state::{read_state, StableMemory};
+
+// Maximum number of entries in a single page
+pub const MAX_EXPORT_PAGE_LENGTH: u64 = 1_000;
+
+// Access tokens for the HTTP export expire after an hour
+pub const EXPORT_ACCESS_TOKEN_TTL_NS: u64 = 60 * 60 * 1_000_000_000;
+
+const EXPORT_PATH_PREFIX: &str = "/export/";
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportCollection {
+    EvmToIcpTxs,
+    IcpToEvmTxs,
+    IcpTokens,
+    EvmTokens,
+    CkErc20BridgePairs,
+    AppicBridgePairs,
+}
+
+impl ExportCollection {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::EvmToIcpTxs => "evm_to_icp_txs",
+            Self::IcpToEvmTxs => "icp_to_evm_txs",
+            Self::IcpTokens => "icp_token_list",
+            Self::EvmTokens => "evm_token_list",
+            Self::CkErc20BridgePairs => "supported_ckerc20_tokens",
+            Self::AppicBridgePairs => "supported_twin_appic_tokens",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::EvmToIcpTxs,
+            Self::IcpToEvmTxs,
+            Self::IcpTokens,
+            Self::EvmTokens,
+            Self::CkErc20BridgePairs,
+            Self::AppicBridgePairs,
+        ]
+        .into_iter()
+        .find(|collection| collection.name() == name)
+    }
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    // A sequence of CBOR items, one per entry
+    Cbor,
+    // Newline-delimited JSON, one line per entry
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Cbor => "application/cbor-seq",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "cbor" => Some(Self::Cbor),
+            "ndjson" => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExportArgs {
+    pub collection: ExportCollection,
+    pub format: ExportFormat,
+    // `next_cursor` of the previous page, none for the first page
+    pub cursor: Option<ByteBuf>,
+    // At most `MAX_EXPORT_PAGE_LENGTH`
+    pub length: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExportPage {
+    // Version of the state when the page was read
+    pub sequence: u64,
+    pub entry_count: u64,
+    pub data: ByteBuf,
+    // None once the collection is exhausted
+    pub next_cursor: Option<ByteBuf>,
+}
+
+#[derive(Serialize)]
+struct ExportEntry<'a, K, V> {
+    key: &'a K,
+    value: &'a V,
+}
+
+// First and last item of an HTTP export
+#[derive(Serialize)]
+pub struct ExportSnapshot<'a> {
+    pub collection: &'a str,
+    pub sequence: u64,
+}
+
+pub fn encode_item<T: Serialize>(format: ExportFormat, item: &T, out: &mut Vec<u8>) {
+    match format {
+        ExportFormat::Cbor => {
+            ciborium::ser::into_writer(item, &mut *out).expect("failed to encode export item")
+        }
+        ExportFormat::Ndjson => {
+            serde_json::to_writer(&mut *out, item).expect("failed to encode export item");
+            out.push(b'\n');
+        }
+    }
+}
+
+// Encodes up to `length` entries of `map` following the key encoded in `cursor`.
+// Returns the encoded entries, their number and the cursor of the next page, or an error if
+// `cursor` does not decode to a key of `map`.
+pub fn export_map<K, V>(
+    map: &BTreeMap<K, V, StableMemory>,
+    cursor: Option<&[u8]>,
+    length: u64,
+    format: ExportFormat,
+) -> Result<(Vec<u8>, u64, Option<ByteBuf>), LoggerQueryError>
+where
+    K: Storable + Ord + Clone + Serialize + DeserializeOwned,
+    V: Storable + Serialize,
+{
+    let length = length.min(MAX_EXPORT_PAGE_LENGTH) as usize;
+    let cursor = cursor
+        .map(bincode::deserialize::<K>)
+        .transpose()
+        .map_err(|_e| LoggerQueryError::InvalidCursor)?;
+    let entries = match &cursor {
+        Some(cursor) => map.range(cursor.clone()..),
+        None => map.range(..),
+    };
+
+    let mut data = vec![];
+    let mut count = 0;
+    let mut last_key = None;
+    for (key, value) in entries
+        .filter(|(key, _value)| Some(key) != cursor.as_ref())
+        .take(length)
+    {
+        encode_item(
+            format,
+            &ExportEntry {
+                key: &key,
+                value: &value,
+            },
+            &mut data,
+        );
+        count += 1;
+        last_key = Some(key);
+    }
+
+    // A full page may be followed by more entries
+    let next_cursor = last_key
+        .filter(|_| length > 0 && count == length as u64)
+        .map(|key| ByteBuf::from(bincode::serialize(&key).expect("failed to encode cursor")));
+    Ok((data, count, next_cursor))
+}
+
+// Parses the collection and format of an export path, none if `path` is not an export
+pub fn parse_export_path(path: &str) -> Option<Result<(ExportCollection, ExportFormat), String>> {
+    let file = path.strip_prefix(EXPORT_PATH_PREFIX)?;
+    let parsed = file.rsplit_once('.').and_then(|(name, extension)| {
+        Some((
+            ExportCollection::from_name(name)?,
+            ExportFormat::from_extension(extension)?,
+        ))
+    });
+    Some(parsed.ok_or_else(|| {
+        format!("unknown export {file}, expected <collection>.cbor or <collection>.ndjson")
+    }))
+}
+
+// HTTP requests are anonymous, so controllers issue short lived tokens for the HTTP export,
+// sent in the `Authorization: Bearer <token>` header. Each token comes with an export id that
+// identifies it in streaming tokens, which pass through the boundary nodes, so the access token
+// itself never leaves the request headers. Both are kept on the heap and do not survive upgrades.
+thread_local! {
+    static EXPORT_ACCESS_TOKENS: RefCell<HeapBTreeMap<String, ExportGrant>> = RefCell::default();
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ExportGrant {
+    export_id: String,
+    expires_at: u64,
+}
+
+// Stores a new access token made of the first half of `random_bytes` and its export id made of
+// the second half, valid until `now` plus the TTL
+pub fn issue_access_token(random_bytes: &[u8], now: u64) -> String {
+    let (token, export_id) = random_bytes.split_at(random_bytes.len() / 2);
+    let token = hex::encode(token);
+    EXPORT_ACCESS_TOKENS.with(|tokens| {
+        let mut tokens = tokens.borrow_mut();
+        tokens.retain(|_token, grant| grant.expires_at > now);
+        tokens.insert(
+            token.clone(),
+            ExportGrant {
+                export_id: hex::encode(export_id),
+                expires_at: now + EXPORT_ACCESS_TOKEN_TTL_NS,
+            },
+        );
+    });
+    token
+}
+
+// Gets the export id of an access token, none if the token is unknown or expired
+pub fn export_id_of(token: &str, now: u64) -> Option<String> {
+    EXPORT_ACCESS_TOKENS.with(|tokens| {
+        tokens
+            .borrow()
+            .get(token)
+            .filter(|grant| grant.expires_at > now)
+            .map(|grant| grant.export_id.clone())
+    })
+}
+
+pub fn is_valid_export_id(export_id: &str, now: u64) -> bool {
+    EXPORT_ACCESS_TOKENS.with(|tokens| {
+        tokens
+            .borrow()
+            .values()
+            .any(|grant| grant.export_id == export_id && grant.expires_at > now)
+    })
+}
+
+// Extracts the access token of an `Authorization: Bearer <token>` header
+pub fn bearer_token<'a>(headers: &'a [(String, String)]) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(name, _value)| name.eq_ignore_ascii_case("authorization"))
+        .and_then(|(_name, value)| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExportToken {
+    pub collection: ExportCollection,
+    pub format: ExportFormat,
+    pub cursor: Option<ByteBuf>,
+    pub export_id: String,
+}
+
+// Renders a page of an HTTP export, along with the token of the next page if any.
+// The first page starts with the sequence it was read at and the last one ends with its own,
+// as the sequence only grows both are equal if nothing changed in between.
+// Fails if the cursor of `token` is not one of the collection.
+pub fn render_export_page(
+    token: ExportToken,
+    first_page: bool,
+) -> Result<(Vec<u8>, Option<ExportToken>), LoggerQueryError> {
+    let page = read_state(|s| {
+        s.export(&ExportArgs {
+            collection: token.collection,
+            format: token.format,
+            cursor: token.cursor.clone(),
+            length: MAX_EXPORT_PAGE_LENGTH,
+        })
+    })?;
+    let snapshot = ExportSnapshot {
+        collection: token.collection.name(),
+        sequence: page.sequence,
+    };
+
+    let mut body = vec![];
+    if first_page {
+        encode_item(token.format, &snapshot, &mut body);
+    }
+    body.extend(page.data.into_vec());
+    match page.next_cursor {
+        Some(cursor) => Ok((
+            body,
+            Some(ExportToken {
+                cursor: Some(cursor),
+                ..token
+            }),
+        )),
+        None => {
+            encode_item(token.format, &snapshot, &mut body);
+            Ok((body, None))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_export_paths() {
+        assert_eq!(
+            parse_export_path("/export/evm_to_icp_txs.ndjson"),
+            Some(Ok((ExportCollection::EvmToIcpTxs, ExportFormat::Ndjson)))
+        );
+        assert_eq!(
+            parse_export_path("/export/supported_twin_appic_tokens.cbor"),
+            Some(Ok((ExportCollection::AppicBridgePairs, ExportFormat::Cbor)))
+        );
+        assert!(matches!(
+            parse_export_path("/export/minters.cbor"),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            parse_export_path("/export/icp_token_list.csv"),
+            Some(Err(_))
+        ));
+        assert_eq!(parse_export_path("/metrics"), None);
+    }
+
+    #[test]
+    fn should_expire_access_tokens() {
+        let mut random_bytes = [1; 32];
+        random_bytes[16..].fill(2);
+        let token = issue_access_token(&random_bytes, 0);
+        let export_id = export_id_of(&token, 0).unwrap();
+
+        assert_ne!(token, export_id);
+        assert!(is_valid_export_id(
+            &export_id,
+            EXPORT_ACCESS_TOKEN_TTL_NS - 1
+        ));
+        assert!(!is_valid_export_id(&export_id, EXPORT_ACCESS_TOKEN_TTL_NS));
+        assert!(!is_valid_export_id(&token, 0));
+        assert_eq!(export_id_of(&token, EXPORT_ACCESS_TOKEN_TTL_NS), None);
+        assert_eq!(export_id_of("unknown", 0), None);
+    }
+
+    #[test]
+    fn should_read_bearer_tokens() {
+        let headers = |value: &str| vec![("authorization".to_string(), value.to_string())];
+
+        assert_eq!(bearer_token(&headers("Bearer abc")), Some("abc"));
+        assert_eq!(bearer_token(&headers("Basic abc")), None);
+        assert_eq!(bearer_token(&[]), None);
+    }
+}
@@ -0,0 +1,131 @@
+// Daily totals of the minter fees and gas paid, per minter and bridged token.
+// Both are paid in the native token of the chain, regardless of the bridged token.
+
+use candid::{CandidType, Nat};
+use ic_ethereum_types::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::aggregate::Aggregate;
+use crate::bridge_stats::Granularity;
+use crate::endpoints::CandidChainId;
+use crate::numeric::Erc20TokenAmount;
+use crate::state::{ChainId, MinterKey, Operator};
+
+// Maximum number of rows returned by a single get_fee_revenue call
+pub const MAX_FEE_REVENUE_ROWS: usize = 5_000;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub struct FeeRevenueKey {
+    pub day_start: u64,
+    pub minter_key: MinterKey,
+    pub token: Address,
+}
+
+impl FeeRevenueKey {
+    pub fn new(minter_key: MinterKey, token: Address, timestamp: u64) -> Self {
+        Self {
+            day_start: Granularity::Daily.bucket_start(timestamp),
+            minter_key,
+            token,
+        }
+    }
+
+    /// Smallest key of the day starting at `day_start`.
+    pub fn first_of_day(day_start: u64) -> Self {
+        Self {
+            day_start,
+            minter_key: MinterKey(ChainId(0), Operator::DfinityCkEthMinter),
+            token: Address::ZERO,
+        }
+    }
+}
+
+// Number of transactions of a day, the minter fees and gas paid for them and their USD values,
+// in this order
+pub type FeeRevenue = Aggregate<(Erc20TokenAmount, Erc20TokenAmount), (f64, f64)>;
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GetFeeRevenueArgs {
+    // Inclusive start and exclusive end in nanoseconds since the epoch
+    pub from: u64,
+    pub to: u64,
+    pub chain_id: Option<CandidChainId>,
+    pub operator: Option<Operator>,
+    pub token: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Serialize)]
+pub struct FeeRevenueReport {
+    pub day_start: u64,
+    pub chain_id: CandidChainId,
+    pub operator: Operator,
+    pub token: String,
+    pub transaction_count: u64,
+    // Amounts in the smallest denomination of the chain's native token
+    pub minter_fees: Nat,
+    pub gas_fees: Nat,
+    // None if the native token price was not known for any of the transactions
+    pub minter_fees_usd: Option<f64>,
+    pub gas_fees_usd: Option<f64>,
+}
+
+impl GetFeeRevenueArgs {
+    pub fn matches(&self, key: &FeeRevenueKey, token: &Option<Address>) -> bool {
+        self.chain_id.as_ref().map_or(true, |chain_id| {
+            ChainId::from(chain_id) == key.minter_key.chain_id()
+        }) && self
+            .operator
+            .map_or(true, |operator| operator == key.minter_key.operator())
+            && token.map_or(true, |token| token == key.token)
+    }
+}
+
+impl From<(FeeRevenueKey, FeeRevenue)> for FeeRevenueReport {
+    fn from((key, revenue): (FeeRevenueKey, FeeRevenue)) -> Self {
+        let (minter_fees, gas_fees) = revenue.amounts;
+        let usd_values = revenue.priced_usd_values();
+        Self {
+            day_start: key.day_start,
+            chain_id: key.minter_key.chain_id().into(),
+            operator: key.minter_key.operator(),
+            token: key.token.to_string(),
+            transaction_count: revenue.count,
+            minter_fees: minter_fees.into(),
+            gas_fees: gas_fees.into(),
+            minter_fees_usd: usd_values.map(|(minter_fees_usd, _)| minter_fees_usd),
+            gas_fees_usd: usd_values.map(|(_, gas_fees_usd)| gas_fees_usd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_accumulate_fees() {
+        let revenue = FeeRevenue::default()
+            .add(
+                (
+                    Erc20TokenAmount::from(10_u64),
+                    Erc20TokenAmount::from(100_u64),
+                ),
+                Some((1.0, 10.0)),
+            )
+            .add(
+                (Erc20TokenAmount::from(10_u64), Erc20TokenAmount::ZERO),
+                None,
+            );
+
+        assert_eq!(revenue.count, 2);
+        assert_eq!(
+            revenue.amounts,
+            (
+                Erc20TokenAmount::from(20_u64),
+                Erc20TokenAmount::from(100_u64)
+            )
+        );
+        assert_eq!(revenue.usd_values, (1.0, 10.0));
+        assert_eq!(revenue.priced_count, 1);
+    }
+}
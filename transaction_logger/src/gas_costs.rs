@@ -0,0 +1,77 @@
+// Rolling history of the gas paid by finalized withdrawals, per chain and token kind,
+// used to quote the cost of a withdrawal before the user signs it
+
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+use crate::numeric::Erc20TokenAmount;
+use crate::rolling_samples::RollingSamples;
+use crate::state::{ChainId, Operator};
+
+// Number of most recent withdrawals the quotes are computed over
+pub const MAX_GAS_COST_SAMPLES: usize = 200;
+
+// ERC-20 transfers use more gas than native transfers, so they are tracked separately
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub enum TokenKind {
+    Native,
+    Erc20,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub struct GasCostKey {
+    pub chain_id: ChainId,
+    pub token_kind: TokenKind,
+}
+
+// Gas used times effective gas price
+pub type GasCostSamples = RollingSamples<Erc20TokenAmount, MAX_GAS_COST_SAMPLES>;
+
+impl GasCostSamples {
+    /// Returns the median and the 95th percentile of the recent gas costs.
+    pub fn percentiles(&self) -> Option<(Erc20TokenAmount, Erc20TokenAmount)> {
+        let [median, p95] = self.nearest_ranks([50, 95])?;
+        Some((median, p95))
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WithdrawalQuote {
+    pub operator: Operator,
+    // Amounts in the smallest denomination of the chain's native token, except `expected_received`
+    // which is denominated in the withdrawn token
+    pub minter_fee: Nat,
+    // Median and 95th percentile of the gas paid by recent withdrawals of the same kind,
+    // None until a withdrawal of that kind was finalized
+    pub expected_gas_cost: Option<Nat>,
+    pub max_gas_cost: Option<Nat>,
+    // Expected gas cost plus the minter fee
+    pub expected_native_fee: Option<Nat>,
+    // For native withdrawals the fee is deducted from the amount,
+    // ERC-20 withdrawals pay it with a separate native burn and receive the full amount
+    pub expected_received: Option<Nat>,
+    pub sample_count: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_recent_gas_costs() {
+        let samples = (1..=MAX_GAS_COST_SAMPLES as u64 + 100)
+            .fold(GasCostSamples::default(), |samples, cost| {
+                samples.push(Erc20TokenAmount::from(cost))
+            });
+
+        assert_eq!(samples.len(), MAX_GAS_COST_SAMPLES);
+        assert_eq!(
+            samples.percentiles(),
+            Some((
+                Erc20TokenAmount::from(200_u64),
+                Erc20TokenAmount::from(290_u64)
+            ))
+        );
+        assert_eq!(GasCostSamples::default().percentiles(), None);
+    }
+}
@@ -1,10 +1,20 @@
 use std::cell::RefCell;
 
+use candid::CandidType;
+use ic_canister_log::log;
+use ic_stable_structures::BTreeMap;
 use serde::{Deserialize, Serialize};
 
-use std::collections::HashSet;
+use std::collections::HashMap;
 
-#[derive(Clone, PartialEq, Hash, Debug, PartialOrd, Eq, Ord, Deserialize, Serialize, Copy)]
+use crate::logs::INFO;
+use crate::state::{task_runs_memory, StableMemory};
+
+const ONE_MINUTE_IN_NS: u64 = 60_000_000_000;
+
+#[derive(
+    Clone, PartialEq, Hash, Debug, PartialOrd, Eq, Ord, Deserialize, Serialize, Copy, CandidType,
+)]
 pub enum TaskType {
     RemoveUnverified,
     ScrapeEvents,
@@ -12,10 +22,63 @@ pub enum TaskType {
     UpdateIcpTokens,
     RemoveInvalidTokens,
     UpdateUsdPrice,
+    DetectStuckTransactions,
+}
+
+impl TaskType {
+    /// Maximum time a guard for this task can go without being renewed before it is
+    /// considered stale. Tasks renew their guard after every `await`, so a lease is only
+    /// stale if its task trapped, or if a single call outlived this time. Either way a
+    /// task that resumes after its lease was taken over stops at its next renewal.
+    pub fn max_hold_time_ns(&self) -> u64 {
+        match self {
+            TaskType::RemoveUnverified => 10 * ONE_MINUTE_IN_NS,
+            // Scraping is blocked during the initial token fetch, so leave enough room for it.
+            TaskType::ScrapeEvents => 30 * ONE_MINUTE_IN_NS,
+            TaskType::UpdateBridgePairs => 60 * ONE_MINUTE_IN_NS,
+            TaskType::UpdateIcpTokens => 2 * 60 * ONE_MINUTE_IN_NS,
+            TaskType::RemoveInvalidTokens => 6 * 60 * ONE_MINUTE_IN_NS,
+            TaskType::UpdateUsdPrice => 10 * ONE_MINUTE_IN_NS,
+            TaskType::DetectStuckTransactions => 10 * ONE_MINUTE_IN_NS,
+        }
+    }
+}
+
+/// A lease on a task, recording when its guard was acquired and last renewed.
+#[derive(Clone, PartialEq, Eq, Debug, Copy)]
+pub struct TaskLease {
+    pub acquired_at: u64,
+    pub renewed_at: u64,
+}
+
+impl TaskLease {
+    pub fn held_for(&self, now: u64) -> u64 {
+        now.saturating_sub(self.acquired_at)
+    }
+
+    pub fn is_stale(&self, task: TaskType, now: u64) -> bool {
+        now.saturating_sub(self.renewed_at) >= task.max_hold_time_ns()
+    }
 }
 
 thread_local! {
-    pub static ACTIVE_TASKS:RefCell<Option<HashSet<TaskType>>>=RefCell::new(Some(HashSet::default()));
+    pub static ACTIVE_TASKS:RefCell<Option<HashMap<TaskType, TaskLease>>>=RefCell::new(Some(HashMap::default()));
+
+    // Timestamp of the last run of each task that completed without trapping. Kept in stable
+    // memory so an upgrade does not make every task look stale until it runs again.
+    static LAST_SUCCESSFUL_RUNS: RefCell<BTreeMap<TaskType, u64, StableMemory>> =
+        RefCell::new(BTreeMap::init(task_runs_memory()));
+}
+
+/// Records that `task` completed successfully at the current time.
+pub fn record_task_success(task: TaskType) {
+    let now = ic_cdk::api::time();
+    LAST_SUCCESSFUL_RUNS.with(|runs| runs.borrow_mut().insert(task, now));
+}
+
+/// Returns the last successful run of every task, ordered by task.
+pub fn last_successful_runs() -> Vec<(TaskType, u64)> {
+    LAST_SUCCESSFUL_RUNS.with(|runs| runs.borrow().iter().collect())
 }
 
 /// Mutates (part of) the current state using `f`.
@@ -23,7 +86,7 @@ thread_local! {
 /// Panics if there is no state.
 pub fn mutate_active_tasks<F, R>(f: F) -> R
 where
-    F: FnOnce(&mut HashSet<TaskType>) -> R,
+    F: FnOnce(&mut HashMap<TaskType, TaskLease>) -> R,
 {
     ACTIVE_TASKS.with(|s| {
         f(s.borrow_mut()
@@ -32,22 +95,86 @@ where
     })
 }
 
+/// Returns all currently held leases, sorted by task type.
+pub fn active_tasks() -> Vec<(TaskType, TaskLease)> {
+    let mut tasks: Vec<(TaskType, TaskLease)> =
+        mutate_active_tasks(|active_tasks| active_tasks.iter().map(|(t, l)| (*t, *l)).collect());
+    tasks.sort_by_key(|(task, _lease)| *task);
+    tasks
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, PartialOrd, Ord)]
 pub struct TimerGuard {
     task: TaskType,
+    acquired_at: u64,
 }
 #[derive(Debug, PartialEq, Eq)]
 pub enum TimerGuardError {
     AlreadyProcessing,
+    // The lease went stale and was taken over by another run
+    LeaseLost,
 }
 
 impl TimerGuard {
     pub fn new(task: TaskType) -> Result<Self, TimerGuardError> {
+        let now = ic_cdk::api::time();
+        if let Some(lease) = mutate_active_tasks(|active_tasks| active_tasks.get(&task).copied()) {
+            if lease.is_stale(task, now) {
+                log!(
+                    INFO,
+                    "[TimerGuard] Taking over stale guard for {:?}, held for {} ns",
+                    task,
+                    lease.held_for(now)
+                );
+            }
+        }
+        Self::new_at(task, now)
+    }
+
+    /// Acquires the guard for `task` at time `now`.
+    /// A lease that outlived the task's maximum hold time is taken over.
+    pub fn new_at(task: TaskType, now: u64) -> Result<Self, TimerGuardError> {
         mutate_active_tasks(|active_tasks| {
-            if !active_tasks.insert(task) {
-                return Err(TimerGuardError::AlreadyProcessing);
+            if let Some(lease) = active_tasks.get(&task) {
+                if !lease.is_stale(task, now) {
+                    return Err(TimerGuardError::AlreadyProcessing);
+                }
+            }
+            active_tasks.insert(
+                task,
+                TaskLease {
+                    acquired_at: now,
+                    renewed_at: now,
+                },
+            );
+            Ok(Self {
+                task,
+                acquired_at: now,
+            })
+        })
+    }
+
+    /// Shows that the task is still alive, to be called after every `await`.
+    /// Fails if the lease was taken over in the meantime, in which case the task should stop.
+    pub fn renew(&self) -> Result<(), TimerGuardError> {
+        let result = self.renew_at(ic_cdk::api::time());
+        if result.is_err() {
+            log!(
+                INFO,
+                "[TimerGuard] Lease of {:?} was taken over, stopping",
+                self.task
+            );
+        }
+        result
+    }
+
+    pub fn renew_at(&self, now: u64) -> Result<(), TimerGuardError> {
+        mutate_active_tasks(|active_tasks| match active_tasks.get_mut(&self.task) {
+            Some(lease) if lease.acquired_at == self.acquired_at => {
+                lease.renewed_at = now;
+                Ok(())
             }
-            Ok(Self { task })
+            _ => Err(TimerGuardError::LeaseLost),
         })
     }
 }
@@ -55,7 +182,84 @@ impl TimerGuard {
 impl Drop for TimerGuard {
     fn drop(&mut self) {
         mutate_active_tasks(|active_tasks| {
-            active_tasks.remove(&self.task);
+            // Only release the lease if it was not taken over in the meantime.
+            if active_tasks
+                .get(&self.task)
+                .is_some_and(|lease| lease.acquired_at == self.acquired_at)
+            {
+                active_tasks.remove(&self.task);
+            }
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reject_second_guard_while_lease_is_fresh() {
+        let _guard = TimerGuard::new_at(TaskType::UpdateUsdPrice, 0).unwrap();
+        assert_eq!(
+            TimerGuard::new_at(TaskType::UpdateUsdPrice, 1),
+            Err(TimerGuardError::AlreadyProcessing)
+        );
+    }
+
+    #[test]
+    fn should_take_over_stale_guard() {
+        let max_hold = TaskType::RemoveUnverified.max_hold_time_ns();
+
+        // Simulates a task that trapped and never dropped its guard.
+        std::mem::forget(TimerGuard::new_at(TaskType::RemoveUnverified, 0).unwrap());
+
+        let guard = TimerGuard::new_at(TaskType::RemoveUnverified, max_hold).unwrap();
+        assert_eq!(
+            active_tasks(),
+            vec![(
+                TaskType::RemoveUnverified,
+                TaskLease {
+                    acquired_at: max_hold,
+                    renewed_at: max_hold,
+                }
+            )]
+        );
+
+        drop(guard);
+        assert!(active_tasks().is_empty());
+    }
+
+    #[test]
+    fn should_not_release_lease_taken_over_by_another_guard() {
+        let max_hold = TaskType::ScrapeEvents.max_hold_time_ns();
+
+        let stale_guard = TimerGuard::new_at(TaskType::ScrapeEvents, 0).unwrap();
+        let _fresh_guard = TimerGuard::new_at(TaskType::ScrapeEvents, max_hold).unwrap();
+
+        drop(stale_guard);
+        assert_eq!(
+            TimerGuard::new_at(TaskType::ScrapeEvents, max_hold + 1),
+            Err(TimerGuardError::AlreadyProcessing)
+        );
+    }
+
+    #[test]
+    fn should_not_take_over_renewed_lease() {
+        let max_hold = TaskType::UpdateIcpTokens.max_hold_time_ns();
+
+        // A long running task that keeps renewing its lease after every await
+        let guard = TimerGuard::new_at(TaskType::UpdateIcpTokens, 0).unwrap();
+        guard.renew_at(max_hold - 1).unwrap();
+        assert_eq!(
+            TimerGuard::new_at(TaskType::UpdateIcpTokens, max_hold),
+            Err(TimerGuardError::AlreadyProcessing)
+        );
+
+        // Once it stops renewing, its lease is taken over and it can not renew anymore
+        let _fresh_guard = TimerGuard::new_at(TaskType::UpdateIcpTokens, 2 * max_hold).unwrap();
+        assert_eq!(
+            guard.renew_at(2 * max_hold + 1),
+            Err(TimerGuardError::LeaseLost)
+        );
+    }
+}
@@ -0,0 +1,87 @@
+// HTTP responses whose body is too large for a single message.
+// The first page is returned by `http_request`, the following ones are pulled by the boundary
+// node through `http_request_streaming_callback` until no token is returned.
+
+use candid::{define_function, CandidType, Principal};
+use ic_canisters_http_types::HttpResponse;
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+
+use crate::export::ExportToken;
+use crate::statement::StatementToken;
+
+pub const STREAMING_CALLBACK_METHOD: &str = "http_request_streaming_callback";
+
+// Identifies the next page of a streamed response
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum StreamingToken {
+    Statement(StatementToken),
+    Export(ExportToken),
+}
+
+define_function!(pub StreamingCallback : (StreamingToken) -> (StreamingCallbackHttpResponse) query);
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum StreamingStrategy {
+    Callback {
+        callback: StreamingCallback,
+        token: StreamingToken,
+    },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StreamingCallbackHttpResponse {
+    pub body: ByteBuf,
+    pub token: Option<StreamingToken>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StreamingHttpResponse {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: ByteBuf,
+    pub streaming_strategy: Option<StreamingStrategy>,
+}
+
+impl StreamingHttpResponse {
+    // A first page that is followed by the page of `token`, if any
+    pub fn streamed(
+        canister_id: Principal,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+        token: Option<StreamingToken>,
+    ) -> Self {
+        Self {
+            status_code: 200,
+            headers,
+            body: ByteBuf::from(body),
+            streaming_strategy: token.map(|token| StreamingStrategy::Callback {
+                callback: StreamingCallback::new(
+                    canister_id,
+                    STREAMING_CALLBACK_METHOD.to_string(),
+                ),
+                token,
+            }),
+        }
+    }
+
+    pub fn error(status_code: u16, message: impl Into<String>) -> Self {
+        Self {
+            status_code,
+            headers: vec![],
+            body: ByteBuf::from(message.into().into_bytes()),
+            streaming_strategy: None,
+        }
+    }
+}
+
+impl From<HttpResponse> for StreamingHttpResponse {
+    fn from(response: HttpResponse) -> Self {
+        Self {
+            status_code: response.status_code,
+            headers: response.headers,
+            body: response.body,
+            streaming_strategy: None,
+        }
+    }
+}
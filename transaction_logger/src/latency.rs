@@ -0,0 +1,63 @@
+// Rolling percentiles of how long transactions take to complete, from Accepted to Minted
+// for deposits and from Accepted to Successful for withdrawals
+
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use crate::bridge_stats::BridgeDirection;
+use crate::rolling_samples::RollingSamples;
+use crate::state::{ChainId, Operator};
+
+// Number of most recent completions the percentiles are computed over
+pub const MAX_LATENCY_SAMPLES: usize = 500;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub struct LatencyKey {
+    pub chain_id: ChainId,
+    pub operator: Operator,
+    pub direction: BridgeDirection,
+}
+
+// Durations in nanoseconds
+pub type LatencySamples = RollingSamples<u64, MAX_LATENCY_SAMPLES>;
+
+impl LatencySamples {
+    pub fn estimate(&self) -> Option<EstimatedCompletion> {
+        let [p50_ns, p95_ns] = self.nearest_ranks([50, 95])?;
+        Some(EstimatedCompletion {
+            p50_ns,
+            p95_ns,
+            sample_count: self.len() as u64,
+        })
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EstimatedCompletion {
+    // Time from acceptance to completion in nanoseconds
+    pub p50_ns: u64,
+    pub p95_ns: u64,
+    pub sample_count: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_compute_percentiles_over_recent_samples() {
+        let samples = (1..=100).fold(LatencySamples::default(), |samples, duration| {
+            samples.push(duration)
+        });
+        assert_eq!(
+            samples.estimate(),
+            Some(EstimatedCompletion {
+                p50_ns: 50,
+                p95_ns: 95,
+                sample_count: 100,
+            })
+        );
+
+        assert_eq!(LatencySamples::default().estimate(), None);
+    }
+}
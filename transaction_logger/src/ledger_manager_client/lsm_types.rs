@@ -167,11 +167,11 @@ impl From<LedgerManagerInfo> for EvmIcpBridgePairs {
         let mapped_pairs: Vec<(Erc20Identifier, Principal)> = value
             .managed_canisters
             .into_iter()
+            // Pairs with a malformed contract address are skipped rather than trapping the task
             .filter_map(|canisters| match canisters.ledger {
                 Some(ledger_id) => Some((
                     Erc20Identifier(
-                        Address::from_str(&canisters.erc20_contract.address)
-                            .expect("The response comes from the canister and it should not fail"),
+                        Address::from_str(&canisters.erc20_contract.address).ok()?,
                         StateChainId::from(&canisters.erc20_contract.chain_id),
                     ),
                     ledger_id.into(),
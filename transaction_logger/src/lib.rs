@@ -1,18 +1,35 @@
 use std::time::Duration;
 
+pub mod account_summary;
 pub mod add_evm_tokens;
+pub mod aggregate;
+pub mod bridge_stats;
 pub mod checked_amount;
+pub mod deposit_verification;
 pub mod endpoints;
+pub mod export;
+pub mod fee_revenue;
+pub mod gas_costs;
 pub mod guard;
+pub mod http_streaming;
 pub mod icp_tokens_service;
+pub mod latency;
 pub mod ledger_manager_client;
 pub mod lifecycle;
+pub mod logger_events;
 pub mod logs;
+pub mod metrics;
 pub mod minter_clinet;
 pub mod numeric;
+pub mod recent_activity;
 pub mod remove_unverified_tx;
+pub mod rolling_samples;
 pub mod scrape_events;
+pub mod search;
 pub mod state;
+pub mod statement;
+pub mod stuck_transactions;
+pub mod token_activity;
 pub mod update_bridge_pairs;
 pub mod update_icp_tokens;
 
@@ -25,6 +42,9 @@ pub const UPDATE_USD_PRICE: Duration = Duration::from_secs(5 * 60);
 // 1 Day
 pub const REMOVE_UNVERIFIED_TX: Duration = Duration::from_secs(1 * 60 * 60);
 
+// 10 Minutes
+pub const DETECT_STUCK_TX: Duration = Duration::from_secs(10 * 60);
+
 // 1 Day
 pub const UPDATE_BRIDGE_PAIRS: Duration = Duration::from_secs(24 * 60 * 60);
 
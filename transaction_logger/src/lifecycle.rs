@@ -5,9 +5,14 @@ use crate::state::nat_to_erc20_amount;
 use crate::state::ChainId;
 use crate::state::Minter;
 
+use crate::logger_events::certify_logger_events_tip;
 use crate::state::mutate_state;
+use crate::state::read_state;
 use crate::state::MinterKey;
+use crate::state::{LegacyMigration, LEGACY_MIGRATION_BATCH};
+use crate::stuck_transactions::StuckThresholds;
 use ic_canister_log::log;
+use std::time::Duration;
 
 pub fn init(init_args: InitArgs) {
     let minters_iter = init_args
@@ -18,9 +23,33 @@ pub fn init(init_args: InitArgs) {
     for minter in minters_iter {
         mutate_state(|s| s.record_minter(minter));
     }
+
+    // Nothing to migrate, marks the migration as complete so scraping can start
+    migrate_legacy_transactions();
+}
+
+// Moves a batch of transactions stored by the first release to the current maps and
+// schedules the next batch until none is left. Moved transactions are indexed as they are
+// written, so no index needs a rebuild afterwards.
+pub fn migrate_legacy_transactions() {
+    let migration = mutate_state(|s| s.migrate_legacy_transactions(LEGACY_MIGRATION_BATCH));
+    if migration != LegacyMigration::default() {
+        log!(
+            INFO,
+            "[upgrade]: migrated {} deposits and {} withdrawals to the current layout, dropped {} records",
+            migration.deposits,
+            migration.withdrawals,
+            migration.dropped
+        );
+    }
+    if migration.remaining {
+        ic_cdk_timers::set_timer(Duration::ZERO, migrate_legacy_transactions);
+    }
 }
 
 pub fn post_upgrade(upgrade_arg: Option<UpgradeArg>) {
+    migrate_legacy_transactions();
+
     if let Some(args) = upgrade_arg {
         log!(INFO, "[upgrade]: upgrading logger with arg: {:?}", args);
 
@@ -42,6 +71,22 @@ pub fn post_upgrade(upgrade_arg: Option<UpgradeArg>) {
                     update_minter_args.operator,
                 );
 
+                let effective_from = match (
+                    update_minter_args.fees_effective_from_event,
+                    update_minter_args.fees_effective_from,
+                ) {
+                    (Some(event), Some(timestamp)) => Some((event, timestamp)),
+                    (None, None) => None,
+                    _ => {
+                        log!(
+                            INFO,
+                            "[upgrade]: ignoring the fee update of minter {:?}: fees_effective_from and fees_effective_from_event must be set together",
+                            minter_key
+                        );
+                        continue;
+                    }
+                };
+
                 log!(
                     INFO,
                     "[init]: updating minter {:?} with args: {:?}",
@@ -53,9 +98,29 @@ pub fn post_upgrade(upgrade_arg: Option<UpgradeArg>) {
                         &minter_key,
                         nat_to_erc20_amount(update_minter_args.evm_to_icp_fee),
                         nat_to_erc20_amount(update_minter_args.icp_to_evm_fee),
+                        effective_from,
+                    )
+                });
+            }
+        }
+
+        if let Some(stuck_thresholds) = args.stuck_thresholds {
+            for thresholds in stuck_thresholds {
+                log!(
+                    INFO,
+                    "[upgrade]: setting stuck transaction thresholds: {:?}",
+                    thresholds
+                );
+                mutate_state(|s| {
+                    s.set_stuck_thresholds(
+                        ChainId::from(&thresholds.chain_id),
+                        StuckThresholds::from(&thresholds),
                     )
                 });
             }
         }
     }
+
+    // Certified data is cleared on upgrade
+    read_state(|s| certify_logger_events_tip(s.logger_events_tip_hash()));
 }
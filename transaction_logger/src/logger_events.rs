@@ -0,0 +1,486 @@
+// Hash-chained log of every change the logger makes to its state.
+// Each entry is hashed as an ICRC-3 block, so auditors can verify the chain
+// and replay how any record reached its current state.
+//
+// Derived data such as indexes, statistics and scraping activity is not logged,
+// since it can be rebuilt by replaying the log. Token USD prices are market data refreshed
+// every few minutes, they are not logged either so the log only grows with records and
+// configuration changes.
+
+use candid::{CandidType, Nat, Principal};
+use icrc_ledger_types::icrc::generic_value::ICRC3Value;
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use std::collections::BTreeMap;
+
+use crate::deposit_verification::CandidDepositDiscrepancy;
+use crate::endpoints::{
+    CandidEvmToIcp, CandidEvmToken, CandidFeeScheduleEntry, CandidIcpToEvm, CandidIcpToken,
+    CandidLedgerBurn, CandidReimbursement, MinterArgs, TokenPair,
+};
+use crate::state::{read_state, EvmToIcpStatus, IcpToEvmStatus, Operator};
+use crate::stuck_transactions::StuckThresholdArgs;
+
+pub type Hash = [u8; 32];
+
+// Maximum number of events returned by a single get_logger_events call
+pub const MAX_EVENTS_PER_RESPONSE: u64 = 100;
+
+/// The reason the logger changed its state.
+#[derive(CandidType, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum ChangeSource {
+    MinterEvent,
+    UserSubmission { caller: Principal },
+    Cleanup,
+    TokenUpdate,
+    AdminAction,
+    Migration,
+}
+
+/// The new value of a record, or the key of a removed one.
+#[derive(CandidType, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum StateChange {
+    RecordedMinter(MinterArgs),
+    RecordedEvmToIcp(LoggedEvmToIcp),
+    RemovedEvmToIcp {
+        transaction_hash: String,
+        chain_id: Nat,
+    },
+    RecordedIcpToEvm(LoggedIcpToEvm),
+    RemovedIcpToEvm {
+        native_ledger_burn_index: Nat,
+        chain_id: Nat,
+    },
+    RecordedEvmToken(CandidEvmToken),
+    RecordedIcpToken(CandidIcpToken),
+    RemovedIcpToken {
+        ledger_id: Principal,
+    },
+    RecordedBridgePair(TokenPair),
+    RecordedMinterFees {
+        chain_id: Nat,
+        operator: Operator,
+        entry: CandidFeeScheduleEntry,
+    },
+    RecordedStuckThresholds(StuckThresholdArgs),
+    RecordedDepositSubmission {
+        transaction_hash: String,
+        chain_id: Nat,
+        caller: Principal,
+        submitted_at: u64,
+    },
+    RemovedDepositSubmission {
+        transaction_hash: String,
+        chain_id: Nat,
+    },
+    RecordedDepositDiscrepancy(CandidDepositDiscrepancy),
+}
+
+impl StateChange {
+    /// Block type of the change, used as the ICRC-3 `btype`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StateChange::RecordedMinter(_) => "minter",
+            StateChange::RecordedEvmToIcp(_) => "evm_to_icp",
+            StateChange::RemovedEvmToIcp { .. } => "evm_to_icp_removed",
+            StateChange::RecordedIcpToEvm(_) => "icp_to_evm",
+            StateChange::RemovedIcpToEvm { .. } => "icp_to_evm_removed",
+            StateChange::RecordedEvmToken(_) => "evm_token",
+            StateChange::RecordedIcpToken(_) => "icp_token",
+            StateChange::RemovedIcpToken { .. } => "icp_token_removed",
+            StateChange::RecordedBridgePair(_) => "bridge_pair",
+            StateChange::RecordedMinterFees { .. } => "minter_fees",
+            StateChange::RecordedStuckThresholds(_) => "stuck_thresholds",
+            StateChange::RecordedDepositSubmission { .. } => "deposit_submission",
+            StateChange::RemovedDepositSubmission { .. } => "deposit_submission_removed",
+            StateChange::RecordedDepositDiscrepancy(_) => "deposit_discrepancy",
+        }
+    }
+}
+
+/// A deposit as recorded in the event log.
+///
+/// Kept apart from `CandidEvmToIcp` so that events logged by earlier versions keep decoding
+/// when the query types change. Fields can only be added as `opt`.
+#[derive(CandidType, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LoggedEvmToIcp {
+    pub from_address: String,
+    pub transaction_hash: String,
+    pub value: Nat,
+    pub block_number: Option<Nat>,
+    pub ledger_mint_index: Option<Nat>,
+    pub actual_received: Option<Nat>,
+    pub principal: Principal,
+    pub subaccount: Option<[u8; 32]>,
+    pub chain_id: Nat,
+    pub total_gas_spent: Option<Nat>,
+    pub erc20_contract_address: String,
+    pub icrc_ledger_id: Option<Principal>,
+    pub status: EvmToIcpStatus,
+    pub verified: bool,
+    pub operator: Operator,
+    pub submitted_at: Option<u64>,
+    pub accepted_at: Option<u64>,
+    pub minted_at: Option<u64>,
+    pub accepted_usd_price: Option<String>,
+    pub accepted_usd_value: Option<String>,
+}
+
+impl From<CandidEvmToIcp> for LoggedEvmToIcp {
+    fn from(value: CandidEvmToIcp) -> Self {
+        Self {
+            from_address: value.from_address,
+            transaction_hash: value.transaction_hash,
+            value: value.value,
+            block_number: value.block_number,
+            ledger_mint_index: value.ledger_mint_index,
+            actual_received: value.actual_received,
+            principal: value.principal,
+            subaccount: value.subaccount,
+            chain_id: value.chain_id,
+            total_gas_spent: value.total_gas_spent,
+            erc20_contract_address: value.erc20_contract_address,
+            icrc_ledger_id: value.icrc_ledger_id,
+            status: value.status,
+            verified: value.verified,
+            operator: value.operator,
+            submitted_at: value.submitted_at,
+            accepted_at: value.accepted_at,
+            minted_at: value.minted_at,
+            accepted_usd_price: value.accepted_usd_price,
+            accepted_usd_value: value.accepted_usd_value,
+        }
+    }
+}
+
+/// A withdrawal as recorded in the event log, see `LoggedEvmToIcp`.
+#[derive(CandidType, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LoggedIcpToEvm {
+    pub transaction_hash: Option<String>,
+    pub native_ledger_burn_index: Nat,
+    pub withdrawal_amount: Nat,
+    pub actual_received: Option<Nat>,
+    pub destination: String,
+    pub from: Principal,
+    pub from_subaccount: Option<[u8; 32]>,
+    pub max_transaction_fee: Option<Nat>,
+    pub effective_gas_price: Option<Nat>,
+    pub gas_used: Option<Nat>,
+    pub total_gas_spent: Option<Nat>,
+    pub erc20_ledger_burn_index: Option<Nat>,
+    pub erc20_contract_address: String,
+    pub icrc_ledger_id: Option<Principal>,
+    pub verified: bool,
+    pub status: IcpToEvmStatus,
+    pub operator: Operator,
+    pub chain_id: Nat,
+    pub submitted_at: Option<u64>,
+    pub accepted_at: Option<u64>,
+    pub finalized_at: Option<u64>,
+    pub reimbursed_at: Option<u64>,
+    pub accepted_usd_price: Option<String>,
+    pub accepted_usd_value: Option<String>,
+    pub erc20_burn: Option<CandidLedgerBurn>,
+    pub native_fee_burn: Option<CandidLedgerBurn>,
+    pub native_reimbursement: Option<CandidReimbursement>,
+    pub erc20_reimbursement: Option<CandidReimbursement>,
+    pub native_fee_paid: Option<Nat>,
+}
+
+impl From<CandidIcpToEvm> for LoggedIcpToEvm {
+    fn from(value: CandidIcpToEvm) -> Self {
+        Self {
+            transaction_hash: value.transaction_hash,
+            native_ledger_burn_index: value.native_ledger_burn_index,
+            withdrawal_amount: value.withdrawal_amount,
+            actual_received: value.actual_received,
+            destination: value.destination,
+            from: value.from,
+            from_subaccount: value.from_subaccount,
+            max_transaction_fee: value.max_transaction_fee,
+            effective_gas_price: value.effective_gas_price,
+            gas_used: value.gas_used,
+            total_gas_spent: value.total_gas_spent,
+            erc20_ledger_burn_index: value.erc20_ledger_burn_index,
+            erc20_contract_address: value.erc20_contract_address,
+            icrc_ledger_id: value.icrc_ledger_id,
+            verified: value.verified,
+            status: value.status,
+            operator: value.operator,
+            chain_id: value.chain_id,
+            submitted_at: value.submitted_at,
+            accepted_at: value.accepted_at,
+            finalized_at: value.finalized_at,
+            reimbursed_at: value.reimbursed_at,
+            accepted_usd_price: value.accepted_usd_price,
+            accepted_usd_value: value.accepted_usd_value,
+            erc20_burn: value.erc20_burn,
+            native_fee_burn: value.native_fee_burn,
+            native_reimbursement: value.native_reimbursement,
+            erc20_reimbursement: value.erc20_reimbursement,
+            native_fee_paid: value.native_fee_paid,
+        }
+    }
+}
+
+/// Candid encoded as the payload of a logger event.
+#[derive(CandidType, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LoggerEventPayload {
+    pub source: ChangeSource,
+    pub change: StateChange,
+}
+
+/// An entry of the logger's event log as it is kept in stable memory.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LoggerEvent {
+    pub timestamp: u64,
+    pub kind: String,
+    pub parent_hash: Option<Hash>,
+    pub payload: Vec<u8>,
+}
+
+impl LoggerEvent {
+    pub fn new(
+        timestamp: u64,
+        parent_hash: Option<Hash>,
+        source: ChangeSource,
+        change: StateChange,
+    ) -> Self {
+        let kind = change.kind().to_string();
+        let payload = candid::encode_one(LoggerEventPayload { source, change })
+            .expect("BUG: failed to encode logger event payload");
+        Self {
+            timestamp,
+            kind,
+            parent_hash,
+            payload,
+        }
+    }
+
+    /// Returns the ICRC-3 block representation of this event.
+    pub fn to_block(&self) -> ICRC3Value {
+        let mut block = BTreeMap::new();
+        if let Some(parent_hash) = self.parent_hash {
+            block.insert(
+                "phash".to_string(),
+                ICRC3Value::Blob(ByteBuf::from(parent_hash.to_vec())),
+            );
+        }
+        block.insert("ts".to_string(), ICRC3Value::Nat(Nat::from(self.timestamp)));
+        block.insert("btype".to_string(), ICRC3Value::Text(self.kind.clone()));
+        block.insert(
+            "payload".to_string(),
+            ICRC3Value::Blob(ByteBuf::from(self.payload.clone())),
+        );
+        ICRC3Value::Map(block)
+    }
+
+    pub fn hash(&self) -> Hash {
+        self.to_block().hash()
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GetLoggerEventsArgs {
+    pub start: u64,
+    pub length: u64,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CandidLoggerEvent {
+    pub index: u64,
+    pub timestamp: u64,
+    pub kind: String,
+    pub parent_hash: Option<ByteBuf>,
+    // Candid encoded `LoggerEventPayload`
+    pub payload: ByteBuf,
+    pub hash: ByteBuf,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GetLoggerEventsResult {
+    pub events: Vec<CandidLoggerEvent>,
+    pub total_event_count: u64,
+    // Hash of the last event, certified by the canister
+    pub tip_hash: Option<ByteBuf>,
+    pub certificate: Option<ByteBuf>,
+}
+
+pub fn get_logger_events(args: GetLoggerEventsArgs) -> GetLoggerEventsResult {
+    read_state(|s| {
+        let total_event_count = s.logger_events.len();
+        let end = args
+            .start
+            .saturating_add(args.length.min(MAX_EVENTS_PER_RESPONSE))
+            .min(total_event_count);
+
+        let events = (args.start..end)
+            .filter_map(|index| {
+                s.logger_events.get(index).map(|event| CandidLoggerEvent {
+                    index,
+                    timestamp: event.timestamp,
+                    kind: event.kind.clone(),
+                    parent_hash: event.parent_hash.map(|hash| ByteBuf::from(hash.to_vec())),
+                    hash: ByteBuf::from(event.hash().to_vec()),
+                    payload: ByteBuf::from(event.payload),
+                })
+            })
+            .collect();
+
+        GetLoggerEventsResult {
+            events,
+            total_event_count,
+            tip_hash: s
+                .logger_events_tip_hash()
+                .map(|hash| ByteBuf::from(hash.to_vec())),
+            certificate: ic_cdk::api::data_certificate().map(ByteBuf::from),
+        }
+    })
+}
+
+/// Sets the certified data of the canister to the hash of the last logger event.
+pub fn certify_logger_events_tip(tip_hash: Option<Hash>) {
+    // Certified data can only be set inside a canister
+    #[cfg(target_arch = "wasm32")]
+    ic_cdk::api::set_certified_data(&tip_hash.unwrap_or_default());
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = tip_hash;
+}
+
+/// Returns the current time in nanoseconds since the epoch.
+pub fn current_time() -> u64 {
+    // ic_cdk::api::time traps outside of a canister, so unit tests use the system clock
+    #[cfg(target_arch = "wasm32")]
+    return ic_cdk::api::time();
+    #[cfg(not(target_arch = "wasm32"))]
+    return std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("BUG: system time before epoch")
+        .as_nanos() as u64;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_removed(timestamp: u64, parent_hash: Option<Hash>) -> LoggerEvent {
+        LoggerEvent::new(
+            timestamp,
+            parent_hash,
+            ChangeSource::Cleanup,
+            StateChange::RemovedIcpToken {
+                ledger_id: Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap(),
+            },
+        )
+    }
+
+    #[test]
+    fn should_chain_event_hashes() {
+        let first = token_removed(1, None);
+        let second = token_removed(2, Some(first.hash()));
+
+        assert_eq!(second.parent_hash, Some(first.hash()));
+        assert_ne!(first.hash(), second.hash());
+
+        // Changing the parent changes the hash of the child
+        let forged = token_removed(2, Some([0; 32]));
+        assert_ne!(forged.hash(), second.hash());
+    }
+
+    #[test]
+    fn should_decode_payload() {
+        let event = token_removed(1, None);
+        let payload: LoggerEventPayload = candid::decode_one(&event.payload).unwrap();
+
+        assert_eq!(event.kind, "icp_token_removed");
+        assert_eq!(payload.source, ChangeSource::Cleanup);
+        assert_eq!(
+            payload.change,
+            StateChange::RemovedIcpToken {
+                ledger_id: Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn should_decode_deposits_logged_with_previous_layout() {
+        // A deposit as logged before ids, accounts and status timestamps were added
+        #[derive(CandidType)]
+        struct EvmToIcpV0 {
+            from_address: String,
+            transaction_hash: String,
+            value: Nat,
+            block_number: Option<Nat>,
+            ledger_mint_index: Option<Nat>,
+            actual_received: Option<Nat>,
+            principal: Principal,
+            subaccount: Option<[u8; 32]>,
+            chain_id: Nat,
+            total_gas_spent: Option<Nat>,
+            erc20_contract_address: String,
+            icrc_ledger_id: Option<Principal>,
+            status: EvmToIcpStatus,
+            verified: bool,
+            time: u64,
+            operator: Operator,
+        }
+        #[derive(CandidType)]
+        enum StateChangeV0 {
+            RecordedEvmToIcp(EvmToIcpV0),
+        }
+        #[derive(CandidType)]
+        struct LoggerEventPayloadV0 {
+            source: ChangeSource,
+            change: StateChangeV0,
+        }
+
+        let principal = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        let transaction_hash =
+            "0x8218f324b45a8cd36f38586b062e3884588d926035f08e1dcd3605160b3ebd42".to_string();
+        let encoded = candid::encode_one(LoggerEventPayloadV0 {
+            source: ChangeSource::MinterEvent,
+            change: StateChangeV0::RecordedEvmToIcp(EvmToIcpV0 {
+                from_address: "0xdd2851cdd40ae6536831558dd46db62fac7a844d".to_string(),
+                transaction_hash: transaction_hash.clone(),
+                value: Nat::from(1_000_u64),
+                block_number: Some(Nat::from(42_u64)),
+                ledger_mint_index: Some(Nat::from(7_u64)),
+                actual_received: Some(Nat::from(990_u64)),
+                principal,
+                subaccount: None,
+                chain_id: Nat::from(56_u64),
+                total_gas_spent: None,
+                erc20_contract_address: "0x0000000000000000000000000000000000000000".to_string(),
+                icrc_ledger_id: Some(principal),
+                status: EvmToIcpStatus::Minted,
+                verified: true,
+                time: 1_700_000_000_000_000_000,
+                operator: Operator::AppicMinter,
+            }),
+        })
+        .unwrap();
+
+        let payload: LoggerEventPayload = candid::decode_one(&encoded).unwrap();
+
+        let StateChange::RecordedEvmToIcp(deposit) = payload.change else {
+            panic!("expected a recorded deposit, got {:?}", payload.change);
+        };
+        assert_eq!(deposit.transaction_hash, transaction_hash);
+        assert_eq!(deposit.ledger_mint_index, Some(Nat::from(7_u64)));
+        assert_eq!(deposit.status, EvmToIcpStatus::Minted);
+        assert_eq!(deposit.submitted_at, None);
+        assert_eq!(deposit.accepted_usd_price, None);
+    }
+
+    #[test]
+    fn should_omit_parent_hash_of_first_block() {
+        let ICRC3Value::Map(block) = token_removed(1, None).to_block() else {
+            panic!("block should be a map");
+        };
+        assert!(!block.contains_key("phash"));
+        assert_eq!(
+            block.get("btype"),
+            Some(&ICRC3Value::Text("icp_token_removed".into()))
+        );
+    }
+}
@@ -57,6 +57,29 @@ impl FromStr for Sort {
     }
 }
 
+/// Decodes a percent-encoded query parameter value, where `+` stands for a space.
+pub fn decode_query_value(value: &str) -> Result<String, String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+    while let Some(byte) = input.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [input.next(), input.next()];
+                let decoded = match hex {
+                    [Some(high), Some(low)] => std::str::from_utf8(&[high, low])
+                        .ok()
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+                    _ => None,
+                };
+                bytes.push(decoded.ok_or_else(|| "invalid percent-encoding".to_string())?);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| "decoded value is not valid UTF-8".to_string())
+}
+
 #[derive(Clone, serde::Serialize, Deserialize, Debug)]
 pub struct LogEntry {
     pub timestamp: u64,
@@ -119,6 +142,21 @@ impl Log {
         entries_json
     }
 
+    /// Keeps entries logged at or after `timestamp`.
+    pub fn retain_since(&mut self, timestamp: u64) {
+        self.entries.retain(|entry| entry.timestamp >= timestamp);
+    }
+
+    /// Keeps entries whose message contains `pattern`.
+    pub fn retain_containing(&mut self, pattern: &str) {
+        self.entries.retain(|entry| entry.message.contains(pattern));
+    }
+
+    /// Keeps entries logged from a source file whose path contains `file`.
+    pub fn retain_file(&mut self, file: &str) {
+        self.entries.retain(|entry| entry.file.contains(file));
+    }
+
     pub fn sort_logs(&mut self, sort_order: Sort) {
         match sort_order {
             Sort::Ascending => self.sort_asc(),
@@ -137,7 +175,7 @@ impl Log {
 
 #[cfg(test)]
 mod tests {
-    use crate::logs::{Log, LogEntry, Priority, Sort};
+    use crate::logs::{decode_query_value, Log, LogEntry, Priority, Sort};
     use proptest::{prop_assert, proptest};
 
     fn info_log_entry_with_timestamp(timestamp: u64) -> LogEntry {
@@ -212,6 +250,55 @@ mod tests {
         assert!(is_descending(&log));
     }
 
+    #[test]
+    fn filtering_entries() {
+        let mut log = Log { entries: vec![] };
+        for (timestamp, file, message) in [
+            (0, "src/scrape_events.rs", "Scraping events from 0 to 99"),
+            (1, "src/update_icp_tokens.rs", "Updating tokens"),
+            (2, "src/scrape_events.rs", "No events to scrape"),
+        ] {
+            log.entries.push(LogEntry {
+                file: file.to_string(),
+                message: message.to_string(),
+                ..info_log_entry_with_timestamp(timestamp)
+            });
+        }
+
+        let mut since = log.clone();
+        since.retain_since(1);
+        assert_eq!(since.entries.len(), 2);
+
+        let mut containing = log.clone();
+        containing.retain_containing("Scraping");
+        assert_eq!(containing.entries.len(), 1);
+        assert_eq!(containing.entries[0].timestamp, 0);
+
+        let mut file = log.clone();
+        file.retain_file("scrape_events");
+        assert_eq!(file.entries.len(), 2);
+
+        file.retain_since(1);
+        assert_eq!(file.entries.len(), 1);
+        assert_eq!(file.entries[0].timestamp, 2);
+    }
+
+    #[test]
+    fn decoding_query_values() {
+        assert_eq!(
+            decode_query_value("No+events%20to%20scrape"),
+            Ok("No events to scrape".to_string())
+        );
+        assert_eq!(
+            decode_query_value("src%2Fscrape_events.rs"),
+            Ok("src/scrape_events.rs".to_string())
+        );
+        assert_eq!(decode_query_value("%E2%9C%93"), Ok("\u{2713}".to_string()));
+        assert!(decode_query_value("100%").is_err());
+        assert!(decode_query_value("%zz").is_err());
+        assert!(decode_query_value("%ff").is_err());
+    }
+
     #[test]
     fn simple_logs_truncation() {
         let mut entries: Vec<LogEntry> = vec![];
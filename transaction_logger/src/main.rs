@@ -1,29 +1,60 @@
 use std::str::FromStr;
 use std::time::Duration;
 
-use candid::Principal;
+use candid::{Nat, Principal};
 use ic_canister_log::log;
+use ic_canisters_http_types::{HttpRequest, HttpResponse, HttpResponseBuilder};
 use ic_cdk::{init, post_upgrade, query, update};
 use ic_cdk_timers;
 use ic_ethereum_types::Address;
+use icrc_ledger_types::icrc1::account::Account;
+use serde_bytes::ByteBuf;
+use transaction_logger::account_summary::AccountSummary;
 use transaction_logger::add_evm_tokens::add_evm_tokens_to_state;
+use transaction_logger::bridge_stats::{
+    BridgeDirection, BridgeStatsBucket, BridgeStatsFilters, BridgeStatsRange, Granularity,
+};
+use transaction_logger::deposit_verification::CandidDepositDiscrepancy;
 use transaction_logger::endpoints::{
-    AddEvmToIcpTx, AddEvmToIcpTxError, AddIcpToEvmTx, AddIcpToEvmTxError, CandidEvmToken,
-    CandidIcpToken, GetEvmTokenArgs, GetIcpTokenArgs, GetTxParams, Icrc28TrustedOriginsResponse,
-    TokenPair, Transaction,
+    ActiveTask, AddEvmToIcpTx, AddEvmToIcpTxError, AddIcpToEvmTx, AddIcpToEvmTxError,
+    CandidChainId, CandidEvmToken, CandidIcpToken, GetEvmTokenArgs, GetIcpTokenArgs, GetTxParams,
+    Icrc28TrustedOriginsResponse, LoggerQueryError, MinterFeeSchedule, MinterStatus, TokenPair,
+    Transaction, TransactionSearchParam,
+};
+use transaction_logger::export::{
+    bearer_token, export_id_of, is_valid_export_id, issue_access_token, parse_export_path,
+    render_export_page, ExportArgs, ExportCollection, ExportFormat, ExportPage, ExportToken,
+};
+use transaction_logger::fee_revenue::{FeeRevenueReport, GetFeeRevenueArgs};
+use transaction_logger::gas_costs::WithdrawalQuote;
+use transaction_logger::guard::{active_tasks, TaskType, TimerGuard};
+use transaction_logger::http_streaming::{
+    StreamingCallbackHttpResponse, StreamingHttpResponse, StreamingToken,
 };
-use transaction_logger::guard::{TaskType, TimerGuard};
+use transaction_logger::latency::EstimatedCompletion;
 use transaction_logger::lifecycle::{self, init as initialize};
+use transaction_logger::logger_events::{self, GetLoggerEventsArgs, GetLoggerEventsResult};
+use transaction_logger::logs::{decode_query_value, Log, Priority, Sort};
+use transaction_logger::metrics::encode_metrics;
+use transaction_logger::recent_activity::{
+    RecentActivityKey, RecentTransactions, RecentTransactionsFilters,
+};
+use transaction_logger::search::{SearchQuery, SearchResults};
 use transaction_logger::state::{
-    mutate_state, nat_to_erc20_amount, nat_to_ledger_burn_index, nat_to_u64, read_state, ChainId,
+    mutate_state, nat_to_erc20_amount, nat_to_ledger_burn_index, read_state, ChainId,
     Erc20Identifier, EvmToIcpStatus, EvmToIcpTx, EvmToIcpTxIdentifier, IcpToEvmIdentifier,
-    IcpToEvmStatus, IcpToEvmTx,
+    IcpToEvmStatus, IcpToEvmTx, Operator, TransactionHash, TransactionId,
+};
+use transaction_logger::statement::{
+    parse_statement_path, StatementFormat, StatementKey, StatementToken, STATEMENT_PAGE_SIZE,
 };
+use transaction_logger::stuck_transactions::{detect_stuck_transactions, CandidAlert};
+use transaction_logger::token_activity::{TokenIndex, TokenTransactions, TokenTransactionsFilters};
 use transaction_logger::update_icp_tokens::{update_icp_tokens, update_usd_price, validate_tokens};
 use transaction_logger::{
     endpoints::LoggerArgs, logs::INFO, remove_unverified_tx::remove_unverified_tx,
-    scrape_events::scrape_events, update_bridge_pairs::update_bridge_pairs, REMOVE_UNVERIFIED_TX,
-    SCRAPE_EVENTS, UPDATE_BRIDGE_PAIRS,
+    scrape_events::scrape_events, update_bridge_pairs::update_bridge_pairs, DETECT_STUCK_TX,
+    REMOVE_UNVERIFIED_TX, SCRAPE_EVENTS, UPDATE_BRIDGE_PAIRS,
 };
 use transaction_logger::{REMOVE_INVALID_ICP_TOKENS, UPDATE_ICP_TOKENS, UPDATE_USD_PRICE};
 // Setup timers
@@ -37,6 +68,9 @@ fn setup_timers() {
     // Remove unverified transactions
     ic_cdk_timers::set_timer_interval(REMOVE_UNVERIFIED_TX, || remove_unverified_tx());
 
+    // Raise and clear alerts of stuck transactions
+    ic_cdk_timers::set_timer_interval(DETECT_STUCK_TX, || detect_stuck_transactions());
+
     // Check new supported twin tokens
     ic_cdk_timers::set_timer_interval(UPDATE_BRIDGE_PAIRS, || ic_cdk::spawn(update_bridge_pairs()));
 
@@ -81,11 +115,17 @@ fn prepare_canister_state() {
 pub async fn get_icp_tokens_and_bridge_pairs() {
     // Ensures that scraping events will be blocked and
     // All tokens are added to cansiter state
-    let _guard_scraping_events: TimerGuard =
+    let guard_scraping_events: TimerGuard =
         TimerGuard::new(TaskType::ScrapeEvents).expect("No guard should exsist at this point");
 
     update_icp_tokens().await;
+    if guard_scraping_events.renew().is_err() {
+        return;
+    }
     update_usd_price().await;
+    if guard_scraping_events.renew().is_err() {
+        return;
+    }
     update_bridge_pairs().await;
 }
 
@@ -146,7 +186,10 @@ fn new_icp_to_evm_tx(tx: AddIcpToEvmTx) -> Result<(), AddIcpToEvmTxError> {
                 destination,
                 from: tx.from,
                 from_subaccount: tx.from_subaccount,
-                time: nat_to_u64(&tx.time),
+                submitted_at: Some(ic_cdk::api::time()),
+                accepted_at: None,
+                finalized_at: None,
+                reimbursed_at: None,
                 max_transaction_fee: Some(nat_to_erc20_amount(tx.max_transaction_fee)),
                 effective_gas_price: None,
                 gas_used: None,
@@ -158,7 +201,12 @@ fn new_icp_to_evm_tx(tx: AddIcpToEvmTx) -> Result<(), AddIcpToEvmTxError> {
                 status: IcpToEvmStatus::PendingVerification,
                 operator: tx.operator,
                 chain_id,
+                accepted_usd_price: None,
+                accepted_usd_value: None,
+                native_reimbursement: None,
+                erc20_reimbursement: None,
             },
+            ic_cdk::caller(),
         )
     });
 
@@ -168,7 +216,7 @@ fn new_icp_to_evm_tx(tx: AddIcpToEvmTx) -> Result<(), AddIcpToEvmTxError> {
 // Add new evm to icp transaction
 #[update]
 fn new_evm_to_icp_tx(tx: AddEvmToIcpTx) -> Result<(), AddEvmToIcpTxError> {
-    let tx_identifier = EvmToIcpTxIdentifier::from(&tx);
+    let tx_identifier = EvmToIcpTxIdentifier::try_from(&tx)?;
     let chain_id = ChainId::from(&tx.chain_id);
 
     if let true = read_state(|s| s.if_evm_to_icp_tx_exists(&tx_identifier)) {
@@ -199,11 +247,13 @@ fn new_evm_to_icp_tx(tx: AddEvmToIcpTx) -> Result<(), AddEvmToIcpTxError> {
 
     mutate_state(|s| {
         s.record_new_evm_to_icp(
-            tx_identifier,
+            tx_identifier.clone(),
             EvmToIcpTx {
-                transaction_hash: tx.transaction_hash,
+                transaction_hash: *tx_identifier.transaction_hash(),
                 actual_received: None,
-                time: ic_cdk::api::time(),
+                submitted_at: Some(ic_cdk::api::time()),
+                accepted_at: None,
+                minted_at: None,
                 erc20_contract_address,
                 icrc_ledger_id: Some(icrc_pair),
                 ledger_mint_index: None,
@@ -217,7 +267,10 @@ fn new_evm_to_icp_tx(tx: AddEvmToIcpTx) -> Result<(), AddEvmToIcpTxError> {
                 subaccount: tx.subaccount,
                 chain_id,
                 total_gas_spent: Some(nat_to_erc20_amount(tx.total_gas_spent)),
+                accepted_usd_price: None,
+                accepted_usd_value: None,
             },
+            ic_cdk::caller(),
         )
     });
 
@@ -226,8 +279,13 @@ fn new_evm_to_icp_tx(tx: AddEvmToIcpTx) -> Result<(), AddEvmToIcpTxError> {
 
 #[query]
 pub fn get_txs_by_address(address: String) -> Vec<Transaction> {
-    let address = Address::from_str(&address).expect("Address should be valid");
-    read_state(|s| s.get_transaction_for_address(address))
+    get_txs_by_address_v2(address).unwrap_or_default()
+}
+
+#[query]
+pub fn get_txs_by_address_v2(address: String) -> Result<Vec<Transaction>, LoggerQueryError> {
+    let address = Address::from_str(&address).map_err(|_e| LoggerQueryError::InvalidAddress)?;
+    Ok(read_state(|s| s.get_transaction_for_address(address)))
 }
 
 #[query]
@@ -235,6 +293,26 @@ pub fn get_txs_by_principal(principal_id: Principal) -> Vec<Transaction> {
     read_state(|s| s.get_transaction_for_principal(principal_id))
 }
 
+// Gets the transactions of an ICRC-1 account, only the default subaccount matches
+// transactions without a subaccount
+#[query]
+pub fn get_txs_by_account(account: Account) -> Vec<Transaction> {
+    read_state(|s| s.get_transaction_for_account(account))
+}
+
+// Aggregates the bridge history of a principal into totals per chain, minter and token
+#[query]
+pub fn get_account_summary(principal_id: Principal) -> AccountSummary {
+    read_state(|s| s.get_account_summary(principal_id))
+}
+
+// Same as `get_txs_by_account`, for an account in the textual ICRC-1 form
+#[query]
+pub fn get_txs_by_account_text(account: String) -> Result<Vec<Transaction>, LoggerQueryError> {
+    let account = Account::from_str(&account).map_err(|_e| LoggerQueryError::InvalidAccount)?;
+    Ok(read_state(|s| s.get_transaction_for_account(account)))
+}
+
 #[query]
 pub fn get_bridge_pairs() -> Vec<TokenPair> {
     read_state(|s| s.get_suported_bridge_pairs())
@@ -242,42 +320,131 @@ pub fn get_bridge_pairs() -> Vec<TokenPair> {
 
 #[query]
 pub fn get_transaction(params: GetTxParams) -> Option<Transaction> {
+    get_transaction_v2(params).ok()
+}
+
+#[query]
+pub fn get_transaction_v2(params: GetTxParams) -> Result<Transaction, LoggerQueryError> {
     // Check if chain id is supported
     let chain_id = ChainId::from(&params.chain_id);
-    let chain_check_result = read_state(|s| s.if_chain_id_exists(chain_id));
+    check_chain_is_supported(chain_id)?;
 
-    if !chain_check_result {
-        return None;
+    if let TransactionSearchParam::TxHash(tx_hash) = &params.search_param {
+        TransactionHash::from_str(tx_hash).map_err(|_e| LoggerQueryError::InvalidHash)?;
     }
 
-    let search_result =
-        read_state(|s| s.get_transaction_by_search_params(params.search_param, chain_id));
+    read_state(|s| s.get_transaction_by_search_params(params.search_param, chain_id))
+        .ok_or(LoggerQueryError::NotFound)
+}
+
+// Gets a transaction by the `id` returned with it, so a client can follow it through its lifecycle
+#[query]
+pub fn get_transaction_by_id(id: String) -> Result<Transaction, LoggerQueryError> {
+    let id = TransactionId::from_str(&id).map_err(|_e| LoggerQueryError::InvalidTransactionId)?;
+    check_chain_is_supported(id.chain_id())?;
 
-    search_result
+    read_state(|s| s.get_transaction_by_id(&id)).ok_or(LoggerQueryError::NotFound)
 }
 
+// Gets the latest transfers across all users and chains, newest first.
+// `cursor` is the `next_cursor` of the previous page.
 #[query]
-pub fn get_evm_token(args: GetEvmTokenArgs) -> Option<CandidEvmToken> {
-    // Validate address and create identifier
-    let identifier = Erc20Identifier::new(
-        &Address::from_str(&args.address).expect("Wrong Address Provided"),
-        ChainId::from(&args.chain_id),
-    );
+pub fn get_recent_transactions(
+    limit: u32,
+    cursor: Option<String>,
+    filters: RecentTransactionsFilters,
+) -> Result<RecentTransactions, LoggerQueryError> {
+    let cursor = cursor
+        .map(|cursor| RecentActivityKey::from_str(&cursor))
+        .transpose()
+        .map_err(|_e| LoggerQueryError::InvalidCursor)?;
+    if let Some(chain_id) = &filters.chain_id {
+        check_chain_is_supported(ChainId::from(chain_id))?;
+    }
 
-    // Get token from state
-    let token = read_state(|s| s.get_evm_token_by_identifier(&identifier))?;
+    Ok(read_state(|s| {
+        s.get_recent_transactions(limit as usize, cursor, &filters)
+    }))
+}
 
-    // Return Token
-    Some(CandidEvmToken::from(token))
+// Gets the transfers of the token of an icrc ledger across all chains, newest first.
+// `cursor` is the `next_cursor` of the previous page.
+#[query]
+pub fn get_txs_by_icrc_ledger(
+    ledger_id: Principal,
+    limit: u32,
+    cursor: Option<String>,
+    filters: TokenTransactionsFilters,
+) -> Result<TokenTransactions, LoggerQueryError> {
+    let cursor = cursor
+        .map(|cursor| RecentActivityKey::from_str(&cursor))
+        .transpose()
+        .map_err(|_e| LoggerQueryError::InvalidCursor)?;
+
+    Ok(read_state(|s| {
+        s.get_token_transactions(
+            TokenIndex::IcrcLedger(ledger_id),
+            limit as usize,
+            cursor,
+            &filters,
+        )
+    }))
+}
+
+// Gets the transfers of an erc20 contract on a chain, newest first.
+// `cursor` is the `next_cursor` of the previous page.
+#[query]
+pub fn get_txs_by_erc20(
+    chain_id: CandidChainId,
+    address: String,
+    limit: u32,
+    cursor: Option<String>,
+    filters: TokenTransactionsFilters,
+) -> Result<TokenTransactions, LoggerQueryError> {
+    let chain_id = ChainId::from(&chain_id);
+    check_chain_is_supported(chain_id)?;
+    let address = Address::from_str(&address).map_err(|_e| LoggerQueryError::InvalidAddress)?;
+    let cursor = cursor
+        .map(|cursor| RecentActivityKey::from_str(&cursor))
+        .transpose()
+        .map_err(|_e| LoggerQueryError::InvalidCursor)?;
+
+    Ok(read_state(|s| {
+        s.get_token_transactions(
+            TokenIndex::Erc20(Erc20Identifier::new(&address, chain_id)),
+            limit as usize,
+            cursor,
+            &filters,
+        )
+    }))
+}
+
+#[query]
+pub fn get_evm_token(args: GetEvmTokenArgs) -> Option<CandidEvmToken> {
+    get_evm_token_v2(args).ok()
+}
+
+#[query]
+pub fn get_evm_token_v2(args: GetEvmTokenArgs) -> Result<CandidEvmToken, LoggerQueryError> {
+    let address =
+        Address::from_str(&args.address).map_err(|_e| LoggerQueryError::InvalidAddress)?;
+    let identifier = Erc20Identifier::new(&address, ChainId::from(&args.chain_id));
+
+    read_state(|s| s.get_evm_token_by_identifier(&identifier))
+        .map(CandidEvmToken::from)
+        .ok_or(LoggerQueryError::NotFound)
 }
 
 #[query]
 pub fn get_icp_token(args: GetIcpTokenArgs) -> Option<CandidIcpToken> {
-    // Get token from state
-    let token = read_state(|s| s.get_icp_token_by_principal(&args.ledger_id))?;
+    get_icp_token_v2(args).ok()
+}
 
-    // Return Token
-    Some(CandidIcpToken::from(token))
+#[query]
+pub fn get_icp_token_v2(args: GetIcpTokenArgs) -> Result<CandidIcpToken, LoggerQueryError> {
+    read_state(|s| s.get_icp_token_by_principal(&args.ledger_id))
+        .map(CandidIcpToken::from)
+        .ok_or(LoggerQueryError::NotFound)
 }
 
 #[query]
@@ -292,6 +459,387 @@ pub fn get_icp_tokens() -> Vec<CandidIcpToken> {
         .collect()
 }
 
+// Gets the bridged volume per chain, token, operator and direction as a time series
+#[query]
+pub fn get_bridge_stats(
+    range: BridgeStatsRange,
+    granularity: Granularity,
+    filters: BridgeStatsFilters,
+) -> Vec<BridgeStatsBucket> {
+    read_state(|s| s.get_bridge_stats(range, granularity, filters))
+}
+
+// Gets the p50 and p95 time from acceptance to completion of recent transactions
+#[query]
+pub fn get_estimated_completion(
+    chain_id: CandidChainId,
+    operator: Operator,
+    direction: BridgeDirection,
+) -> Option<EstimatedCompletion> {
+    get_estimated_completion_v2(chain_id, operator, direction).ok()
+}
+
+#[query]
+pub fn get_estimated_completion_v2(
+    chain_id: CandidChainId,
+    operator: Operator,
+    direction: BridgeDirection,
+) -> Result<EstimatedCompletion, LoggerQueryError> {
+    let chain_id = ChainId::from(&chain_id);
+    check_chain_is_supported(chain_id)?;
+
+    read_state(|s| s.get_estimated_completion(chain_id, operator, direction))
+        .ok_or(LoggerQueryError::NotFound)
+}
+
+// Quotes the gas cost, minter fee and amount received of a withdrawal before it is signed
+#[query]
+pub fn quote_withdrawal(
+    chain_id: CandidChainId,
+    token: String,
+    amount: Nat,
+) -> Option<WithdrawalQuote> {
+    quote_withdrawal_v2(chain_id, token, amount).ok()
+}
+
+#[query]
+pub fn quote_withdrawal_v2(
+    chain_id: CandidChainId,
+    token: String,
+    amount: Nat,
+) -> Result<WithdrawalQuote, LoggerQueryError> {
+    let chain_id = ChainId::from(&chain_id);
+    check_chain_is_supported(chain_id)?;
+    let token = Address::from_str(&token).map_err(|_e| LoggerQueryError::InvalidAddress)?;
+
+    read_state(|s| {
+        s.quote_withdrawal(
+            chain_id,
+            token,
+            nat_to_erc20_amount(amount),
+            ic_cdk::api::time(),
+        )
+    })
+    .ok_or(LoggerQueryError::NotFound)
+}
+
+// Searches transactions, tokens and bridge pairs of every chain for a hash, an EVM address,
+// a principal, an ICRC-1 account or a burn or mint index
+#[query]
+pub fn search(query: String) -> Result<SearchResults, LoggerQueryError> {
+    let query = SearchQuery::parse(&query).ok_or(LoggerQueryError::UnrecognizedQuery)?;
+    Ok(read_state(|s| s.search(&query)))
+}
+
+fn check_chain_is_supported(chain_id: ChainId) -> Result<(), LoggerQueryError> {
+    if read_state(|s| s.if_chain_id_exists(chain_id)) {
+        Ok(())
+    } else {
+        Err(LoggerQueryError::UnsupportedChain)
+    }
+}
+
+// Gets the fields of user submitted deposits that did not match the minter event
+#[query]
+pub fn get_deposit_discrepancies() -> Vec<CandidDepositDiscrepancy> {
+    read_state(|s| s.get_deposit_discrepancies())
+}
+
+// Gets the open alerts of transactions that stopped progressing
+#[query]
+pub fn get_alerts() -> Vec<CandidAlert> {
+    read_state(|s| s.get_alerts(ic_cdk::api::time()))
+}
+
+// Gets the fees charged by every minter over time
+#[query]
+pub fn get_minter_fee_schedules() -> Vec<MinterFeeSchedule> {
+    read_state(|s| s.get_fee_schedules())
+}
+
+// Gets the minter fees and gas paid per minter, token and day
+#[query]
+pub fn get_fee_revenue(args: GetFeeRevenueArgs) -> Vec<FeeRevenueReport> {
+    read_state(|s| s.get_fee_revenue(args))
+}
+
+// Gets the logger's own hash-chained event log, along with the certified tip hash
+#[query]
+pub fn get_logger_events(args: GetLoggerEventsArgs) -> GetLoggerEventsResult {
+    logger_events::get_logger_events(args)
+}
+
+// Reports the scraping progress and health of every minter
+#[query]
+pub fn get_minters_status() -> Vec<MinterStatus> {
+    read_state(|s| s.get_minters_status(ic_cdk::api::time()))
+}
+
+// Reports the guards currently held by timer tasks.
+// A stale guard means its task trapped before releasing it.
+#[query]
+pub fn get_active_tasks() -> Vec<ActiveTask> {
+    let now = ic_cdk::api::time();
+    active_tasks()
+        .into_iter()
+        .map(|(task, lease)| ActiveTask {
+            task,
+            acquired_at: lease.acquired_at,
+            renewed_at: lease.renewed_at,
+            held_for_ns: lease.held_for(now),
+            max_hold_time_ns: task.max_hold_time_ns(),
+            stale: lease.is_stale(task, now),
+        })
+        .collect()
+}
+
+#[query(hidden = true)]
+fn http_request(req: HttpRequest) -> StreamingHttpResponse {
+    if ic_cdk::api::data_certificate().is_none() {
+        ic_cdk::trap("update call rejected");
+    }
+
+    if let Some(export) = parse_export_path(req.path()) {
+        return match export {
+            Ok((collection, format)) => match bearer_token(&req.headers)
+                .and_then(|access_token| export_id_of(access_token, ic_cdk::api::time()))
+            {
+                Some(export_id) => serve_export(collection, format, export_id),
+                None => StreamingHttpResponse::error(403, "missing or expired access token"),
+            },
+            Err(err) => HttpResponseBuilder::bad_request()
+                .with_body_and_content_length(err)
+                .build()
+                .into(),
+        };
+    }
+
+    match parse_statement_path(req.path()) {
+        Some(Ok((principal, format))) => serve_statement(principal, format),
+        Some(Err(err)) => HttpResponseBuilder::bad_request()
+            .with_body_and_content_length(err)
+            .build()
+            .into(),
+        None => serve_http_request(req).into(),
+    }
+}
+
+// Exports a page of one of the stored maps, restricted to controllers
+#[query]
+pub fn export_state(args: ExportArgs) -> Result<ExportPage, LoggerQueryError> {
+    if !ic_cdk::api::is_controller(&ic_cdk::caller()) {
+        return Err(LoggerQueryError::NotAuthorized);
+    }
+    read_state(|s| s.export(&args))
+}
+
+// Issues an access token for the HTTP export under `/export/<collection>.<cbor|ndjson>`,
+// passed in the `Authorization: Bearer <token>` header and valid for an hour
+#[update]
+pub async fn create_export_access_token() -> Result<String, LoggerQueryError> {
+    if !ic_cdk::api::is_controller(&ic_cdk::caller()) {
+        return Err(LoggerQueryError::NotAuthorized);
+    }
+    let (random_bytes,) = ic_cdk::api::management_canister::main::raw_rand()
+        .await
+        .unwrap_or_else(|(code, msg)| ic_cdk::trap(&format!("raw_rand failed: {code:?} {msg}")));
+    Ok(issue_access_token(&random_bytes, ic_cdk::api::time()))
+}
+
+// Pulled by the boundary node for every page after the first of a streamed response
+#[query(hidden = true)]
+fn http_request_streaming_callback(token: StreamingToken) -> StreamingCallbackHttpResponse {
+    match token {
+        StreamingToken::Statement(token) => {
+            let (body, next) = render_statement_page(token);
+            StreamingCallbackHttpResponse {
+                body: ByteBuf::from(body),
+                token: next.map(StreamingToken::Statement),
+            }
+        }
+        // An access token expiring mid export, or a cursor that does not decode, ends the
+        // stream without its last snapshot
+        StreamingToken::Export(token) => {
+            let page = match is_valid_export_id(&token.export_id, ic_cdk::api::time()) {
+                true => render_export_page(token, false).ok(),
+                false => None,
+            };
+            match page {
+                Some((body, next)) => StreamingCallbackHttpResponse {
+                    body: ByteBuf::from(body),
+                    token: next.map(StreamingToken::Export),
+                },
+                None => StreamingCallbackHttpResponse {
+                    body: ByteBuf::new(),
+                    token: None,
+                },
+            }
+        }
+    }
+}
+
+fn serve_export(
+    collection: ExportCollection,
+    format: ExportFormat,
+    export_id: String,
+) -> StreamingHttpResponse {
+    let first_page = ExportToken {
+        collection,
+        format,
+        cursor: None,
+        export_id,
+    };
+    let (body, next) = match render_export_page(first_page, true) {
+        Ok(page) => page,
+        Err(err) => {
+            return HttpResponseBuilder::bad_request()
+                .with_body_and_content_length(format!("invalid export cursor: {err:?}"))
+                .build()
+                .into()
+        }
+    };
+    let headers = vec![(
+        "Content-Type".to_string(),
+        format.content_type().to_string(),
+    )];
+
+    StreamingHttpResponse::streamed(
+        ic_cdk::id(),
+        headers,
+        body,
+        next.map(StreamingToken::Export),
+    )
+}
+
+// Renders the page of a statement following the cursor of `token`. A cursor that does not
+// parse ends the stream.
+fn render_statement_page(token: StatementToken) -> (Vec<u8>, Option<StatementToken>) {
+    let cursor = match token
+        .cursor
+        .as_deref()
+        .map(|cursor| StatementKey::from_cursor(token.principal, cursor))
+        .transpose()
+    {
+        Ok(cursor) => cursor,
+        Err(_) => return (vec![], None),
+    };
+    let (rows, has_more) =
+        read_state(|s| s.get_statement_page(token.principal, cursor.as_ref(), STATEMENT_PAGE_SIZE));
+    token.render(&rows, has_more)
+}
+
+fn serve_statement(principal: Principal, format: StatementFormat) -> StreamingHttpResponse {
+    let first_page = StatementToken {
+        principal,
+        format,
+        cursor: None,
+    };
+    let (body, next) = render_statement_page(first_page);
+    let headers = vec![
+        (
+            "Content-Type".to_string(),
+            format.content_type().to_string(),
+        ),
+        (
+            "Content-Disposition".to_string(),
+            format!(
+                "attachment; filename=\"statement-{}.{}\"",
+                principal,
+                format.extension()
+            ),
+        ),
+    ];
+
+    StreamingHttpResponse::streamed(
+        ic_cdk::id(),
+        headers,
+        body,
+        next.map(StreamingToken::Statement),
+    )
+}
+
+fn serve_http_request(req: HttpRequest) -> HttpResponse {
+    if req.path() == "/metrics" {
+        let mut writer = ic_metrics_encoder::MetricsEncoder::new(
+            vec![],
+            (ic_cdk::api::time() / 1_000_000) as i64,
+        );
+
+        match encode_metrics(&mut writer) {
+            Ok(()) => HttpResponseBuilder::ok()
+                .header("Content-Type", "text/plain; version=0.0.4")
+                .with_body_and_content_length(writer.into_inner())
+                .build(),
+            Err(err) => {
+                HttpResponseBuilder::server_error(format!("Failed to encode metrics: {}", err))
+                    .build()
+            }
+        }
+    } else if req.path() == "/logs" {
+        match serve_logs(&req) {
+            Ok(response) | Err(response) => response,
+        }
+    } else {
+        HttpResponseBuilder::not_found().build()
+    }
+}
+
+// Serves `/logs?priority=&sort=&since=&contains=&file=`, unknown or malformed parameters
+// are rejected with a bad request
+fn serve_logs(req: &HttpRequest) -> Result<HttpResponse, HttpResponse> {
+    let bad_request = |message: String| {
+        HttpResponseBuilder::bad_request()
+            .with_body_and_content_length(message)
+            .build()
+    };
+    let query_param = |name: &str| {
+        req.raw_query_param(name)
+            .map(decode_query_value)
+            .transpose()
+            .map_err(|err| bad_request(format!("failed to decode the '{name}' parameter: {err}")))
+    };
+
+    let since = match query_param("since")? {
+        Some(arg) => u64::from_str(&arg)
+            .map_err(|_| bad_request("failed to parse the 'since' parameter".to_string()))?,
+        None => 0,
+    };
+
+    let mut log: Log = Default::default();
+
+    match query_param("priority")? {
+        Some(priority) => log.push_logs(Priority::from_str(&priority).map_err(|err| {
+            bad_request(format!("failed to parse the 'priority' parameter: {err}"))
+        })?),
+        None => log.push_all(),
+    }
+
+    log.retain_since(since);
+
+    if let Some(pattern) = query_param("contains")? {
+        log.retain_containing(&pattern);
+    }
+
+    if let Some(file) = query_param("file")? {
+        log.retain_file(&file);
+    }
+
+    // Newest entries first, unless the caller is tailing from a timestamp
+    let sort_order = match query_param("sort")? {
+        Some(sort) => Sort::from_str(&sort)
+            .map_err(|err| bad_request(format!("failed to parse the 'sort' parameter: {err}")))?,
+        None if since == 0 => Sort::Descending,
+        None => Sort::Ascending,
+    };
+    log.sort_logs(sort_order);
+
+    const MAX_BODY_SIZE: usize = 2_000_000;
+    Ok(HttpResponseBuilder::ok()
+        .header("Content-Type", "application/json; charset=utf-8")
+        .with_body_and_content_length(log.serialize_logs(MAX_BODY_SIZE))
+        .build())
+}
+
 // list every base URL that users will authenticate to your app from
 #[update]
 fn icrc28_trusted_origins() -> Icrc28TrustedOriginsResponse {
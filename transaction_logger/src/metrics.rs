@@ -0,0 +1,231 @@
+// Prometheus metrics served under /metrics
+
+use std::collections::BTreeMap;
+
+use ic_metrics_encoder::MetricsEncoder;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bridge_stats::BridgeDirection,
+    guard::last_successful_runs,
+    state::{read_state, stable_memory_sizes, ChainId, EvmToIcpTx, IcpToEvmTx, Minter},
+};
+
+// Transactions are counted by direction, chain, status and whether they were verified as they
+// are written, so scrapes do not walk the stored transactions
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub struct TxCountKey {
+    pub direction: BridgeDirection,
+    pub chain_id: ChainId,
+    pub status: String,
+    pub verified: bool,
+}
+
+impl TxCountKey {
+    pub fn for_evm_to_icp(tx: &EvmToIcpTx) -> Self {
+        let status: &str = (&tx.status).into();
+        Self {
+            direction: BridgeDirection::EvmToIcp,
+            chain_id: tx.chain_id,
+            status: status.to_string(),
+            verified: tx.verified,
+        }
+    }
+
+    pub fn for_icp_to_evm(tx: &IcpToEvmTx) -> Self {
+        let status: &str = (&tx.status).into();
+        Self {
+            direction: BridgeDirection::IcpToEvm,
+            chain_id: tx.chain_id,
+            status: status.to_string(),
+            verified: tx.verified,
+        }
+    }
+}
+
+fn direction_label(direction: BridgeDirection) -> &'static str {
+    match direction {
+        BridgeDirection::EvmToIcp => "evm_to_icp",
+        BridgeDirection::IcpToEvm => "icp_to_evm",
+    }
+}
+
+pub fn encode_metrics(w: &mut MetricsEncoder<Vec<u8>>) -> std::io::Result<()> {
+    w.encode_gauge(
+        "cycle_balance",
+        ic_cdk::api::canister_balance128() as f64,
+        "Cycle balance of the logger canister.",
+    )?;
+
+    w.encode_gauge(
+        "stable_memory_pages",
+        ic_cdk::api::stable::stable_size() as f64,
+        "Size of the stable memory allocated by this canister measured in 64KiB pages.",
+    )?;
+
+    let mut memory_sizes = w.gauge_vec(
+        "virtual_memory_pages",
+        "Size of each virtual stable memory measured in 64KiB pages.",
+    )?;
+    for (memory_id, name, pages) in stable_memory_sizes() {
+        let memory_id = memory_id.to_string();
+        memory_sizes = memory_sizes.value(
+            &[("memory_id", memory_id.as_str()), ("name", name)],
+            pages as f64,
+        )?;
+    }
+
+    read_state(|s| {
+        // (chain_id, operator) labels of each minter
+        let minters: Vec<(String, String, Minter)> = s
+            .minters
+            .iter()
+            .map(|(key, minter)| {
+                (
+                    key.chain_id().0.to_string(),
+                    format!("{:?}", key.operator()),
+                    minter,
+                )
+            })
+            .collect();
+
+        let mut last_observed = w.gauge_vec(
+            "minter_last_observed_event",
+            "Index of the last event observed on each minter.",
+        )?;
+        for (chain_id, operator, minter) in minters.iter() {
+            last_observed = last_observed.value(
+                &[
+                    ("chain_id", chain_id.as_str()),
+                    ("operator", operator.as_str()),
+                ],
+                minter.last_observed_event as f64,
+            )?;
+        }
+
+        let mut last_scraped = w.gauge_vec(
+            "minter_last_scraped_event",
+            "Index of the last event scraped from each minter.",
+        )?;
+        for (chain_id, operator, minter) in minters.iter() {
+            last_scraped = last_scraped.value(
+                &[
+                    ("chain_id", chain_id.as_str()),
+                    ("operator", operator.as_str()),
+                ],
+                minter.last_scraped_event as f64,
+            )?;
+        }
+
+        let mut lag = w.gauge_vec(
+            "minter_event_lag",
+            "Number of observed events that are not scraped yet.",
+        )?;
+        for (chain_id, operator, minter) in minters.iter() {
+            lag = lag.value(
+                &[
+                    ("chain_id", chain_id.as_str()),
+                    ("operator", operator.as_str()),
+                ],
+                minter
+                    .last_observed_event
+                    .saturating_sub(minter.last_scraped_event) as f64,
+            )?;
+        }
+
+        // (direction, chain_id, status) -> count
+        let mut tx_counts: BTreeMap<(&str, u64, String), u64> = BTreeMap::new();
+        // direction -> count
+        let mut unverified_counts: BTreeMap<&str, u64> =
+            BTreeMap::from([("evm_to_icp", 0), ("icp_to_evm", 0)]);
+
+        for (key, count) in s.tx_counts.iter() {
+            let direction = direction_label(key.direction);
+            if !key.verified {
+                *unverified_counts.entry(direction).or_default() += count;
+            }
+            *tx_counts
+                .entry((direction, key.chain_id.0, key.status))
+                .or_default() += count;
+        }
+
+        let mut transactions = w.gauge_vec(
+            "transactions",
+            "Number of recorded transactions by direction, chain and status.",
+        )?;
+        for ((direction, chain_id, status), count) in tx_counts {
+            let chain_id = chain_id.to_string();
+            transactions = transactions.value(
+                &[
+                    ("direction", direction),
+                    ("chain_id", chain_id.as_str()),
+                    ("status", status.as_str()),
+                ],
+                count as f64,
+            )?;
+        }
+
+        let mut unverified = w.gauge_vec(
+            "unverified_transactions",
+            "Number of user submitted transactions not yet verified by a minter event.",
+        )?;
+        for (direction, count) in unverified_counts {
+            unverified = unverified.value(&[("direction", direction)], count as f64)?;
+        }
+
+        // reason -> count
+        let mut alert_counts: BTreeMap<&str, u64> = BTreeMap::new();
+        for (_key, alert) in s.stuck_alerts.iter() {
+            *alert_counts.entry(alert.reason.into()).or_default() += 1;
+        }
+
+        let mut stuck = w.gauge_vec(
+            "stuck_transactions",
+            "Number of open stuck transaction alerts by reason.",
+        )?;
+        for (reason, count) in alert_counts {
+            stuck = stuck.value(&[("reason", reason)], count as f64)?;
+        }
+
+        w.encode_gauge(
+            "icp_tokens",
+            s.icp_token_list.len() as f64,
+            "Number of tokens in the ICP token list.",
+        )?;
+
+        w.encode_gauge(
+            "evm_tokens",
+            s.evm_token_list.len() as f64,
+            "Number of tokens in the EVM token list.",
+        )?;
+
+        w.gauge_vec(
+            "bridge_pairs",
+            "Number of supported bridge pairs by operator.",
+        )?
+        .value(
+            &[("operator", "DfinityCkEthMinter")],
+            s.supported_ckerc20_tokens.len() as f64,
+        )?
+        .value(
+            &[("operator", "AppicMinter")],
+            s.supported_twin_appic_tokens.len() as f64,
+        )?;
+
+        Ok::<(), std::io::Error>(())
+    })?;
+
+    let mut last_success = w.gauge_vec(
+        "task_last_success_timestamp_seconds",
+        "Timestamp of the last successful run of each timer task.",
+    )?;
+    for (task, timestamp) in last_successful_runs() {
+        let task = format!("{:?}", task);
+        last_success = last_success.value(
+            &[("task", task.as_str())],
+            (timestamp / 1_000_000_000) as f64,
+        )?;
+    }
+
+    Ok(())
+}
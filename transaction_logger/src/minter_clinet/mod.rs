@@ -91,7 +91,7 @@ impl MinterClient {
     }
 
     // Get total evetns count
-    pub async fn get_total_events_count(&self) -> u64 {
+    pub async fn get_total_events_count(&self) -> Result<u64, CallError> {
         // Get total events count
         let toatl_events_count = match self.operator {
             Operator::DfinityCkEthMinter => {
@@ -104,8 +104,7 @@ impl MinterClient {
                             length: 0,
                         },
                     )
-                    .await
-                    .expect("Call should not fail. will retry in next interval")
+                    .await?
                     .total_event_count
             }
             Operator::AppicMinter => {
@@ -118,13 +117,12 @@ impl MinterClient {
                             length: 0,
                         },
                     )
-                    .await
-                    .expect("Call should not fail. will retry in next interval")
+                    .await?
                     .total_event_count
             }
         };
 
-        toatl_events_count
+        Ok(toatl_events_count)
     }
 
     // scrape events
@@ -0,0 +1,372 @@
+// Feed of the latest transfers across all users and chains, newest first.
+// Transactions are indexed by the time they first appeared, so a page never has to scan the
+// transaction maps and a cursor stays valid while newer transfers keep coming in.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use candid::{CandidType, Nat, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::bridge_stats::BridgeDirection;
+use crate::endpoints::{
+    CandidChainId, CandidEvmToIcp, CandidIcpToEvm, CandidLedgerBurn, CandidReimbursement,
+    Transaction,
+};
+use crate::state::{ChainId, EvmToIcpTx, IcpToEvmTx, Operator, TransactionId};
+
+pub const MAX_RECENT_TRANSACTIONS: usize = 100;
+
+// Index entries a single page may walk, so a filter on a quiet chain or operator does not
+// walk the whole feed
+pub const MAX_SCANNED_RECENT_ACTIVITY: usize = 1_000;
+
+// Replaces principals, subaccounts, addresses, ids and hashes of redacted transactions
+pub const REDACTED: &str = "redacted";
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct RecentActivityKey {
+    pub timestamp: u64,
+    pub id: TransactionId,
+}
+
+impl RecentActivityKey {
+    // User submitted transfers appear when they are submitted, scraped ones when they are accepted
+    pub fn for_evm_to_icp(tx: &EvmToIcpTx) -> Self {
+        Self {
+            timestamp: tx.submitted_at.or(tx.accepted_at).unwrap_or_default(),
+            id: TransactionId::from(tx),
+        }
+    }
+
+    pub fn for_icp_to_evm(tx: &IcpToEvmTx) -> Self {
+        Self {
+            timestamp: tx.submitted_at.or(tx.accepted_at).unwrap_or_default(),
+            id: TransactionId::from(tx),
+        }
+    }
+}
+
+// Newest first, so iterating the index from the start walks back in time
+impl Ord for RecentActivityKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .timestamp
+            .cmp(&self.timestamp)
+            .then_with(|| self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for RecentActivityKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// The textual form is the cursor handed to clients
+impl fmt::Display for RecentActivityKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.timestamp, self.id)
+    }
+}
+
+impl FromStr for RecentActivityKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (timestamp, id) = s
+            .split_once(':')
+            .ok_or_else(|| format!("cursor should start with a timestamp, got {s}"))?;
+        let timestamp = timestamp
+            .parse::<u64>()
+            .map_err(|e| format!("invalid timestamp in cursor: {e}"))?;
+        let id = TransactionId::from_str(id)?;
+        Ok(Self { timestamp, id })
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecentTransactionsFilters {
+    pub chain_id: Option<CandidChainId>,
+    pub operator: Option<Operator>,
+    pub direction: Option<BridgeDirection>,
+    // Hides who sent and received every returned transfer and anything identifying it
+    pub redact: bool,
+}
+
+impl RecentTransactionsFilters {
+    // Filters that only need the index key
+    pub fn matches_key(&self, key: &RecentActivityKey) -> bool {
+        let direction = match key.id {
+            TransactionId::EvmToIcp(_) => BridgeDirection::EvmToIcp,
+            TransactionId::IcpToEvm(_) => BridgeDirection::IcpToEvm,
+        };
+        self.chain_id.as_ref().map_or(true, |chain_id| {
+            ChainId::from(chain_id) == key.id.chain_id()
+        }) && self.direction.map_or(true, |filter| filter == direction)
+    }
+
+    pub fn matches_operator(&self, operator: Operator) -> bool {
+        self.operator.map_or(true, |filter| filter == operator)
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecentTransactions {
+    pub transactions: Vec<Transaction>,
+    // Passed back to get the next page, none once the feed is exhausted. A page that walked
+    // `MAX_SCANNED_RECENT_ACTIVITY` entries may hold fewer transfers than asked, or none.
+    pub next_cursor: Option<String>,
+}
+
+// Removes the principals, subaccounts and addresses of both sides of a transfer, along with
+// every id, hash and ledger index that would let it be looked up on chain or on a ledger.
+// Amounts, tokens, statuses and times are kept so the feed still shows what moved.
+pub fn redact(transaction: Transaction) -> Transaction {
+    match transaction {
+        Transaction::EvmToIcp(tx) => Transaction::EvmToIcp(CandidEvmToIcp {
+            id: REDACTED.to_string(),
+            from_address: REDACTED.to_string(),
+            transaction_hash: REDACTED.to_string(),
+            block_number: None,
+            ledger_mint_index: None,
+            principal: Principal::anonymous(),
+            subaccount: None,
+            account: REDACTED.to_string(),
+            ..tx
+        }),
+        Transaction::IcpToEvm(tx) => Transaction::IcpToEvm(CandidIcpToEvm {
+            id: REDACTED.to_string(),
+            transaction_hash: None,
+            native_ledger_burn_index: Nat::from(0_u8),
+            destination: REDACTED.to_string(),
+            from: Principal::anonymous(),
+            from_subaccount: None,
+            from_account: REDACTED.to_string(),
+            erc20_ledger_burn_index: None,
+            erc20_burn: tx.erc20_burn.map(redact_burn),
+            native_fee_burn: tx.native_fee_burn.map(redact_burn),
+            native_reimbursement: tx.native_reimbursement.map(redact_reimbursement),
+            erc20_reimbursement: tx.erc20_reimbursement.map(redact_reimbursement),
+            ..tx
+        }),
+    }
+}
+
+fn redact_burn(burn: CandidLedgerBurn) -> CandidLedgerBurn {
+    CandidLedgerBurn {
+        burn_index: Nat::from(0_u8),
+        ..burn
+    }
+}
+
+fn redact_reimbursement(reimbursement: CandidReimbursement) -> CandidReimbursement {
+    CandidReimbursement {
+        reimbursed_in_block: None,
+        ..reimbursement
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::numeric::LedgerBurnIndex;
+    use crate::state::{
+        EvmToIcpStatus, EvmToIcpTxIdentifier, IcpToEvmIdentifier, IcpToEvmStatus, TransactionHash,
+    };
+
+    #[test]
+    fn should_order_newest_first_and_round_trip_cursors() {
+        let older = RecentActivityKey {
+            timestamp: 1,
+            id: TransactionId::EvmToIcp(EvmToIcpTxIdentifier::new(
+                &TransactionHash::new([1; 32]),
+                ChainId(56),
+            )),
+        };
+        let newer = RecentActivityKey {
+            timestamp: 2,
+            id: TransactionId::IcpToEvm(IcpToEvmIdentifier::new(
+                LedgerBurnIndex::new(3),
+                ChainId(1),
+            )),
+        };
+        assert!(newer < older);
+
+        for key in [older, newer] {
+            assert_eq!(RecentActivityKey::from_str(&key.to_string()), Ok(key));
+        }
+        assert!(RecentActivityKey::from_str("icp_to_evm:1:3").is_err());
+    }
+
+    #[test]
+    fn should_leave_nothing_traceable_in_redacted_transactions() {
+        let owner = Principal::from_slice(&[7]);
+        let hash = TransactionHash::new([5; 32]).to_string();
+        let address = "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string();
+        let deposit = CandidEvmToIcp {
+            id: format!("evm_to_icp:56:{hash}"),
+            from_address: address.clone(),
+            transaction_hash: hash.clone(),
+            value: Nat::from(4_000_u64),
+            block_number: Some(Nat::from(11_u64)),
+            ledger_mint_index: Some(Nat::from(12_u64)),
+            actual_received: Some(Nat::from(3_900_u64)),
+            principal: owner,
+            subaccount: Some([1; 32]),
+            account: owner.to_text(),
+            chain_id: Nat::from(56_u64),
+            total_gas_spent: None,
+            erc20_contract_address: address.clone(),
+            icrc_ledger_id: None,
+            status: EvmToIcpStatus::Minted,
+            verified: true,
+            submitted_at: None,
+            accepted_at: Some(1),
+            minted_at: Some(2),
+            operator: Operator::AppicMinter,
+            accepted_usd_price: None,
+            accepted_usd_value: None,
+            estimated_completion_at: None,
+        };
+
+        // Every field is named so a new one has to be classified here
+        let CandidEvmToIcp {
+            id,
+            from_address,
+            transaction_hash,
+            value: _,
+            block_number,
+            ledger_mint_index,
+            actual_received: _,
+            principal,
+            subaccount,
+            account,
+            chain_id: _,
+            total_gas_spent: _,
+            erc20_contract_address: _,
+            icrc_ledger_id: _,
+            status: _,
+            verified: _,
+            submitted_at: _,
+            accepted_at: _,
+            minted_at: _,
+            operator: _,
+            accepted_usd_price: _,
+            accepted_usd_value: _,
+            estimated_completion_at: _,
+        } = match redact(Transaction::EvmToIcp(deposit)) {
+            Transaction::EvmToIcp(tx) => tx,
+            Transaction::IcpToEvm(_) => panic!("expected a deposit"),
+        };
+        assert_eq!(id, REDACTED);
+        assert_eq!(from_address, REDACTED);
+        assert_eq!(transaction_hash, REDACTED);
+        assert_eq!(block_number, None);
+        assert_eq!(ledger_mint_index, None);
+        assert_eq!(principal, Principal::anonymous());
+        assert_eq!(subaccount, None);
+        assert_eq!(account, REDACTED);
+
+        let reimbursement = CandidReimbursement {
+            ledger_id: None,
+            amount: Nat::from(10_u64),
+            reimbursed_in_block: Some(Nat::from(13_u64)),
+        };
+        let withdrawal = CandidIcpToEvm {
+            id: "icp_to_evm:56:3".to_string(),
+            transaction_hash: Some(hash),
+            native_ledger_burn_index: Nat::from(3_u64),
+            withdrawal_amount: Nat::from(4_000_u64),
+            actual_received: None,
+            destination: address.clone(),
+            from: owner,
+            from_subaccount: Some([1; 32]),
+            from_account: owner.to_text(),
+            submitted_at: None,
+            accepted_at: Some(1),
+            finalized_at: None,
+            reimbursed_at: Some(2),
+            max_transaction_fee: None,
+            effective_gas_price: None,
+            gas_used: None,
+            total_gas_spent: None,
+            erc20_ledger_burn_index: Some(Nat::from(4_u64)),
+            erc20_contract_address: address,
+            icrc_ledger_id: None,
+            verified: true,
+            status: IcpToEvmStatus::Reimbursed,
+            operator: Operator::AppicMinter,
+            chain_id: Nat::from(56_u64),
+            accepted_usd_price: None,
+            accepted_usd_value: None,
+            erc20_burn: Some(CandidLedgerBurn {
+                burn_index: Nat::from(4_u64),
+                amount: Nat::from(4_000_u64),
+            }),
+            native_fee_burn: Some(CandidLedgerBurn {
+                burn_index: Nat::from(3_u64),
+                amount: Nat::from(100_u64),
+            }),
+            native_reimbursement: Some(reimbursement.clone()),
+            erc20_reimbursement: Some(reimbursement),
+            native_fee_paid: None,
+            estimated_completion_at: None,
+        };
+
+        let CandidIcpToEvm {
+            id,
+            transaction_hash,
+            native_ledger_burn_index,
+            withdrawal_amount: _,
+            actual_received: _,
+            destination,
+            from,
+            from_subaccount,
+            from_account,
+            submitted_at: _,
+            accepted_at: _,
+            finalized_at: _,
+            reimbursed_at: _,
+            max_transaction_fee: _,
+            effective_gas_price: _,
+            gas_used: _,
+            total_gas_spent: _,
+            erc20_ledger_burn_index,
+            erc20_contract_address: _,
+            icrc_ledger_id: _,
+            verified: _,
+            status: _,
+            operator: _,
+            chain_id: _,
+            accepted_usd_price: _,
+            accepted_usd_value: _,
+            erc20_burn,
+            native_fee_burn,
+            native_reimbursement,
+            erc20_reimbursement,
+            native_fee_paid: _,
+            estimated_completion_at: _,
+        } = match redact(Transaction::IcpToEvm(withdrawal)) {
+            Transaction::IcpToEvm(tx) => tx,
+            Transaction::EvmToIcp(_) => panic!("expected a withdrawal"),
+        };
+        assert_eq!(id, REDACTED);
+        assert_eq!(transaction_hash, None);
+        assert_eq!(native_ledger_burn_index, Nat::from(0_u8));
+        assert_eq!(destination, REDACTED);
+        assert_eq!(from, Principal::anonymous());
+        assert_eq!(from_subaccount, None);
+        assert_eq!(from_account, REDACTED);
+        assert_eq!(erc20_ledger_burn_index, None);
+        // Burned and reimbursed amounts are kept without the ledger blocks they are in
+        for burn in [erc20_burn.unwrap(), native_fee_burn.unwrap()] {
+            assert_eq!(burn.burn_index, Nat::from(0_u8));
+        }
+        for reimbursement in [native_reimbursement.unwrap(), erc20_reimbursement.unwrap()] {
+            assert_eq!(reimbursement.reimbursed_in_block, None);
+            assert_eq!(reimbursement.amount, Nat::from(10_u64));
+        }
+    }
+}
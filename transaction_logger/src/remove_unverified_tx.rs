@@ -1,7 +1,7 @@
 use ic_canister_log::log;
 
 use crate::{
-    guard::TimerGuard,
+    guard::{record_task_success, TaskType, TimerGuard},
     logs::INFO,
     state::{mutate_state, read_state},
 };
@@ -13,7 +13,7 @@ const ONE_HOUR_IN_NS: u64 = 3_600_000_000_000;
 
 pub fn remove_unverified_tx() {
     // Issue a timer gaurd
-    let _gaurd = match TimerGuard::new(crate::guard::TaskType::RemoveUnverified) {
+    let _gaurd = match TimerGuard::new(TaskType::RemoveUnverified) {
         Ok(gaurd) => gaurd,
         Err(_) => return,
     };
@@ -46,4 +46,6 @@ pub fn remove_unverified_tx() {
             mutate_state(|s| s.remove_unverified_icp_to_evm(&identifier))
         }
     }
+
+    record_task_success(TaskType::RemoveUnverified);
 }
@@ -0,0 +1,73 @@
+// Window of the most recent samples of a measurement, with nearest-rank percentiles over it.
+// Shared by the completion latencies and the gas costs of withdrawals.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+// Stored as the bare queue, the layout of the latency and gas cost samples it replaced
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct RollingSamples<T, const CAPACITY: usize> {
+    // Oldest first
+    samples: VecDeque<T>,
+}
+
+impl<T, const CAPACITY: usize> Default for RollingSamples<T, CAPACITY> {
+    fn default() -> Self {
+        Self {
+            samples: VecDeque::new(),
+        }
+    }
+}
+
+impl<T: Copy + Ord, const CAPACITY: usize> RollingSamples<T, CAPACITY> {
+    /// Adds a sample, dropping the oldest one once `CAPACITY` samples are kept.
+    pub fn push(mut self, sample: T) -> Self {
+        if self.samples.len() >= CAPACITY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Returns the nearest-rank value of each of `percentiles`, none if there are no samples.
+    pub fn nearest_ranks<const N: usize>(&self, percentiles: [usize; N]) -> Option<[T; N]> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<T> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        Some(percentiles.map(|percentile| {
+            let rank = (percentile * sorted.len()).div_ceil(100);
+            sorted[rank.saturating_sub(1)]
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_the_most_recent_samples() {
+        let samples = (0..110).fold(RollingSamples::<u64, 100>::default(), |samples, sample| {
+            samples.push(sample)
+        });
+
+        assert_eq!(samples.len(), 100);
+        // The ten oldest samples were dropped
+        assert_eq!(samples.nearest_ranks([0, 50, 100]), Some([10, 59, 109]));
+        assert_eq!(
+            RollingSamples::<u64, 100>::default().nearest_ranks([50]),
+            None
+        );
+    }
+}
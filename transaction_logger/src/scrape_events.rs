@@ -1,13 +1,13 @@
 use crate::{
-    guard::TimerGuard,
+    guard::{record_task_success, TaskType, TimerGuard, TimerGuardError},
     logs::{DEBUG, INFO},
     minter_clinet::MinterClient,
-    numeric::Erc20TokenAmount,
     state::{
         mutate_state, nat_to_ledger_burn_index, nat_to_ledger_mint_index, read_state, ChainId,
-        EvmToIcpTxIdentifier, IcpToEvmIdentifier, MinterKey, Operator,
+        EvmToIcpTxIdentifier, IcpToEvmIdentifier, MinterKey, Operator, TransactionHash,
     },
 };
+use std::str::FromStr;
 
 use crate::minter_clinet::appic_minter_types::events::EventPayload as AppicEventPayload;
 use ic_canister_log::log;
@@ -19,24 +19,54 @@ pub const NATIVE_ERC20_ADDRESS: &str = "0x00000000000000000000000000000000000000
 
 pub async fn scrape_events() {
     // Issue a timer gaurd
-    let _gaurd = match TimerGuard::new(crate::guard::TaskType::ScrapeEvents) {
+    let gaurd = match TimerGuard::new(TaskType::ScrapeEvents) {
         Ok(gaurd) => gaurd,
         Err(_) => return,
     };
 
+    // Events of transactions that were not moved to the current layout yet would be lost
+    if read_state(|s| s.is_migrating_legacy_transactions()) {
+        log!(
+            INFO,
+            "[Scraping Events] waiting for the legacy transactions to be migrated"
+        );
+        return;
+    }
+
     let minters = read_state(|s| s.get_minters());
 
     for (minter_key, minter) in minters.iter() {
         let minter_client = MinterClient::from(minter);
 
+        // Recorded before the first call, so a scrape that traps shows up as stalled
+        mutate_state(|s| s.record_scrape_attempt(minter_key, ic_cdk::api::time()));
+
         // Get the latest event count to update last_observed_event;
         // -1 since the starting index in 0 not 1
-        let latest_event_count = minter_client.get_total_events_count().await - 1;
+        let total_events_count = minter_client.get_total_events_count().await;
+        if gaurd.renew().is_err() {
+            return;
+        }
+        let latest_event_count = match total_events_count {
+            Ok(count) => count.saturating_sub(1),
+            Err(err) => {
+                log!(
+                    DEBUG,
+                    "[Scraping Events] Failed to get the event count of minter {:?}: {:?}, will retry in next interval",
+                    minter_key,
+                    err
+                );
+                mutate_state(|s| s.record_scrape_failure(minter_key));
+                continue;
+            }
+        };
 
         // Check if the previos last_observed_event is greater or equal to latest one;
-        // If yes there should be no scraping for events and last_observed_event should not be updated
+        // If yes there should be no scraping for events and last_observed_event should not be updated.
+        // Minters after this one may still have new events, so move on to the next one.
         if minter.last_observed_event >= latest_event_count {
-            break;
+            mutate_state(|s| s.record_scrape_success(minter_key, ic_cdk::api::time()));
+            continue;
         };
 
         // Updating last observed event count
@@ -49,34 +79,39 @@ pub async fn scrape_events() {
         // min((last_observed_evnet - last_scraped_event),100) will be the specified range
         // If last_observed_evnet - last_scraped_event contains more than 100, the event scaping will be divided into multiple calls
 
-        scrape_events_range(
+        let scraped = scrape_events_range(
             latest_event_count,
             last_scraped_event,
             MAX_EVENTS_PER_RESPONSE,
             &minter_client,
             minter_key,
-            minter.evm_to_icp_fee,
-            minter.icp_to_evm_fee,
+            &gaurd,
         )
-        .await
+        .await;
+        if scraped.is_err() {
+            return;
+        }
     }
+
+    record_task_success(TaskType::ScrapeEvents);
 }
 
+// Fails only if the lease of `gaurd` was taken over, in which case scraping should stop
 pub async fn scrape_events_range(
     last_observed_event: u64,
     last_scraped_event: u64,
     max_event_scrap: u64,
     minter_client: &MinterClient,
     minter_key: &MinterKey,
-    evm_to_icp_fee: Erc20TokenAmount,
-    icp_to_evm_fee: Erc20TokenAmount,
-) {
+    gaurd: &TimerGuard,
+) -> Result<(), TimerGuardError> {
     if last_scraped_event >= last_observed_event {
         log!(
             INFO,
             "[Scraping Events] No events to scrape. All events are already processed."
         );
-        return;
+        mutate_state(|s| s.record_scrape_success(minter_key, ic_cdk::api::time()));
+        return Ok(());
     }
 
     let mut start = last_scraped_event + 1; // Start from the next event after the last scraped
@@ -98,16 +133,14 @@ pub async fn scrape_events_range(
 
         while attempts < MAX_RETRIES {
             let events_result = minter_client.scrape_events(start, 100).await;
+            gaurd.renew()?;
             match events_result {
                 Ok(events) => {
-                    apply_state_transition(
-                        events,
-                        minter_key.operator(),
-                        minter_key.chain_id(),
-                        evm_to_icp_fee,
-                        icp_to_evm_fee,
-                    );
-                    mutate_state(|s| s.update_last_scraped_event(&minter_key, chunk_end));
+                    apply_state_transition(events, minter_key.operator(), minter_key.chain_id());
+                    mutate_state(|s| {
+                        s.update_last_scraped_event(&minter_key, chunk_end);
+                        s.record_events_applied(&minter_key, ic_cdk::api::time());
+                    });
                     success = true; // Mark as successful
                     break; // Exit retry loop
                 }
@@ -145,19 +178,42 @@ pub async fn scrape_events_range(
                 DEBUG,
                 "[Scraping Events] Aborting further scraping due to repeated failures."
             );
-            break;
+            mutate_state(|s| s.record_scrape_failure(minter_key));
+            return Ok(());
         }
     }
+
+    mutate_state(|s| s.record_scrape_success(minter_key, ic_cdk::api::time()));
+    Ok(())
 }
 
-fn apply_state_transition(
-    events: Events,
-    operator: Operator,
+// Minters report hashes in their canonical form, so a malformed one is a bug in the minter.
+// The event is logged and skipped.
+fn evm_to_icp_identifier(
+    transaction_hash: &str,
     chain_id: ChainId,
-    evm_to_icp_fee: Erc20TokenAmount,
-    icp_to_evm_fee: Erc20TokenAmount,
-) {
+) -> Option<EvmToIcpTxIdentifier> {
+    match TransactionHash::from_str(transaction_hash) {
+        Ok(transaction_hash) => Some(EvmToIcpTxIdentifier::new(&transaction_hash, chain_id)),
+        Err(_) => {
+            log!(
+                INFO,
+                "[Scraping Events] skipping event of deposit with malformed hash {transaction_hash} on chain {chain_id:?}"
+            );
+            None
+        }
+    }
+}
+
+fn apply_state_transition(events: Events, operator: Operator, chain_id: ChainId) {
+    let minter_key = MinterKey(chain_id, operator);
     for event in events.events.into_iter() {
+        // Fees are charged as they were when the event happened, not as they are now.
+        // They are unknown for events before the first fee change the logger recorded.
+        let fees = read_state(|s| s.get_minter_fees_at(&minter_key, event.timestamp));
+        let evm_to_icp_fee = fees.map(|(evm_to_icp_fee, _)| evm_to_icp_fee);
+        let icp_to_evm_fee = fees.map(|(_, icp_to_evm_fee)| icp_to_evm_fee);
+
         // Applying the state transition
         mutate_state(|s| match event.payload {
             AppicEventPayload::Init(_init_arg) => {}
@@ -170,19 +226,23 @@ fn apply_state_transition(
                 principal,
                 subaccount,
                 ..
-            } => s.record_accepted_evm_to_icp(
-                EvmToIcpTxIdentifier::new(&transaction_hash, chain_id),
-                transaction_hash,
-                block_number,
-                from_address,
-                value,
-                principal,
-                NATIVE_ERC20_ADDRESS.to_string(),
-                subaccount,
-                chain_id,
-                operator,
-                event.timestamp,
-            ),
+            } => {
+                let Some(identifier) = evm_to_icp_identifier(&transaction_hash, chain_id) else {
+                    return;
+                };
+                s.record_accepted_evm_to_icp(
+                    identifier,
+                    block_number,
+                    from_address,
+                    value,
+                    principal,
+                    NATIVE_ERC20_ADDRESS.to_string(),
+                    subaccount,
+                    chain_id,
+                    operator,
+                    event.timestamp,
+                )
+            }
             AppicEventPayload::AcceptedErc20Deposit {
                 transaction_hash,
                 block_number,
@@ -192,34 +252,50 @@ fn apply_state_transition(
                 principal,
                 erc20_contract_address,
                 subaccount,
-            } => s.record_accepted_evm_to_icp(
-                EvmToIcpTxIdentifier::new(&transaction_hash, chain_id),
-                transaction_hash,
-                block_number,
-                from_address,
-                value,
-                principal,
-                erc20_contract_address,
-                subaccount,
-                chain_id,
-                operator,
-                event.timestamp,
-            ),
+            } => {
+                let Some(identifier) = evm_to_icp_identifier(&transaction_hash, chain_id) else {
+                    return;
+                };
+                s.record_accepted_evm_to_icp(
+                    identifier,
+                    block_number,
+                    from_address,
+                    value,
+                    principal,
+                    erc20_contract_address,
+                    subaccount,
+                    chain_id,
+                    operator,
+                    event.timestamp,
+                )
+            }
             AppicEventPayload::InvalidDeposit {
                 event_source,
                 reason,
-            } => s.record_invalid_evm_to_icp(
-                EvmToIcpTxIdentifier::new(&event_source.transaction_hash, chain_id),
-                reason,
-            ),
+            } => {
+                let Some(identifier) =
+                    evm_to_icp_identifier(&event_source.transaction_hash, chain_id)
+                else {
+                    return;
+                };
+                s.record_invalid_evm_to_icp(identifier, reason)
+            }
             AppicEventPayload::MintedNative {
                 event_source,
                 mint_block_index,
-            } => s.record_minted_evm_to_icp(
-                EvmToIcpTxIdentifier::new(&event_source.transaction_hash, chain_id),
-                evm_to_icp_fee,
-                nat_to_ledger_mint_index(&mint_block_index),
-            ),
+            } => {
+                let Some(identifier) =
+                    evm_to_icp_identifier(&event_source.transaction_hash, chain_id)
+                else {
+                    return;
+                };
+                s.record_minted_evm_to_icp(
+                    identifier,
+                    evm_to_icp_fee,
+                    nat_to_ledger_mint_index(&mint_block_index),
+                    event.timestamp,
+                )
+            }
             AppicEventPayload::SyncedToBlock { .. } => {}
             AppicEventPayload::AcceptedNativeWithdrawalRequest {
                 withdrawal_amount,
@@ -227,7 +303,7 @@ fn apply_state_transition(
                 ledger_burn_index,
                 from,
                 from_subaccount,
-                created_at,
+                ..
             } => s.record_accepted_icp_to_evm(
                 IcpToEvmIdentifier::new(nat_to_ledger_burn_index(&ledger_burn_index), chain_id),
                 None,
@@ -238,7 +314,6 @@ fn apply_state_transition(
                 None,
                 from,
                 from_subaccount,
-                created_at,
                 operator,
                 chain_id,
                 event.timestamp,
@@ -249,15 +324,15 @@ fn apply_state_transition(
                     chain_id,
                 )),
             AppicEventPayload::SignedTransaction { withdrawal_id, .. } => s
-                .record_signed_icp_to_evm(IcpToEvmIdentifier::new(
-                    nat_to_ledger_burn_index(&withdrawal_id),
-                    chain_id,
-                )),
+                .record_signed_icp_to_evm(
+                    IcpToEvmIdentifier::new(nat_to_ledger_burn_index(&withdrawal_id), chain_id),
+                    event.timestamp,
+                ),
             AppicEventPayload::ReplacedTransaction { withdrawal_id, .. } => s
-                .record_replaced_icp_to_evm(IcpToEvmIdentifier::new(
-                    nat_to_ledger_burn_index(&withdrawal_id),
-                    chain_id,
-                )),
+                .record_replaced_icp_to_evm(
+                    IcpToEvmIdentifier::new(nat_to_ledger_burn_index(&withdrawal_id), chain_id),
+                    event.timestamp,
+                ),
             AppicEventPayload::FinalizedTransaction {
                 withdrawal_id,
                 transaction_receipt,
@@ -265,17 +340,32 @@ fn apply_state_transition(
                 IcpToEvmIdentifier::new(nat_to_ledger_burn_index(&withdrawal_id), chain_id),
                 transaction_receipt,
                 icp_to_evm_fee,
+                event.timestamp,
+            ),
+            AppicEventPayload::ReimbursedNativeWithdrawal {
+                withdrawal_id,
+                reimbursed_amount,
+                reimbursed_in_block,
+                ..
+            } => s.record_native_reimbursed_icp_to_evm(
+                IcpToEvmIdentifier::new(nat_to_ledger_burn_index(&withdrawal_id), chain_id),
+                reimbursed_amount,
+                Some(nat_to_ledger_mint_index(&reimbursed_in_block)),
+                event.timestamp,
+            ),
+            AppicEventPayload::ReimbursedErc20Withdrawal {
+                withdrawal_id,
+                ledger_id,
+                reimbursed_amount,
+                reimbursed_in_block,
+                ..
+            } => s.record_erc20_reimbursed_icp_to_evm(
+                IcpToEvmIdentifier::new(nat_to_ledger_burn_index(&withdrawal_id), chain_id),
+                ledger_id,
+                reimbursed_amount,
+                nat_to_ledger_mint_index(&reimbursed_in_block),
+                event.timestamp,
             ),
-            AppicEventPayload::ReimbursedNativeWithdrawal { withdrawal_id, .. } => s
-                .record_reimbursed_icp_to_evm(IcpToEvmIdentifier::new(
-                    nat_to_ledger_burn_index(&withdrawal_id),
-                    chain_id,
-                )),
-            AppicEventPayload::ReimbursedErc20Withdrawal { withdrawal_id, .. } => s
-                .record_reimbursed_icp_to_evm(IcpToEvmIdentifier::new(
-                    nat_to_ledger_burn_index(&withdrawal_id),
-                    chain_id,
-                )),
             AppicEventPayload::SkippedBlock { .. } => {}
             AppicEventPayload::AddedErc20Token { .. } => {}
             AppicEventPayload::AcceptedErc20WithdrawalRequest {
@@ -287,7 +377,6 @@ fn apply_state_transition(
                 erc20_ledger_burn_index,
                 from,
                 from_subaccount,
-                created_at,
                 ..
             } => s.record_accepted_icp_to_evm(
                 IcpToEvmIdentifier::new(
@@ -302,30 +391,46 @@ fn apply_state_transition(
                 Some(erc20_ledger_burn_index),
                 from,
                 from_subaccount,
-                Some(created_at),
                 operator,
                 chain_id,
                 event.timestamp,
             ),
-            AppicEventPayload::FailedErc20WithdrawalRequest { withdrawal_id, .. } => s
-                .record_reimbursed_icp_to_evm(IcpToEvmIdentifier::new(
-                    nat_to_ledger_burn_index(&withdrawal_id),
-                    chain_id,
-                )),
+            // The ERC-20 burn failed, so the native fee burn is reimbursed
+            AppicEventPayload::FailedErc20WithdrawalRequest {
+                withdrawal_id,
+                reimbursed_amount,
+                ..
+            } => s.record_native_reimbursed_icp_to_evm(
+                IcpToEvmIdentifier::new(nat_to_ledger_burn_index(&withdrawal_id), chain_id),
+                reimbursed_amount,
+                None,
+                event.timestamp,
+            ),
             AppicEventPayload::MintedErc20 {
                 event_source,
                 mint_block_index,
                 ..
-            } => s.record_minted_evm_to_icp(
-                EvmToIcpTxIdentifier::new(&event_source.transaction_hash, chain_id),
-                evm_to_icp_fee,
-                nat_to_ledger_mint_index(&mint_block_index),
-            ),
-            AppicEventPayload::QuarantinedDeposit { event_source } => s
-                .record_quarantined_evm_to_icp(EvmToIcpTxIdentifier::new(
-                    &event_source.transaction_hash,
-                    chain_id,
-                )),
+            } => {
+                let Some(identifier) =
+                    evm_to_icp_identifier(&event_source.transaction_hash, chain_id)
+                else {
+                    return;
+                };
+                s.record_minted_evm_to_icp(
+                    identifier,
+                    evm_to_icp_fee,
+                    nat_to_ledger_mint_index(&mint_block_index),
+                    event.timestamp,
+                )
+            }
+            AppicEventPayload::QuarantinedDeposit { event_source } => {
+                let Some(identifier) =
+                    evm_to_icp_identifier(&event_source.transaction_hash, chain_id)
+                else {
+                    return;
+                };
+                s.record_quarantined_evm_to_icp(identifier)
+            }
             AppicEventPayload::QuarantinedReimbursement { index } => s
                 .record_quarantined_reimbursed_icp_to_evm(IcpToEvmIdentifier::new(
                     index.into(),
@@ -0,0 +1,117 @@
+// Search over a bare string pasted into the explorer search box.
+// The kind of input is detected from its shape and looked up across all chains and operators.
+
+use std::str::FromStr;
+
+use candid::{CandidType, Principal};
+use ic_ethereum_types::Address;
+use icrc_ledger_types::icrc1::account::Account;
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::{CandidEvmToken, CandidIcpToken, TokenPair, Transaction};
+use crate::state::TransactionHash;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SearchQuery {
+    TransactionHash(TransactionHash),
+    Address(Address),
+    Principal(Principal),
+    Account(Account),
+    // Either a withdrawal id (ledger burn index) or a ledger mint index
+    Index(u64),
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> Option<Self> {
+        let query = query.trim();
+
+        if let Ok(index) = query.parse::<u64>() {
+            return Some(Self::Index(index));
+        }
+        // Hashes and addresses are both hex and are told apart by their length
+        if let Ok(transaction_hash) = TransactionHash::from_str(query) {
+            return Some(Self::TransactionHash(transaction_hash));
+        }
+        if let Ok(address) = Address::from_str(query) {
+            return Some(Self::Address(address));
+        }
+        // The textual form of an account with the default subaccount is its owner
+        if let Ok(principal) = Principal::from_text(query) {
+            return Some(Self::Principal(principal));
+        }
+        Account::from_str(query).ok().map(Self::Account)
+    }
+
+    pub fn kind(&self) -> SearchQueryKind {
+        match self {
+            Self::TransactionHash(_) => SearchQueryKind::TransactionHash,
+            Self::Address(_) => SearchQueryKind::Address,
+            Self::Principal(_) => SearchQueryKind::Principal,
+            Self::Account(_) => SearchQueryKind::Account,
+            Self::Index(_) => SearchQueryKind::Index,
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SearchQueryKind {
+    TransactionHash,
+    Address,
+    Principal,
+    Account,
+    Index,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchResults {
+    pub query_kind: SearchQueryKind,
+    pub transactions: Vec<Transaction>,
+    pub evm_tokens: Vec<CandidEvmToken>,
+    pub icp_tokens: Vec<CandidIcpToken>,
+    pub bridge_pairs: Vec<TokenPair>,
+}
+
+impl SearchResults {
+    pub fn new(query_kind: SearchQueryKind) -> Self {
+        Self {
+            query_kind,
+            transactions: vec![],
+            evm_tokens: vec![],
+            icp_tokens: vec![],
+            bridge_pairs: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_kind_of_query() {
+        let kind = |query: &str| SearchQuery::parse(query).map(|query| query.kind());
+
+        assert_eq!(kind(" 42 "), Some(SearchQueryKind::Index));
+        assert_eq!(
+            kind("0x8218f324b45a8cd36f38586b062e3884588d926035f08e1dcd3605160b3ebd42"),
+            Some(SearchQueryKind::TransactionHash)
+        );
+        assert_eq!(
+            kind("0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54"),
+            Some(SearchQueryKind::Address)
+        );
+        assert_eq!(
+            kind("ryjl3-tyaaa-aaaaa-aaaba-cai"),
+            Some(SearchQueryKind::Principal)
+        );
+        let account = Account {
+            owner: Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap(),
+            subaccount: Some([1; 32]),
+        };
+        assert_eq!(
+            SearchQuery::parse(&account.to_string()),
+            Some(SearchQuery::Account(account))
+        );
+        assert_eq!(kind("not a query"), None);
+    }
+}
@@ -1,9 +1,14 @@
 use crate::numeric::LedgerMintIndex;
+use crate::UPDATE_USD_PRICE;
 use candid::{CandidType, Nat, Principal};
+use ic_canister_log::log;
 use ic_ethereum_types::Address;
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
+use ic_stable_structures::Cell as StableCell;
 use ic_stable_structures::DefaultMemoryImpl;
+use ic_stable_structures::Log as StableLog;
 use ic_stable_structures::{storable::Bound, BTreeMap, Storable};
+use icrc_ledger_types::icrc1::account::Account;
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
@@ -11,20 +16,66 @@ use std::cell::RefCell;
 use std::hash::{Hash, Hasher};
 
 use storage_config::{
-    evm_to_icp_memory, evm_token_list_id, icp_to_evm_memory, icp_token_list_id, minter_memory,
-    supported_appic_tokens_memory_id, supported_ckerc20_tokens_memory_id,
+    bridge_stats_memory, completed_backfills_memory, deposit_discrepancies_memory,
+    deposit_submissions_memory, evm_to_icp_memory, evm_token_list_id, fee_revenue_memory,
+    fee_schedules_memory, gas_costs_memory, icp_to_evm_memory, icp_token_list_id,
+    latency_samples_memory, legacy_evm_to_icp_memory, legacy_icp_to_evm_memory,
+    logger_events_data_memory, logger_events_index_memory, minter_activity_memory, minter_memory,
+    recent_activity_memory, state_version_memory, statement_index_memory, stuck_alerts_memory,
+    stuck_thresholds_memory, supported_appic_tokens_memory_id, supported_ckerc20_tokens_memory_id,
+    token_activity_memory, tx_counts_memory, waiting_txs_memory,
 };
+pub use storage_config::{stable_memory_sizes, task_runs_memory};
 
 use std::str::FromStr;
 
+use crate::account_summary::{
+    AccountSummary, AccountTokenKey, AccountTokenSummary, AccountTokenTotals,
+};
+use crate::bridge_stats::{
+    BridgeDirection, BridgeStats, BridgeStatsBucket, BridgeStatsFilters, BridgeStatsKey,
+    BridgeStatsRange, Granularity, MAX_BRIDGE_STATS_BUCKETS,
+};
+use crate::deposit_verification::{
+    compare_deposit, CandidDepositDiscrepancy, DepositDiscrepancy, DepositSubmission,
+};
 use crate::endpoints::{
-    AddEvmToIcpTx, AddIcpToEvmTx, CandidEvmToIcp, CandidEvmToken, CandidIcpToEvm, CandidIcpToken,
-    MinterArgs, TokenPair, Transaction, TransactionSearchParam,
+    AddEvmToIcpTx, AddEvmToIcpTxError, AddIcpToEvmTx, CandidEvmToIcp, CandidEvmToken,
+    CandidFeeScheduleEntry, CandidIcpToEvm, CandidIcpToken, LoggerQueryError, MinterArgs,
+    MinterFeeSchedule, MinterStatus, TokenPair, Transaction, TransactionSearchParam,
+};
+use crate::export::{export_map, ExportArgs, ExportCollection, ExportPage, MAX_EXPORT_PAGE_LENGTH};
+use crate::fee_revenue::{
+    FeeRevenue, FeeRevenueKey, FeeRevenueReport, GetFeeRevenueArgs, MAX_FEE_REVENUE_ROWS,
 };
+use crate::gas_costs::{GasCostKey, GasCostSamples, TokenKind, WithdrawalQuote};
+use crate::guard::TaskType;
+use crate::latency::{EstimatedCompletion, LatencyKey, LatencySamples};
+use crate::logger_events::{
+    certify_logger_events_tip, current_time, ChangeSource, Hash as LoggerEventHash, LoggerEvent,
+    StateChange,
+};
+use crate::logs::INFO;
+use crate::metrics::TxCountKey;
 use crate::numeric::{BlockNumber, Erc20TokenAmount, LedgerBurnIndex};
+use crate::recent_activity::{
+    redact, RecentActivityKey, RecentTransactions, RecentTransactionsFilters,
+    MAX_RECENT_TRANSACTIONS, MAX_SCANNED_RECENT_ACTIVITY,
+};
 use crate::scrape_events::NATIVE_ERC20_ADDRESS;
+use crate::search::{SearchQuery, SearchResults};
+use crate::statement::{format_amount, StatementKey, StatementRow};
+use crate::stuck_transactions::{
+    Alert, AlertKey, CandidAlert, StuckReason, StuckThresholdArgs, StuckThresholds,
+};
+use crate::token_activity::{
+    TokenActivityKey, TokenIndex, TokenTransactions, TokenTransactionsFilters,
+    MAX_SCANNED_TOKEN_ACTIVITY, MAX_TOKEN_TRANSACTIONS,
+};
 
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
+
+mod legacy;
 
 use crate::minter_clinet::appic_minter_types::events::{TransactionReceipt, TransactionStatus};
 
@@ -80,6 +131,73 @@ impl Minter {
     }
 }
 
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub struct FeeScheduleKey {
+    pub minter_key: MinterKey,
+    pub effective_from_timestamp: u64,
+}
+
+// Fees charged by a minter from `effective_from_timestamp` until the next entry of its schedule
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct FeeScheduleEntry {
+    // Last event observed on the minter when the fees were recorded
+    pub effective_from_event: u64,
+    pub effective_from_timestamp: u64,
+    pub evm_to_icp_fee: Erc20TokenAmount,
+    pub icp_to_evm_fee: Erc20TokenAmount,
+}
+
+// A scrape that started this long ago without completing is considered stalled
+const STALLED_SCRAPE_NS: u64 = 10 * 60 * 1_000_000_000;
+
+// A minter not successfully scraped for this long is considered lagging
+const LAGGING_AFTER_NS: u64 = 5 * 60 * 1_000_000_000;
+
+// Number of unscraped events a minter can have while still being healthy
+const MAX_HEALTHY_LAG: u64 = 100;
+
+#[derive(Clone, CandidType, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum MinterHealth {
+    Healthy,
+    Lagging,
+    Failing,
+}
+
+// Scraping activity of a single minter, used to derive its health
+#[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct MinterActivity {
+    pub last_scrape_attempt_at: Option<u64>,
+    pub last_scrape_success_at: Option<u64>,
+    pub last_event_applied_at: Option<u64>,
+    pub consecutive_failures: u32,
+}
+
+impl MinterActivity {
+    pub fn health(&self, lag: u64, now: u64) -> MinterHealth {
+        // An attempt without a later success means the scrape either is in progress or trapped
+        let stalled = match self.last_scrape_attempt_at {
+            Some(attempt) if self.last_scrape_success_at.map_or(true, |s| s < attempt) => {
+                now.saturating_sub(attempt) > STALLED_SCRAPE_NS
+            }
+            _ => false,
+        };
+
+        if self.consecutive_failures > 0 || stalled {
+            return MinterHealth::Failing;
+        }
+
+        let behind = self.last_scrape_success_at.map_or(true, |success| {
+            now.saturating_sub(success) > LAGGING_AFTER_NS
+        });
+
+        if lag > MAX_HEALTHY_LAG || behind {
+            MinterHealth::Lagging
+        } else {
+            MinterHealth::Healthy
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Ord, Eq, PartialOrd, Debug, Deserialize, Serialize)]
 pub struct MinterKey(pub ChainId, pub Operator);
 
@@ -99,7 +217,69 @@ impl From<&Minter> for MinterKey {
     }
 }
 
-type TransactionHash = String;
+/// A 32-byte EVM transaction hash.
+/// Hashes are accepted in any case and with or without the `0x` prefix, and are
+/// displayed and stored in the canonical lowercase form with the prefix.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TransactionHash([u8; 32]);
+
+impl TransactionHash {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl FromStr for TransactionHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        if digits.len() != 64 {
+            return Err(format!(
+                "transaction hash should have 64 hex digits, got {}",
+                digits.len()
+            ));
+        }
+        let mut bytes = [0_u8; 32];
+        hex::decode_to_slice(digits, &mut bytes)
+            .map_err(|e| format!("transaction hash is not valid hex: {e}"))?;
+        Ok(Self(bytes))
+    }
+}
+
+impl fmt::Display for TransactionHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for TransactionHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+// Stored as the canonical string, so records written before hashes were validated
+// keep the same encoding when their hash was already canonical
+impl Serialize for TransactionHash {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for TransactionHash {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hash = String::deserialize(deserializer)?;
+        Self::from_str(&hash).map_err(serde::de::Error::custom)
+    }
+}
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
 pub struct EvmToIcpTxIdentifier(TransactionHash, ChainId);
@@ -107,17 +287,40 @@ pub struct EvmToIcpTxIdentifier(TransactionHash, ChainId);
 impl EvmToIcpTxIdentifier {
     /// Creates a new `EvmToIcpTxIdentifier` instance.
     pub fn new(transaction_hash: &TransactionHash, chain_id: ChainId) -> Self {
-        Self(transaction_hash.clone(), chain_id)
+        Self(*transaction_hash, chain_id)
+    }
+
+    pub fn transaction_hash(&self) -> &TransactionHash {
+        &self.0
+    }
+
+    pub fn chain_id(&self) -> ChainId {
+        self.1
     }
 }
 
-impl From<&AddEvmToIcpTx> for EvmToIcpTxIdentifier {
-    fn from(value: &AddEvmToIcpTx) -> Self {
-        Self::new(&value.transaction_hash, ChainId::from(&value.chain_id))
+impl TryFrom<&AddEvmToIcpTx> for EvmToIcpTxIdentifier {
+    type Error = AddEvmToIcpTxError;
+
+    fn try_from(value: &AddEvmToIcpTx) -> Result<Self, Self::Error> {
+        let transaction_hash = TransactionHash::from_str(&value.transaction_hash)
+            .map_err(|_e| AddEvmToIcpTxError::InvalidTransactionHash)?;
+        Ok(Self::new(&transaction_hash, ChainId::from(&value.chain_id)))
     }
 }
 
-#[derive(Clone, CandidType, PartialEq, Ord, Eq, PartialOrd, Debug, Deserialize, Serialize)]
+#[derive(
+    Clone,
+    CandidType,
+    PartialEq,
+    Ord,
+    Eq,
+    PartialOrd,
+    Debug,
+    Deserialize,
+    Serialize,
+    strum_macros::IntoStaticStr,
+)]
 pub enum EvmToIcpStatus {
     PendingVerification,
     Accepted,
@@ -142,8 +345,15 @@ pub struct EvmToIcpTx {
     pub icrc_ledger_id: Option<Principal>,
     pub status: EvmToIcpStatus,
     pub verified: bool,
-    pub time: u64,
+    // Nanoseconds since the epoch. `submitted_at` is set when a user submits the deposit,
+    // the others from the timestamp of the minter event that moved it to that step.
+    pub submitted_at: Option<u64>,
+    pub accepted_at: Option<u64>,
+    pub minted_at: Option<u64>,
     pub operator: Operator,
+    // USD price of the icrc twin and USD value of `value` when the deposit was accepted
+    pub accepted_usd_price: Option<String>,
+    pub accepted_usd_value: Option<String>,
 }
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
@@ -154,6 +364,14 @@ impl IcpToEvmIdentifier {
     pub fn new(ledger_burn_index: LedgerBurnIndex, chain_id: ChainId) -> Self {
         Self(ledger_burn_index, chain_id)
     }
+
+    pub fn native_ledger_burn_index(&self) -> LedgerBurnIndex {
+        self.0
+    }
+
+    pub fn chain_id(&self) -> ChainId {
+        self.1
+    }
 }
 
 impl From<&AddIcpToEvmTx> for IcpToEvmIdentifier {
@@ -164,7 +382,113 @@ impl From<&AddIcpToEvmTx> for IcpToEvmIdentifier {
     }
 }
 
-#[derive(CandidType, Clone, PartialEq, Ord, Eq, PartialOrd, Debug, Deserialize, Serialize)]
+// Stable id of a transaction returned to clients, built from the key it is stored under.
+// Its textual form is opaque to clients and only needs to round trip through `FromStr`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub enum TransactionId {
+    EvmToIcp(EvmToIcpTxIdentifier),
+    IcpToEvm(IcpToEvmIdentifier),
+}
+
+impl TransactionId {
+    pub fn chain_id(&self) -> ChainId {
+        match self {
+            Self::EvmToIcp(identifier) => identifier.chain_id(),
+            Self::IcpToEvm(identifier) => identifier.chain_id(),
+        }
+    }
+}
+
+const EVM_TO_ICP_ID_PREFIX: &str = "evm_to_icp";
+const ICP_TO_EVM_ID_PREFIX: &str = "icp_to_evm";
+
+impl fmt::Display for TransactionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EvmToIcp(identifier) => write!(
+                f,
+                "{EVM_TO_ICP_ID_PREFIX}:{}:{}",
+                identifier.chain_id().0,
+                identifier.transaction_hash()
+            ),
+            Self::IcpToEvm(identifier) => write!(
+                f,
+                "{ICP_TO_EVM_ID_PREFIX}:{}:{}",
+                identifier.chain_id().0,
+                identifier.native_ledger_burn_index().get()
+            ),
+        }
+    }
+}
+
+impl FromStr for TransactionId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let (Some(direction), Some(chain_id), Some(key), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!("transaction id should have three parts, got {s}"));
+        };
+        let chain_id = chain_id
+            .parse::<u64>()
+            .map(ChainId)
+            .map_err(|e| format!("invalid chain id in transaction id: {e}"))?;
+
+        match direction {
+            EVM_TO_ICP_ID_PREFIX => {
+                let transaction_hash = TransactionHash::from_str(key)?;
+                Ok(Self::EvmToIcp(EvmToIcpTxIdentifier::new(
+                    &transaction_hash,
+                    chain_id,
+                )))
+            }
+            ICP_TO_EVM_ID_PREFIX => {
+                let ledger_burn_index = key
+                    .parse::<u64>()
+                    .map(LedgerBurnIndex::new)
+                    .map_err(|e| format!("invalid burn index in transaction id: {e}"))?;
+                Ok(Self::IcpToEvm(IcpToEvmIdentifier::new(
+                    ledger_burn_index,
+                    chain_id,
+                )))
+            }
+            _ => Err(format!("unknown direction in transaction id: {direction}")),
+        }
+    }
+}
+
+impl From<&EvmToIcpTx> for TransactionId {
+    fn from(value: &EvmToIcpTx) -> Self {
+        Self::EvmToIcp(EvmToIcpTxIdentifier::new(
+            &value.transaction_hash,
+            value.chain_id,
+        ))
+    }
+}
+
+impl From<&IcpToEvmTx> for TransactionId {
+    fn from(value: &IcpToEvmTx) -> Self {
+        Self::IcpToEvm(IcpToEvmIdentifier::new(
+            value.native_ledger_burn_index,
+            value.chain_id,
+        ))
+    }
+}
+
+#[derive(
+    CandidType,
+    Clone,
+    PartialEq,
+    Ord,
+    Eq,
+    PartialOrd,
+    Debug,
+    Deserialize,
+    Serialize,
+    strum_macros::IntoStaticStr,
+)]
 pub enum IcpToEvmStatus {
     PendingVerification,
     Accepted,
@@ -188,7 +512,12 @@ pub struct IcpToEvmTx {
     pub from: Principal,
     pub chain_id: ChainId,
     pub from_subaccount: Option<[u8; 32]>,
-    pub time: u64,
+    // Nanoseconds since the epoch. `submitted_at` is set when a user submits the withdrawal,
+    // the others from the timestamp of the minter event that moved it to that step.
+    pub submitted_at: Option<u64>,
+    pub accepted_at: Option<u64>,
+    pub finalized_at: Option<u64>,
+    pub reimbursed_at: Option<u64>,
     pub max_transaction_fee: Option<Erc20TokenAmount>,
     pub effective_gas_price: Option<Erc20TokenAmount>,
     pub gas_used: Option<Erc20TokenAmount>,
@@ -199,6 +528,22 @@ pub struct IcpToEvmTx {
     pub verified: bool,
     pub status: IcpToEvmStatus,
     pub operator: Operator,
+    // USD price of the icrc twin and USD value of `withdrawal_amount` when the withdrawal was accepted
+    pub accepted_usd_price: Option<String>,
+    pub accepted_usd_value: Option<String>,
+    // Native tokens returned to the user, either the whole withdrawal or the unused fee of an ERC-20 withdrawal
+    pub native_reimbursement: Option<Reimbursement>,
+    // ERC-20 twin tokens returned to the user after a failed ERC-20 withdrawal
+    pub erc20_reimbursement: Option<Reimbursement>,
+}
+
+/// Tokens minted back to the user after a withdrawal burned them.
+#[derive(Clone, PartialEq, Ord, Eq, PartialOrd, Debug, Deserialize, Serialize)]
+pub struct Reimbursement {
+    pub ledger_id: Option<Principal>,
+    pub amount: Erc20TokenAmount,
+    // None if the minter did not report the block of the reimbursement
+    pub reimbursed_in_block: Option<LedgerMintIndex>,
 }
 
 #[derive(Clone, PartialEq, Ord, Eq, PartialOrd, Debug, Deserialize, Serialize)]
@@ -280,6 +625,9 @@ pub struct State {
     // List of all minters including (cketh dfinity and appic minters)
     pub minters: BTreeMap<MinterKey, Minter, StableMemory>,
 
+    // Scraping activity of each minter
+    pub minter_activity: BTreeMap<MinterKey, MinterActivity, StableMemory>,
+
     // List of all evm_to_icp transactions
     pub evm_to_icp_txs: BTreeMap<EvmToIcpTxIdentifier, EvmToIcpTx, StableMemory>,
 
@@ -291,22 +639,110 @@ pub struct State {
 
     pub evm_token_list: BTreeMap<Erc20Identifier, EvmToken, StableMemory>,
     pub icp_token_list: BTreeMap<Principal, IcpToken, StableMemory>,
+
+    // Hash-chained log of every change made to the maps above
+    pub logger_events: StableLog<LoggerEvent, StableMemory, StableMemory>,
+
+    // Hourly and daily volume of completed transactions
+    pub bridge_stats: BTreeMap<BridgeStatsKey, BridgeStats, StableMemory>,
+
+    // Daily minter fees and gas paid per minter and token
+    pub fee_revenue: BTreeMap<FeeRevenueKey, FeeRevenue, StableMemory>,
+
+    // Fees of every minter over time
+    pub fee_schedules: BTreeMap<FeeScheduleKey, FeeScheduleEntry, StableMemory>,
+
+    // Most recent completion times per chain, operator and direction
+    pub latency_samples: BTreeMap<LatencyKey, LatencySamples, StableMemory>,
+
+    // Chains whose stuck transaction thresholds differ from the defaults
+    pub stuck_thresholds: BTreeMap<ChainId, StuckThresholds, StableMemory>,
+
+    // Open alerts of transactions that stopped progressing
+    pub stuck_alerts: BTreeMap<AlertKey, Alert, StableMemory>,
+
+    // Most recent gas costs of finalized withdrawals per chain and token kind
+    pub gas_costs: BTreeMap<GasCostKey, GasCostSamples, StableMemory>,
+
+    // Callers of deposits submitted by users that are not verified yet
+    pub deposit_submissions: BTreeMap<EvmToIcpTxIdentifier, DepositSubmission, StableMemory>,
+
+    // Fields of user submitted deposits that did not match the minter event
+    pub deposit_discrepancies: BTreeMap<EvmToIcpTxIdentifier, DepositDiscrepancy, StableMemory>,
+
+    // Every transaction keyed by the time it first appeared, newest first
+    pub recent_activity: BTreeMap<RecentActivityKey, (), StableMemory>,
+
+    // Every transaction keyed by its icrc ledger and by its erc20 contract, newest first
+    pub token_activity: BTreeMap<TokenActivityKey, (), StableMemory>,
+
+    // One-time backfills that already ran, with the time they completed
+    pub completed_backfills: BTreeMap<String, u64, StableMemory>,
+
+    // Accepted deposits and signed or replaced withdrawals, the transactions that can get
+    // stuck, with the time they entered that status
+    pub waiting_txs: BTreeMap<AlertKey, u64, StableMemory>,
+
+    // Every transaction keyed by the principal of its ICP side, oldest first
+    pub statement_index: BTreeMap<StatementKey, (), StableMemory>,
+
+    // Bumped by every `mutate_state`, so reads at the same version see the same state
+    pub state_version: StableCell<u64, StableMemory>,
+
+    // Number of stored transactions by direction, chain, status and verification
+    pub tx_counts: BTreeMap<TxCountKey, u64, StableMemory>,
+}
+
+// Key of `migrate_legacy_transactions` in the completed backfills, set once no legacy record
+// is left
+const LEGACY_TRANSACTIONS_MIGRATION: &str = "legacy_transactions";
+
+// Number of legacy records moved by a single call of `migrate_legacy_transactions`.
+// Each record is converted, logged and indexed, so larger batches risk the instruction limit.
+pub const LEGACY_MIGRATION_BATCH: usize = 1_000;
+
+// Outcome of a batch of the legacy migration
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct LegacyMigration {
+    pub deposits: u64,
+    pub withdrawals: u64,
+    // Records that could not be converted or that a kept record superseded
+    pub dropped: u64,
+    // Whether records are left for another batch
+    pub remaining: bool,
 }
 
 impl State {
+    // Updates the fees of a minter, effective from the given (event, timestamp) or from the last
+    // observed event and the current time. A backdated timestamp needs the event it belongs
+    // to, the cursor of the minter would not match it. Events before that keep being charged
+    // the previous fees.
     pub fn update_minter_fees(
         &mut self,
         minter_key: &MinterKey,
         evm_to_icp_fee: Erc20TokenAmount,
         icp_to_evm_fee: Erc20TokenAmount,
+        effective_from: Option<(u64, u64)>,
     ) {
         if let Some(minter) = self.minters.get(minter_key) {
+            let (effective_from_event, effective_from_timestamp) =
+                effective_from.unwrap_or((minter.last_observed_event, current_time()));
+            self.record_fee_schedule_entry(
+                minter_key,
+                FeeScheduleEntry {
+                    effective_from_event,
+                    effective_from_timestamp,
+                    evm_to_icp_fee,
+                    icp_to_evm_fee,
+                },
+            );
+
             let new_minter = Minter {
                 evm_to_icp_fee,
                 icp_to_evm_fee,
                 ..minter
             };
-            self.record_minter(new_minter);
+            self.insert_minter(new_minter, ChangeSource::AdminAction);
         }
     }
 
@@ -316,7 +752,7 @@ impl State {
                 last_observed_event,
                 ..minter
             };
-            self.record_minter(new_minter);
+            self.insert_minter(new_minter, ChangeSource::MinterEvent);
         }
     }
 
@@ -326,7 +762,7 @@ impl State {
                 last_scraped_event,
                 ..minter
             };
-            self.record_minter(new_minter);
+            self.insert_minter(new_minter, ChangeSource::MinterEvent);
         }
     }
 
@@ -334,6 +770,82 @@ impl State {
         self.minters.iter().collect()
     }
 
+    pub fn get_minter_activity(&self, minter_key: &MinterKey) -> MinterActivity {
+        self.minter_activity.get(minter_key).unwrap_or_default()
+    }
+
+    // Gets the scraping status and health of every minter
+    pub fn get_minters_status(&self, now: u64) -> Vec<MinterStatus> {
+        self.minters
+            .iter()
+            .map(|(minter_key, minter)| {
+                let activity = self.get_minter_activity(&minter_key);
+                let lag = minter
+                    .last_observed_event
+                    .saturating_sub(minter.last_scraped_event);
+                MinterStatus {
+                    minter_id: minter.id,
+                    operator: minter.operator,
+                    chain_id: minter.chain_id.into(),
+                    evm_to_icp_fee: minter.evm_to_icp_fee.into(),
+                    icp_to_evm_fee: minter.icp_to_evm_fee.into(),
+                    last_observed_event: minter.last_observed_event,
+                    last_scraped_event: minter.last_scraped_event,
+                    lag,
+                    last_event_applied_at: activity.last_event_applied_at,
+                    last_scrape_success_at: activity.last_scrape_success_at,
+                    consecutive_scrape_failures: activity.consecutive_failures,
+                    health: activity.health(lag, now),
+                }
+            })
+            .collect()
+    }
+
+    pub fn record_scrape_attempt(&mut self, minter_key: &MinterKey, timestamp: u64) {
+        let activity = self.get_minter_activity(minter_key);
+        self.minter_activity.insert(
+            minter_key.clone(),
+            MinterActivity {
+                last_scrape_attempt_at: Some(timestamp),
+                ..activity
+            },
+        );
+    }
+
+    pub fn record_scrape_success(&mut self, minter_key: &MinterKey, timestamp: u64) {
+        let activity = self.get_minter_activity(minter_key);
+        self.minter_activity.insert(
+            minter_key.clone(),
+            MinterActivity {
+                last_scrape_success_at: Some(timestamp),
+                consecutive_failures: 0,
+                ..activity
+            },
+        );
+    }
+
+    pub fn record_scrape_failure(&mut self, minter_key: &MinterKey) {
+        let activity = self.get_minter_activity(minter_key);
+        self.minter_activity.insert(
+            minter_key.clone(),
+            MinterActivity {
+                consecutive_failures: activity.consecutive_failures.saturating_add(1),
+                ..activity
+            },
+        );
+    }
+
+    pub fn record_events_applied(&mut self, minter_key: &MinterKey, timestamp: u64) {
+        let activity = self.get_minter_activity(minter_key);
+        self.minter_activity.insert(
+            minter_key.clone(),
+            MinterActivity {
+                last_event_applied_at: Some(timestamp),
+                ..activity
+            },
+        );
+    }
+
     pub fn if_chain_id_exists(&self, chain_id: ChainId) -> bool {
         for (_minter_key, minter) in self.get_minters() {
             if minter.chain_id == chain_id {
@@ -344,7 +856,135 @@ impl State {
     }
 
     pub fn record_minter(&mut self, minter: Minter) {
-        self.minters.insert(MinterKey::from(&minter), minter);
+        self.seed_fee_schedule(&minter);
+        self.insert_minter(minter, ChangeSource::AdminAction);
+    }
+
+    // Gets the fee schedule of a minter, ordered by the time the fees became effective
+    pub fn get_fee_schedule(&self, minter_key: &MinterKey) -> Vec<FeeScheduleEntry> {
+        let first_key = FeeScheduleKey {
+            minter_key: minter_key.clone(),
+            effective_from_timestamp: 0,
+        };
+        self.fee_schedules
+            .range(first_key..)
+            .take_while(|(key, _entry)| &key.minter_key == minter_key)
+            .map(|(_key, entry)| entry)
+            .collect()
+    }
+
+    // Gets the fee schedules of all minters
+    pub fn get_fee_schedules(&self) -> Vec<MinterFeeSchedule> {
+        self.minters
+            .iter()
+            .map(|(minter_key, minter)| {
+                let mut entries = self.get_fee_schedule(&minter_key);
+                if entries.is_empty() {
+                    entries.push(FeeScheduleEntry {
+                        effective_from_event: 0,
+                        effective_from_timestamp: 0,
+                        evm_to_icp_fee: minter.evm_to_icp_fee,
+                        icp_to_evm_fee: minter.icp_to_evm_fee,
+                    });
+                }
+                MinterFeeSchedule {
+                    chain_id: minter_key.chain_id().into(),
+                    operator: minter_key.operator(),
+                    entries: entries
+                        .into_iter()
+                        .map(CandidFeeScheduleEntry::from)
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    // Gets the (evm_to_icp_fee, icp_to_evm_fee) of a minter in effect at `timestamp`.
+    // Minters without a schedule never changed their fees. Otherwise the fees before the
+    // first scheduled change are not known, since the logger did not record since when the
+    // previous fees applied, so they are left unpriced instead of guessed.
+    pub fn get_minter_fees_at(
+        &self,
+        minter_key: &MinterKey,
+        timestamp: u64,
+    ) -> Option<(Erc20TokenAmount, Erc20TokenAmount)> {
+        let schedule = self.get_fee_schedule(minter_key);
+        if schedule.is_empty() {
+            return self
+                .minters
+                .get(minter_key)
+                .map(|minter| (minter.evm_to_icp_fee, minter.icp_to_evm_fee));
+        }
+        schedule
+            .into_iter()
+            .take_while(|entry| entry.effective_from_timestamp <= timestamp)
+            .last()
+            .map(|entry| (entry.evm_to_icp_fee, entry.icp_to_evm_fee))
+    }
+
+    fn record_fee_schedule_entry(&mut self, minter_key: &MinterKey, entry: FeeScheduleEntry) {
+        self.record_logger_event(
+            ChangeSource::AdminAction,
+            StateChange::RecordedMinterFees {
+                chain_id: minter_key.chain_id().into(),
+                operator: minter_key.operator(),
+                entry: CandidFeeScheduleEntry::from(entry.clone()),
+            },
+        );
+        self.fee_schedules.insert(
+            FeeScheduleKey {
+                minter_key: minter_key.clone(),
+                effective_from_timestamp: entry.effective_from_timestamp,
+            },
+            entry,
+        );
+    }
+
+    fn insert_minter(&mut self, minter: Minter, source: ChangeSource) {
+        let minter_key = MinterKey::from(&minter);
+        // Scraping rewrites minters with unchanged cursors, which is not worth logging
+        if self.minters.get(&minter_key).as_ref() == Some(&minter) {
+            return;
+        }
+        self.record_logger_event(
+            source,
+            StateChange::RecordedMinter(MinterArgs::from(&minter)),
+        );
+        self.minters.insert(minter_key, minter);
+    }
+
+    // Appends a change to the logger's own event log, chained to the previous event
+    fn record_logger_event(&mut self, source: ChangeSource, change: StateChange) {
+        let event = LoggerEvent::new(
+            current_time(),
+            self.logger_events_tip_hash(),
+            source,
+            change,
+        );
+        self.logger_events
+            .append(&event)
+            .expect("BUG: failed to append logger event");
+    }
+
+    pub fn state_version(&self) -> u64 {
+        *self.state_version.get()
+    }
+
+    // Bumped by the writers of the exported maps whenever they change an entry, so an equal
+    // version is proof that no exported entry changed
+    fn bump_state_version(&mut self) {
+        let version = self.state_version() + 1;
+        self.state_version
+            .set(version)
+            .expect("BUG: failed to store state version");
+    }
+
+    pub fn logger_events_tip_hash(&self) -> Option<LoggerEventHash> {
+        self.logger_events
+            .len()
+            .checked_sub(1)
+            .and_then(|index| self.logger_events.get(index))
+            .map(|event| event.hash())
     }
 
     pub fn get_icrc_twin_for_erc20(
@@ -372,14 +1012,142 @@ impl State {
         self.icp_to_evm_txs.get(identifier).is_some()
     }
 
-    pub fn record_new_evm_to_icp(&mut self, identifier: EvmToIcpTxIdentifier, tx: EvmToIcpTx) {
-        self.evm_to_icp_txs.insert(identifier, tx);
+    // Records a transaction submitted by `caller` before it is verified by a minter event
+    pub fn record_new_evm_to_icp(
+        &mut self,
+        identifier: EvmToIcpTxIdentifier,
+        tx: EvmToIcpTx,
+        caller: Principal,
+    ) {
+        self.insert_deposit_submission(
+            identifier.clone(),
+            DepositSubmission {
+                caller,
+                submitted_at: tx.submitted_at.unwrap_or_default(),
+            },
+        );
+        self.insert_evm_to_icp(identifier, tx, ChangeSource::UserSubmission { caller });
+    }
+
+    fn insert_deposit_submission(
+        &mut self,
+        identifier: EvmToIcpTxIdentifier,
+        submission: DepositSubmission,
+    ) {
+        self.record_logger_event(
+            ChangeSource::UserSubmission {
+                caller: submission.caller,
+            },
+            StateChange::RecordedDepositSubmission {
+                transaction_hash: identifier.transaction_hash().to_string(),
+                chain_id: identifier.chain_id().into(),
+                caller: submission.caller,
+                submitted_at: submission.submitted_at,
+            },
+        );
+        self.deposit_submissions.insert(identifier, submission);
+    }
+
+    fn remove_deposit_submission(
+        &mut self,
+        identifier: &EvmToIcpTxIdentifier,
+        source: ChangeSource,
+    ) -> Option<DepositSubmission> {
+        let submission = self.deposit_submissions.remove(identifier)?;
+        self.record_logger_event(
+            source,
+            StateChange::RemovedDepositSubmission {
+                transaction_hash: identifier.transaction_hash().to_string(),
+                chain_id: identifier.chain_id().into(),
+            },
+        );
+        Some(submission)
+    }
+
+    fn insert_evm_to_icp(
+        &mut self,
+        identifier: EvmToIcpTxIdentifier,
+        tx: EvmToIcpTx,
+        source: ChangeSource,
+    ) {
+        self.record_logger_event(
+            source,
+            StateChange::RecordedEvmToIcp(CandidEvmToIcp::from(tx.clone()).into()),
+        );
+        // Deposits wait for their mint from the time they were accepted
+        let waiting_since = tx
+            .accepted_at
+            .filter(|_accepted_at| tx.status == EvmToIcpStatus::Accepted);
+        self.set_waiting_since(AlertKey::EvmToIcp(identifier.clone()), waiting_since);
+        self.put_evm_to_icp(identifier, tx);
+    }
+
+    // Keeps the index of the transactions that can get stuck in sync with their status.
+    // Transactions that moved on are removed along with their alert.
+    fn set_waiting_since(&mut self, key: AlertKey, waiting_since: Option<u64>) {
+        match waiting_since {
+            Some(waiting_since) => {
+                self.waiting_txs.insert(key, waiting_since);
+            }
+            None => {
+                self.waiting_txs.remove(&key);
+                self.stuck_alerts.remove(&key);
+            }
+        }
+    }
+
+    fn count_tx(&mut self, key: TxCountKey) {
+        let count = self.tx_counts.get(&key).unwrap_or_default();
+        self.tx_counts.insert(key, count + 1);
+    }
+
+    fn uncount_tx(&mut self, key: TxCountKey) {
+        match self.tx_counts.get(&key).unwrap_or_default() {
+            0 | 1 => self.tx_counts.remove(&key),
+            count => self.tx_counts.insert(key, count - 1),
+        };
+    }
+
+    // Stores a deposit and keeps its entries in the recent activity, token activity and
+    // statement indexes and the transaction counts up to date
+    fn put_evm_to_icp(&mut self, identifier: EvmToIcpTxIdentifier, tx: EvmToIcpTx) {
+        let activity_key = RecentActivityKey::for_evm_to_icp(&tx);
+        let token_keys = TokenActivityKey::for_evm_to_icp(&tx);
+        let statement_key = StatementKey::for_evm_to_icp(&tx);
+        let count_key = TxCountKey::for_evm_to_icp(&tx);
+        let previous = self.evm_to_icp_txs.insert(identifier, tx.clone());
+        // Re-scraped events write the transaction they already stored
+        if previous.as_ref() == Some(&tx) {
+            return;
+        }
+        self.bump_state_version();
+        if let Some(previous) = previous {
+            self.uncount_tx(TxCountKey::for_evm_to_icp(&previous));
+            let previous_key = RecentActivityKey::for_evm_to_icp(&previous);
+            if previous_key != activity_key {
+                self.recent_activity.remove(&previous_key);
+            }
+            for previous_key in TokenActivityKey::for_evm_to_icp(&previous) {
+                if !token_keys.contains(&previous_key) {
+                    self.token_activity.remove(&previous_key);
+                }
+            }
+            let previous_key = StatementKey::for_evm_to_icp(&previous);
+            if previous_key != statement_key {
+                self.statement_index.remove(&previous_key);
+            }
+        }
+        self.recent_activity.insert(activity_key, ());
+        for token_key in token_keys {
+            self.token_activity.insert(token_key, ());
+        }
+        self.statement_index.insert(statement_key, ());
+        self.count_tx(count_key);
     }
 
     pub fn record_accepted_evm_to_icp(
         &mut self,
         identifier: EvmToIcpTxIdentifier,
-        transaction_hash: TransactionHash,
         block_number: Nat,
         from_address: String,
         value: Nat,
@@ -396,26 +1164,49 @@ impl State {
         let parsed_erc20_address = Address::from_str(&erc20_contract_address)
             .expect("Should not fail converting erc20_contract_address to Address");
 
+        let value = nat_to_erc20_amount(value);
+        let icrc_ledger_id = self
+            .get_icrc_twin_for_erc20(&Erc20Identifier(parsed_erc20_address, chain_id), &operator);
+
         if let Some(tx) = self.evm_to_icp_txs.get(&identifier) {
-            // Update only the necessary fields in the existing transaction
+            // Keep the snapshot taken when the deposit was first accepted
+            let (accepted_usd_price, accepted_usd_value) = match tx.accepted_usd_price {
+                Some(_) => (tx.accepted_usd_price.clone(), tx.accepted_usd_value.clone()),
+                None => self.get_usd_snapshot(icrc_ledger_id, value, timestamp),
+            };
+
+            // Replace every field a user could have submitted with the values of the event,
+            // the minter does not report the gas spent by deposits
             let new_tx = EvmToIcpTx {
                 verified: true,
                 block_number: Some(nat_to_block_number(block_number)),
                 from_address: parsed_from_address,
-                value: nat_to_erc20_amount(value),
+                value,
                 principal,
                 erc20_contract_address: parsed_erc20_address,
                 subaccount,
+                icrc_ledger_id,
+                operator,
+                total_gas_spent: None,
                 status: EvmToIcpStatus::Accepted,
-                ..tx
+                accepted_at: tx.accepted_at.or(Some(timestamp)),
+                accepted_usd_price,
+                accepted_usd_value,
+                ..tx.clone()
             };
-            self.record_new_evm_to_icp(identifier, new_tx);
+            if !tx.verified {
+                self.record_deposit_discrepancy(&identifier, &tx, &new_tx, timestamp);
+            }
+            self.insert_evm_to_icp(identifier, new_tx, ChangeSource::MinterEvent);
         } else {
+            let (accepted_usd_price, accepted_usd_value) =
+                self.get_usd_snapshot(icrc_ledger_id, value, timestamp);
+
             // Create a new transaction only if one doses not already exist
             let new_tx = EvmToIcpTx {
                 from_address: parsed_from_address,
-                transaction_hash,
-                value: nat_to_erc20_amount(value),
+                transaction_hash: identifier.0,
+                value,
                 block_number: Some(nat_to_block_number(block_number)),
                 actual_received: None,
                 principal,
@@ -423,68 +1214,180 @@ impl State {
                 chain_id,
                 total_gas_spent: None,
                 erc20_contract_address: parsed_erc20_address,
-                icrc_ledger_id: self.get_icrc_twin_for_erc20(
-                    &Erc20Identifier(parsed_erc20_address, chain_id),
-                    &operator,
-                ),
+                icrc_ledger_id,
                 status: EvmToIcpStatus::Accepted,
                 verified: true,
-                time: timestamp,
+                submitted_at: None,
+                accepted_at: Some(timestamp),
+                minted_at: None,
                 operator,
                 ledger_mint_index: None,
+                accepted_usd_price,
+                accepted_usd_value,
             };
 
-            self.record_new_evm_to_icp(identifier, new_tx);
+            self.insert_evm_to_icp(identifier, new_tx, ChangeSource::MinterEvent);
         }
     }
 
+    // `evm_to_icp_fee` is none if the fee in effect when the deposit was minted is not known
     pub fn record_minted_evm_to_icp(
         &mut self,
         identifier: EvmToIcpTxIdentifier,
-        evm_to_icp_fee: Erc20TokenAmount,
+        evm_to_icp_fee: Option<Erc20TokenAmount>,
         ledger_mint_index: LedgerMintIndex,
+        timestamp: u64,
     ) {
         if let Some(tx) = self.evm_to_icp_txs.get(&identifier) {
-            // Fee calculation
-            let actual_received = if is_native_token(&tx.erc20_contract_address) {
-                Some(tx.value.checked_sub(evm_to_icp_fee).unwrap_or(tx.value))
+            // Re-scraped events must not be counted twice
+            if tx.status != EvmToIcpStatus::Minted {
+                self.record_bridge_volume(
+                    BridgeDirection::EvmToIcp,
+                    tx.chain_id,
+                    tx.erc20_contract_address,
+                    tx.operator,
+                    tx.accepted_usd_value.as_deref(),
+                    tx.value,
+                    timestamp,
+                );
+                self.record_latency(
+                    BridgeDirection::EvmToIcp,
+                    tx.chain_id,
+                    tx.operator,
+                    tx.accepted_at,
+                    timestamp,
+                );
+            }
+
+            // Fee calculation, only native deposits are charged a fee
+            let charged_fee = if is_native_token(&tx.erc20_contract_address) {
+                evm_to_icp_fee.map(|fee| {
+                    if tx.value >= fee {
+                        fee
+                    } else {
+                        Erc20TokenAmount::ZERO
+                    }
+                })
             } else {
-                Some(tx.value)
+                Some(Erc20TokenAmount::ZERO)
             };
+            let actual_received =
+                charged_fee.map(|fee| tx.value.checked_sub(fee).unwrap_or(tx.value));
+
+            // Deposits minted at an unknown fee are left out of the fee revenue
+            if tx.status != EvmToIcpStatus::Minted {
+                if let Some(charged_fee) = charged_fee {
+                    self.record_fee_revenue(
+                        MinterKey(tx.chain_id, tx.operator),
+                        tx.erc20_contract_address,
+                        charged_fee,
+                        Erc20TokenAmount::ZERO,
+                        timestamp,
+                    );
+                }
+            }
 
             // Transaction update
             let new_tx = EvmToIcpTx {
                 actual_received,
                 ledger_mint_index: Some(ledger_mint_index),
                 status: EvmToIcpStatus::Minted,
+                minted_at: tx.minted_at.or(Some(timestamp)),
                 ..tx
             };
-            self.record_new_evm_to_icp(identifier, new_tx);
+            self.insert_evm_to_icp(identifier, new_tx, ChangeSource::MinterEvent);
         }
     }
 
     pub fn record_invalid_evm_to_icp(&mut self, identifier: EvmToIcpTxIdentifier, reason: String) {
         if let Some(tx) = self.evm_to_icp_txs.get(&identifier) {
+            self.remove_deposit_submission(&identifier, ChangeSource::MinterEvent);
             let new_tx = EvmToIcpTx {
                 status: EvmToIcpStatus::Invalid(reason),
+                total_gas_spent: None,
                 ..tx
             };
-            self.record_new_evm_to_icp(identifier, new_tx);
+            self.insert_evm_to_icp(identifier, new_tx, ChangeSource::MinterEvent);
         }
     }
 
     pub fn record_quarantined_evm_to_icp(&mut self, identifier: EvmToIcpTxIdentifier) {
         if let Some(tx) = self.evm_to_icp_txs.get(&identifier) {
+            self.remove_deposit_submission(&identifier, ChangeSource::MinterEvent);
             let new_tx = EvmToIcpTx {
                 status: EvmToIcpStatus::Quarantined,
+                total_gas_spent: None,
                 ..tx
             };
-            self.record_new_evm_to_icp(identifier, new_tx);
+            self.insert_evm_to_icp(identifier, new_tx, ChangeSource::MinterEvent);
+        }
+    }
+
+    // Records a transaction submitted by `caller` before it is verified by a minter event
+    pub fn record_new_icp_to_evm(
+        &mut self,
+        identifier: IcpToEvmIdentifier,
+        tx: IcpToEvmTx,
+        caller: Principal,
+    ) {
+        self.insert_icp_to_evm(identifier, tx, ChangeSource::UserSubmission { caller });
+    }
+
+    fn insert_icp_to_evm(
+        &mut self,
+        identifier: IcpToEvmIdentifier,
+        tx: IcpToEvmTx,
+        source: ChangeSource,
+    ) {
+        self.record_logger_event(
+            source,
+            StateChange::RecordedIcpToEvm(CandidIcpToEvm::from(tx.clone()).into()),
+        );
+        // Withdrawals start waiting when they are signed or replaced, see `record_signed_icp_to_evm`
+        if !matches!(
+            tx.status,
+            IcpToEvmStatus::SignedTransaction | IcpToEvmStatus::ReplacedTransaction
+        ) {
+            self.set_waiting_since(AlertKey::IcpToEvm(identifier.clone()), None);
         }
+        self.put_icp_to_evm(identifier, tx);
     }
 
-    pub fn record_new_icp_to_evm(&mut self, identifier: IcpToEvmIdentifier, tx: IcpToEvmTx) {
-        self.icp_to_evm_txs.insert(identifier, tx);
+    // Stores a withdrawal and keeps its entries in the recent activity, token activity and
+    // statement indexes and the transaction counts up to date
+    fn put_icp_to_evm(&mut self, identifier: IcpToEvmIdentifier, tx: IcpToEvmTx) {
+        let activity_key = RecentActivityKey::for_icp_to_evm(&tx);
+        let token_keys = TokenActivityKey::for_icp_to_evm(&tx);
+        let statement_key = StatementKey::for_icp_to_evm(&tx);
+        let count_key = TxCountKey::for_icp_to_evm(&tx);
+        let previous = self.icp_to_evm_txs.insert(identifier, tx.clone());
+        // Re-scraped events write the transaction they already stored
+        if previous.as_ref() == Some(&tx) {
+            return;
+        }
+        self.bump_state_version();
+        if let Some(previous) = previous {
+            self.uncount_tx(TxCountKey::for_icp_to_evm(&previous));
+            let previous_key = RecentActivityKey::for_icp_to_evm(&previous);
+            if previous_key != activity_key {
+                self.recent_activity.remove(&previous_key);
+            }
+            for previous_key in TokenActivityKey::for_icp_to_evm(&previous) {
+                if !token_keys.contains(&previous_key) {
+                    self.token_activity.remove(&previous_key);
+                }
+            }
+            let previous_key = StatementKey::for_icp_to_evm(&previous);
+            if previous_key != statement_key {
+                self.statement_index.remove(&previous_key);
+            }
+        }
+        self.recent_activity.insert(activity_key, ());
+        for token_key in token_keys {
+            self.token_activity.insert(token_key, ());
+        }
+        self.statement_index.insert(statement_key, ());
+        self.count_tx(count_key);
     }
 
     pub fn record_accepted_icp_to_evm(
@@ -498,7 +1401,6 @@ impl State {
         erc20_ledger_burn_index: Option<Nat>,
         from: Principal,
         from_subaccount: Option<[u8; 32]>,
-        created_at: Option<u64>,
         operator: Operator,
         chain_id: ChainId,
         timestamp: u64,
@@ -517,8 +1419,16 @@ impl State {
             erc20_ledger_burn_index.map(|burn_index| LedgerBurnIndex::new(nat_to_u64(&burn_index)));
 
         if let Some(tx) = self.icp_to_evm_txs.get(&identifier) {
+            // Keep the snapshot taken when the withdrawal was first accepted
+            let (accepted_usd_price, accepted_usd_value) = match tx.accepted_usd_price {
+                Some(_) => (tx.accepted_usd_price.clone(), tx.accepted_usd_value.clone()),
+                None => self.get_usd_snapshot(tx.icrc_ledger_id, withdrawal_amount, timestamp),
+            };
+
             let new_tx = IcpToEvmTx {
                 verified: true,
+                accepted_usd_price,
+                accepted_usd_value,
                 max_transaction_fee,
                 withdrawal_amount,
                 erc20_contract_address: erc20_address,
@@ -528,13 +1438,16 @@ impl State {
                 from,
                 from_subaccount,
                 status: IcpToEvmStatus::Accepted,
+                accepted_at: tx.accepted_at.or(Some(timestamp)),
                 ..tx
             };
 
-            self.record_new_icp_to_evm(identifier, new_tx);
+            self.insert_icp_to_evm(identifier, new_tx, ChangeSource::MinterEvent);
         } else {
             let icrc_ledger_id =
                 self.get_icrc_twin_for_erc20(&Erc20Identifier(erc20_address, chain_id), &operator);
+            let (accepted_usd_price, accepted_usd_value) =
+                self.get_usd_snapshot(icrc_ledger_id, withdrawal_amount, timestamp);
 
             let new_tx = IcpToEvmTx {
                 native_ledger_burn_index,
@@ -543,7 +1456,10 @@ impl State {
                 destination: destination_address,
                 from,
                 from_subaccount,
-                time: created_at.unwrap_or(timestamp),
+                submitted_at: None,
+                accepted_at: Some(timestamp),
+                finalized_at: None,
+                reimbursed_at: None,
                 max_transaction_fee,
                 erc20_ledger_burn_index,
                 icrc_ledger_id,
@@ -556,9 +1472,13 @@ impl State {
                 gas_used: None,
                 transaction_hash: None,
                 total_gas_spent: None,
+                accepted_usd_price,
+                accepted_usd_value,
+                native_reimbursement: None,
+                erc20_reimbursement: None,
             };
 
-            self.record_new_icp_to_evm(identifier, new_tx);
+            self.insert_icp_to_evm(identifier, new_tx, ChangeSource::MinterEvent);
         }
     }
 
@@ -568,76 +1488,182 @@ impl State {
                 status: IcpToEvmStatus::Created,
                 ..tx
             };
-            self.record_new_icp_to_evm(identifier, new_tx);
+            self.insert_icp_to_evm(identifier, new_tx, ChangeSource::MinterEvent);
         }
     }
 
-    pub fn record_signed_icp_to_evm(&mut self, identifier: IcpToEvmIdentifier) {
+    // A withdrawal waits for its transaction to be finalized from the time it was signed
+    pub fn record_signed_icp_to_evm(&mut self, identifier: IcpToEvmIdentifier, timestamp: u64) {
         if let Some(tx) = self.icp_to_evm_txs.get(&identifier) {
             let new_tx = IcpToEvmTx {
                 status: IcpToEvmStatus::SignedTransaction,
                 ..tx
             };
-            self.record_new_icp_to_evm(identifier, new_tx);
+            self.insert_icp_to_evm(identifier.clone(), new_tx, ChangeSource::MinterEvent);
+            self.set_waiting_since(AlertKey::IcpToEvm(identifier), Some(timestamp));
         }
     }
 
-    pub fn record_replaced_icp_to_evm(&mut self, identifier: IcpToEvmIdentifier) {
+    // A replacement transaction is waited for from the time it replaced the previous one
+    pub fn record_replaced_icp_to_evm(&mut self, identifier: IcpToEvmIdentifier, timestamp: u64) {
         if let Some(tx) = self.icp_to_evm_txs.get(&identifier) {
             let new_tx = IcpToEvmTx {
                 status: IcpToEvmStatus::ReplacedTransaction,
                 ..tx
             };
-            self.record_new_icp_to_evm(identifier, new_tx);
+            self.insert_icp_to_evm(identifier.clone(), new_tx, ChangeSource::MinterEvent);
+            self.set_waiting_since(AlertKey::IcpToEvm(identifier), Some(timestamp));
         }
     }
 
+    // `icp_to_evm_fee` is none if the fee in effect when the withdrawal was finalized is not
+    // known, in which case the total spent and the amount received are left unset
     pub fn record_finalized_icp_to_evm(
         &mut self,
         identifier: IcpToEvmIdentifier,
         receipt: TransactionReceipt,
-        icp_to_evm_fee: Erc20TokenAmount,
+        icp_to_evm_fee: Option<Erc20TokenAmount>,
+        timestamp: u64,
     ) {
         if let Some(tx) = self.icp_to_evm_txs.get(&identifier) {
             let gas_used = nat_to_erc20_amount(receipt.gas_used);
             let effective_gas_price = nat_to_erc20_amount(receipt.effective_gas_price);
 
-            let total_gas_spent = gas_used
-                .checked_mul(effective_gas_price)
-                .unwrap()
-                .checked_add(icp_to_evm_fee)
-                .unwrap();
+            let gas_fee = gas_used.checked_mul(effective_gas_price).unwrap();
+            let total_gas_spent = icp_to_evm_fee.map(|fee| gas_fee.checked_add(fee).unwrap());
 
             let actual_received = if is_native_token(&tx.erc20_contract_address) {
-                tx.withdrawal_amount.checked_sub(total_gas_spent)
+                total_gas_spent.and_then(|spent| tx.withdrawal_amount.checked_sub(spent))
             } else {
                 Some(tx.withdrawal_amount)
             };
 
+            // A malformed receipt hash is a bug in the minter, the finalization is still recorded
+            let transaction_hash = match TransactionHash::from_str(&receipt.transaction_hash) {
+                Ok(transaction_hash) => Some(transaction_hash),
+                Err(_) => {
+                    log!(
+                        INFO,
+                        "[Scraping Events] malformed receipt hash {} of withdrawal {:?}",
+                        receipt.transaction_hash,
+                        identifier
+                    );
+                    tx.transaction_hash
+                }
+            };
+
             let status = match receipt.status {
                 TransactionStatus::Success => IcpToEvmStatus::Successful,
                 TransactionStatus::Failure => IcpToEvmStatus::Failed,
             };
+
+            if status == IcpToEvmStatus::Successful && tx.status != IcpToEvmStatus::Successful {
+                self.record_bridge_volume(
+                    BridgeDirection::IcpToEvm,
+                    tx.chain_id,
+                    tx.erc20_contract_address,
+                    tx.operator,
+                    tx.accepted_usd_value.as_deref(),
+                    tx.withdrawal_amount,
+                    timestamp,
+                );
+                self.record_latency(
+                    BridgeDirection::IcpToEvm,
+                    tx.chain_id,
+                    tx.operator,
+                    tx.accepted_at,
+                    timestamp,
+                );
+            }
+
+            // Gas is paid for failed transactions as well
+            if !matches!(
+                tx.status,
+                IcpToEvmStatus::Successful | IcpToEvmStatus::Failed
+            ) {
+                // The gas is known even if the minter fee is not, which is then left out
+                self.record_fee_revenue(
+                    MinterKey(tx.chain_id, tx.operator),
+                    tx.erc20_contract_address,
+                    icp_to_evm_fee.unwrap_or(Erc20TokenAmount::ZERO),
+                    gas_fee,
+                    timestamp,
+                );
+                self.record_gas_cost(tx.chain_id, &tx.erc20_contract_address, gas_fee);
+            }
+
             let new_tx = IcpToEvmTx {
                 actual_received,
-                transaction_hash: Some(receipt.transaction_hash),
+                transaction_hash,
                 gas_used: Some(gas_used),
                 effective_gas_price: Some(effective_gas_price),
-                total_gas_spent: Some(total_gas_spent),
+                total_gas_spent,
+                status,
+                finalized_at: tx.finalized_at.or(Some(timestamp)),
+                ..tx
+            };
+            self.insert_icp_to_evm(identifier, new_tx, ChangeSource::MinterEvent);
+        }
+    }
+
+    // Records native tokens minted back to the user. For ERC-20 withdrawals this is either the
+    // fee of a withdrawal that could not be processed, or the unused part of the fee of one
+    // that succeeded, in which case the status is kept.
+    pub fn record_native_reimbursed_icp_to_evm(
+        &mut self,
+        identifier: IcpToEvmIdentifier,
+        reimbursed_amount: Nat,
+        reimbursed_in_block: Option<LedgerMintIndex>,
+        timestamp: u64,
+    ) {
+        if let Some(tx) = self.icp_to_evm_txs.get(&identifier) {
+            let ledger_id = self.get_icrc_twin_for_erc20(
+                &Erc20Identifier(native_token_address(), tx.chain_id),
+                &tx.operator,
+            );
+            let status = if !is_native_token(&tx.erc20_contract_address)
+                && tx.status == IcpToEvmStatus::Successful
+            {
+                IcpToEvmStatus::Successful
+            } else {
+                IcpToEvmStatus::Reimbursed
+            };
+
+            let new_tx = IcpToEvmTx {
+                native_reimbursement: Some(Reimbursement {
+                    ledger_id,
+                    amount: nat_to_erc20_amount(reimbursed_amount),
+                    reimbursed_in_block,
+                }),
                 status,
+                reimbursed_at: Some(timestamp),
                 ..tx
             };
-            self.record_new_icp_to_evm(identifier, new_tx);
+            self.insert_icp_to_evm(identifier, new_tx, ChangeSource::MinterEvent);
         }
     }
 
-    pub fn record_reimbursed_icp_to_evm(&mut self, identifier: IcpToEvmIdentifier) {
+    // Records ERC-20 twin tokens minted back to the user after a failed ERC-20 withdrawal
+    pub fn record_erc20_reimbursed_icp_to_evm(
+        &mut self,
+        identifier: IcpToEvmIdentifier,
+        ledger_id: Principal,
+        reimbursed_amount: Nat,
+        reimbursed_in_block: LedgerMintIndex,
+        timestamp: u64,
+    ) {
         if let Some(tx) = self.icp_to_evm_txs.get(&identifier) {
             let new_tx = IcpToEvmTx {
+                erc20_reimbursement: Some(Reimbursement {
+                    ledger_id: Some(ledger_id),
+                    amount: nat_to_erc20_amount(reimbursed_amount),
+                    reimbursed_in_block: Some(reimbursed_in_block),
+                }),
                 status: IcpToEvmStatus::Reimbursed,
+                reimbursed_at: Some(timestamp),
                 ..tx
             };
-            self.record_new_icp_to_evm(identifier, new_tx);
+            self.insert_icp_to_evm(identifier, new_tx, ChangeSource::MinterEvent);
         }
     }
 
@@ -647,32 +1673,700 @@ impl State {
                 status: IcpToEvmStatus::QuarantinedReimbursement,
                 ..tx
             };
-            self.record_new_icp_to_evm(identifier, new_tx);
+            self.insert_icp_to_evm(identifier, new_tx, ChangeSource::MinterEvent);
+        }
+    }
+
+    // Adds a completed transaction to the hourly and daily volume buckets. The transaction is
+    // valued with the USD snapshot taken when it was accepted, not at the current price.
+    fn record_bridge_volume(
+        &mut self,
+        direction: BridgeDirection,
+        chain_id: ChainId,
+        token: Address,
+        operator: Operator,
+        accepted_usd_value: Option<&str>,
+        amount: Erc20TokenAmount,
+        timestamp: u64,
+    ) {
+        let usd_value = accepted_usd_value.and_then(|value| f64::from_str(value).ok());
+        for granularity in Granularity::ALL {
+            let key = BridgeStatsKey {
+                granularity,
+                bucket_start: granularity.bucket_start(timestamp),
+                chain_id,
+                token,
+                operator,
+                direction,
+            };
+            let stats = self.bridge_stats.get(&key).unwrap_or_default();
+            self.bridge_stats.insert(key, stats.add(amount, usd_value));
+        }
+    }
+
+    // Adds the minter fee and gas paid for a transaction to the daily totals of its minter.
+    // Both amounts are in the native token of the chain.
+    fn record_fee_revenue(
+        &mut self,
+        minter_key: MinterKey,
+        token: Address,
+        minter_fee: Erc20TokenAmount,
+        gas_fee: Erc20TokenAmount,
+        timestamp: u64,
+    ) {
+        let native_ledger_id = self.get_icrc_twin_for_erc20(
+            &Erc20Identifier(native_token_address(), minter_key.chain_id()),
+            &minter_key.operator(),
+        );
+        let usd_values = native_ledger_id.and_then(|ledger_id| {
+            Some((
+                self.get_usd_value(&ledger_id, minter_fee)?,
+                self.get_usd_value(&ledger_id, gas_fee)?,
+            ))
+        });
+
+        let key = FeeRevenueKey::new(minter_key, token, timestamp);
+        let revenue = self.fee_revenue.get(&key).unwrap_or_default();
+        self.fee_revenue
+            .insert(key, revenue.add((minter_fee, gas_fee), usd_values));
+    }
+
+    // Gets the daily fee revenue of the minters within the range of `args`
+    pub fn get_fee_revenue(&self, args: GetFeeRevenueArgs) -> Vec<FeeRevenueReport> {
+        let token = match args.token.as_ref().map(|token| Address::from_str(token)) {
+            Some(Ok(token)) => Some(token),
+            Some(Err(_)) => return vec![],
+            None => None,
+        };
+        let first_key = FeeRevenueKey::first_of_day(Granularity::Daily.bucket_start(args.from));
+
+        self.fee_revenue
+            .range(first_key..)
+            .take_while(|(key, _revenue)| key.day_start < args.to)
+            .filter(|(key, _revenue)| args.matches(key, &token))
+            .take(MAX_FEE_REVENUE_ROWS)
+            .map(FeeRevenueReport::from)
+            .collect()
+    }
+
+    // Adds the gas paid by a finalized withdrawal to the rolling history of its chain
+    fn record_gas_cost(&mut self, chain_id: ChainId, token: &Address, gas_fee: Erc20TokenAmount) {
+        let key = GasCostKey {
+            chain_id,
+            token_kind: token_kind(token),
+        };
+        let samples = self.gas_costs.get(&key).unwrap_or_default();
+        self.gas_costs.insert(key, samples.push(gas_fee));
+    }
+
+    // Quotes the fees of withdrawing `amount` of `token` with the minter bridging it at `now`.
+    // Returns None if no minter of the chain bridges the token.
+    pub fn quote_withdrawal(
+        &self,
+        chain_id: ChainId,
+        token: Address,
+        amount: Erc20TokenAmount,
+        now: u64,
+    ) -> Option<WithdrawalQuote> {
+        let token_kind = token_kind(&token);
+        let minter_key = self
+            .minters
+            .iter()
+            .map(|(key, _minter)| key)
+            .filter(|key| key.chain_id() == chain_id)
+            .find(|key| {
+                token_kind == TokenKind::Native
+                    || self
+                        .get_icrc_twin_for_erc20(&Erc20Identifier(token, chain_id), &key.operator())
+                        .is_some()
+            })?;
+        let (_evm_to_icp_fee, minter_fee) = self.get_minter_fees_at(&minter_key, now)?;
+
+        let samples = self
+            .gas_costs
+            .get(&GasCostKey {
+                chain_id,
+                token_kind,
+            })
+            .unwrap_or_default();
+        let percentiles = samples.percentiles();
+        let expected_native_fee = percentiles
+            .and_then(|(expected_gas_cost, _)| expected_gas_cost.checked_add(minter_fee));
+        let expected_received = match token_kind {
+            TokenKind::Native => expected_native_fee.and_then(|fee| amount.checked_sub(fee)),
+            TokenKind::Erc20 => Some(amount),
+        };
+
+        Some(WithdrawalQuote {
+            operator: minter_key.operator(),
+            minter_fee: minter_fee.into(),
+            expected_gas_cost: percentiles.map(|(expected, _max)| expected.into()),
+            max_gas_cost: percentiles.map(|(_expected, max)| max.into()),
+            expected_native_fee: expected_native_fee.map(Into::into),
+            expected_received: expected_received.map(Into::into),
+            sample_count: samples.len() as u64,
+        })
+    }
+
+    // Adds the time a transaction took from acceptance to completion to the rolling window
+    fn record_latency(
+        &mut self,
+        direction: BridgeDirection,
+        chain_id: ChainId,
+        operator: Operator,
+        accepted_at: Option<u64>,
+        completed_at: u64,
+    ) {
+        let Some(accepted_at) = accepted_at else {
+            return;
+        };
+        let key = LatencyKey {
+            chain_id,
+            operator,
+            direction,
+        };
+        let samples = self.latency_samples.get(&key).unwrap_or_default();
+        self.latency_samples
+            .insert(key, samples.push(completed_at.saturating_sub(accepted_at)));
+    }
+
+    pub fn get_estimated_completion(
+        &self,
+        chain_id: ChainId,
+        operator: Operator,
+        direction: BridgeDirection,
+    ) -> Option<EstimatedCompletion> {
+        self.latency_samples
+            .get(&LatencyKey {
+                chain_id,
+                operator,
+                direction,
+            })?
+            .estimate()
+    }
+
+    // Median completion time of a pending transaction accepted at `accepted_at`
+    fn estimated_completion_at(
+        &self,
+        chain_id: ChainId,
+        operator: Operator,
+        direction: BridgeDirection,
+        accepted_at: Option<u64>,
+    ) -> Option<u64> {
+        let estimate = self.get_estimated_completion(chain_id, operator, direction)?;
+        Some(accepted_at?.saturating_add(estimate.p50_ns))
+    }
+
+    // Gets the volume time series of the buckets starting within `range`
+    pub fn get_bridge_stats(
+        &self,
+        range: BridgeStatsRange,
+        granularity: Granularity,
+        filters: BridgeStatsFilters,
+    ) -> Vec<BridgeStatsBucket> {
+        let token = match filters.token.as_ref().map(|token| Address::from_str(token)) {
+            Some(Ok(token)) => Some(token),
+            Some(Err(_)) => return vec![],
+            None => None,
+        };
+        let first_key =
+            BridgeStatsKey::first_of_bucket(granularity, granularity.bucket_start(range.from));
+
+        self.bridge_stats
+            .range(first_key..)
+            .take_while(|(key, _stats)| {
+                key.granularity == granularity && key.bucket_start < range.to
+            })
+            .filter(|(key, _stats)| filters.matches(key, &token))
+            .take(MAX_BRIDGE_STATS_BUCKETS)
+            .map(BridgeStatsBucket::from)
+            .collect()
+    }
+
+    pub fn set_stuck_thresholds(&mut self, chain_id: ChainId, thresholds: StuckThresholds) {
+        self.record_logger_event(
+            ChangeSource::AdminAction,
+            StateChange::RecordedStuckThresholds(StuckThresholdArgs {
+                chain_id: chain_id.into(),
+                deposit_threshold_ns: thresholds.deposit_ns,
+                withdrawal_threshold_ns: thresholds.withdrawal_ns,
+            }),
+        );
+        self.stuck_thresholds.insert(chain_id, thresholds);
+    }
+
+    pub fn get_stuck_thresholds(&self, chain_id: ChainId) -> StuckThresholds {
+        self.stuck_thresholds.get(&chain_id).unwrap_or_default()
+    }
+
+    // Raises an alert for every transaction that has been waiting for the minter in its current
+    // status longer than the threshold of its chain, and clears the alerts of transactions
+    // that moved on. Only the index of waiting transactions is scanned.
+    // Returns the number of raised and cleared alerts.
+    pub fn update_stuck_alerts(&mut self, now: u64) -> (u64, u64) {
+        let mut stuck: Vec<(AlertKey, Alert)> = vec![];
+
+        for (key, waiting_since) in self.waiting_txs.iter() {
+            let (reason, chain_id, operator, accepted_at) = match &key {
+                AlertKey::EvmToIcp(identifier) => {
+                    let Some(tx) = self.evm_to_icp_txs.get(identifier) else {
+                        continue;
+                    };
+                    (
+                        StuckReason::DepositNotMinted,
+                        tx.chain_id,
+                        tx.operator,
+                        tx.accepted_at,
+                    )
+                }
+                AlertKey::IcpToEvm(identifier) => {
+                    let Some(tx) = self.icp_to_evm_txs.get(identifier) else {
+                        continue;
+                    };
+                    let reason = match tx.status {
+                        IcpToEvmStatus::SignedTransaction => {
+                            StuckReason::WithdrawalSignedNotFinalized
+                        }
+                        IcpToEvmStatus::ReplacedTransaction => {
+                            StuckReason::WithdrawalReplacedNotFinalized
+                        }
+                        _ => continue,
+                    };
+                    (reason, tx.chain_id, tx.operator, tx.accepted_at)
+                }
+            };
+            let thresholds = self.get_stuck_thresholds(chain_id);
+            let threshold = match key {
+                AlertKey::EvmToIcp(_) => thresholds.deposit_ns,
+                AlertKey::IcpToEvm(_) => thresholds.withdrawal_ns,
+            };
+            if now.saturating_sub(waiting_since) >= threshold {
+                stuck.push((
+                    key,
+                    Alert {
+                        reason,
+                        chain_id,
+                        operator,
+                        accepted_at: accepted_at.unwrap_or_default(),
+                        detected_at: now,
+                    },
+                ));
+            }
+        }
+
+        let stuck_keys: std::collections::BTreeSet<&AlertKey> =
+            stuck.iter().map(|(key, _alert)| key).collect();
+        let cleared: Vec<AlertKey> = self
+            .stuck_alerts
+            .iter()
+            .map(|(key, _alert)| key)
+            .filter(|key| !stuck_keys.contains(key))
+            .collect();
+        for key in cleared.iter() {
+            self.stuck_alerts.remove(key);
         }
+
+        let mut raised = 0;
+        for (key, alert) in stuck {
+            // Keep the detection time of alerts that are already open
+            match self.stuck_alerts.get(&key) {
+                Some(open) if open.reason == alert.reason => {}
+                _ => {
+                    self.stuck_alerts.insert(key, alert);
+                    raised += 1;
+                }
+            }
+        }
+
+        (raised, cleared.len() as u64)
+    }
+
+    // Reports the fields of a user submitted deposit that do not match the minter event
+    // that verified it. The submission is forgotten either way.
+    fn record_deposit_discrepancy(
+        &mut self,
+        identifier: &EvmToIcpTxIdentifier,
+        submitted: &EvmToIcpTx,
+        verified: &EvmToIcpTx,
+        timestamp: u64,
+    ) {
+        let Some(submission) =
+            self.remove_deposit_submission(identifier, ChangeSource::MinterEvent)
+        else {
+            return;
+        };
+        let mismatches = compare_deposit(submitted, verified);
+        if mismatches.is_empty() {
+            return;
+        }
+        let discrepancy = DepositDiscrepancy {
+            submitted_by: submission.caller,
+            chain_id: verified.chain_id,
+            operator: verified.operator,
+            submitted_at: submission.submitted_at,
+            detected_at: timestamp,
+            mismatches,
+        };
+        self.record_logger_event(
+            ChangeSource::MinterEvent,
+            StateChange::RecordedDepositDiscrepancy(CandidDepositDiscrepancy::new(
+                identifier.clone(),
+                discrepancy.clone(),
+            )),
+        );
+        self.deposit_discrepancies
+            .insert(identifier.clone(), discrepancy);
+    }
+
+    // Gets the discrepancies of user submitted deposits, most recent first
+    pub fn get_deposit_discrepancies(&self) -> Vec<CandidDepositDiscrepancy> {
+        let mut discrepancies: Vec<CandidDepositDiscrepancy> = self
+            .deposit_discrepancies
+            .iter()
+            .map(|(identifier, discrepancy)| CandidDepositDiscrepancy::new(identifier, discrepancy))
+            .collect();
+        discrepancies.sort_by_key(|discrepancy| std::cmp::Reverse(discrepancy.detected_at));
+        discrepancies
+    }
+
+    // Gets the open alerts, oldest transaction first
+    pub fn get_alerts(&self, now: u64) -> Vec<CandidAlert> {
+        let mut alerts: Vec<CandidAlert> = self
+            .stuck_alerts
+            .iter()
+            .map(|(key, alert)| {
+                let waiting_since = self.waiting_txs.get(&key).unwrap_or(alert.accepted_at);
+                CandidAlert::new(key, alert, waiting_since, now)
+            })
+            .collect();
+        alerts.sort_by_key(|alert| alert.accepted_at);
+        alerts
     }
 
     pub fn all_unverified_icp_to_evm(&self) -> Vec<(IcpToEvmIdentifier, u64)> {
         self.icp_to_evm_txs
             .iter()
             .filter(|(_, tx)| !tx.verified) // Filter out verified transactions
-            .map(|(identifier, tx)| (identifier, tx.time)) // Map to the desired tuple
+            .map(|(identifier, tx)| (identifier, tx.submitted_at.unwrap_or_default())) // Map to the desired tuple
             .collect()
     }
 
     pub fn remove_unverified_icp_to_evm(&mut self, identifier: &IcpToEvmIdentifier) {
-        self.icp_to_evm_txs.remove(identifier);
+        if let Some(tx) = self.icp_to_evm_txs.remove(identifier) {
+            self.bump_state_version();
+            self.recent_activity
+                .remove(&RecentActivityKey::for_icp_to_evm(&tx));
+            for token_key in TokenActivityKey::for_icp_to_evm(&tx) {
+                self.token_activity.remove(&token_key);
+            }
+            self.statement_index
+                .remove(&StatementKey::for_icp_to_evm(&tx));
+            self.uncount_tx(TxCountKey::for_icp_to_evm(&tx));
+            self.record_logger_event(
+                ChangeSource::Cleanup,
+                StateChange::RemovedIcpToEvm {
+                    native_ledger_burn_index: identifier.0.get().into(),
+                    chain_id: identifier.1.into(),
+                },
+            );
+        }
     }
 
     pub fn all_unverified_evm_to_icp(&self) -> Vec<(EvmToIcpTxIdentifier, u64)> {
         self.evm_to_icp_txs
             .iter()
             .filter(|(_, tx)| !tx.verified) // Filter out verified transactions
-            .map(|(identifier, tx)| (identifier, tx.time)) // Map to the desired tuple
+            .map(|(identifier, tx)| (identifier, tx.submitted_at.unwrap_or_default())) // Map to the desired tuple
             .collect()
     }
 
     pub fn remove_unverified_evm_to_icp(&mut self, identifier: &EvmToIcpTxIdentifier) {
-        self.evm_to_icp_txs.remove(identifier);
+        self.remove_deposit_submission(identifier, ChangeSource::Cleanup);
+        if let Some(tx) = self.evm_to_icp_txs.remove(identifier) {
+            self.bump_state_version();
+            self.recent_activity
+                .remove(&RecentActivityKey::for_evm_to_icp(&tx));
+            for token_key in TokenActivityKey::for_evm_to_icp(&tx) {
+                self.token_activity.remove(&token_key);
+            }
+            self.statement_index
+                .remove(&StatementKey::for_evm_to_icp(&tx));
+            self.uncount_tx(TxCountKey::for_evm_to_icp(&tx));
+            self.record_logger_event(
+                ChangeSource::Cleanup,
+                StateChange::RemovedEvmToIcp {
+                    transaction_hash: identifier.0.to_string(),
+                    chain_id: identifier.1.into(),
+                },
+            );
+        }
+    }
+
+    // Moves up to `batch_size` transactions stored by the first release to the current maps,
+    // with their hashes in canonical form. Every moved transaction is logged as recorded by the
+    // migration, and every one that is dropped, either because it can not be converted or
+    // because a kept transaction supersedes it, is logged as removed under its previous key.
+    pub fn migrate_legacy_transactions(&mut self, batch_size: usize) -> LegacyMigration {
+        let mut legacy_evm_to_icp: BTreeMap<
+            legacy::EvmToIcpTxIdentifierV0,
+            legacy::EvmToIcpTxV0,
+            StableMemory,
+        > = BTreeMap::init(legacy_evm_to_icp_memory());
+        let mut legacy_icp_to_evm: BTreeMap<
+            IcpToEvmIdentifier,
+            legacy::IcpToEvmTxV0,
+            StableMemory,
+        > = BTreeMap::init(legacy_icp_to_evm_memory());
+        let mut migration = LegacyMigration::default();
+
+        let batch: Vec<_> = legacy_evm_to_icp.iter().take(batch_size).collect();
+        let budget = batch_size - batch.len();
+        for (identifier, tx) in batch {
+            legacy_evm_to_icp.remove(&identifier);
+            self.migrate_legacy_deposit(identifier, tx, &mut migration);
+        }
+
+        let batch: Vec<_> = legacy_icp_to_evm.iter().take(budget).collect();
+        for (identifier, tx) in batch {
+            legacy_icp_to_evm.remove(&identifier);
+            self.migrate_legacy_withdrawal(identifier, tx, &mut migration);
+        }
+
+        migration.remaining = !(legacy_evm_to_icp.is_empty() && legacy_icp_to_evm.is_empty());
+        if !migration.remaining {
+            self.completed_backfills
+                .insert(LEGACY_TRANSACTIONS_MIGRATION.to_string(), current_time());
+        }
+        migration
+    }
+
+    fn migrate_legacy_deposit(
+        &mut self,
+        legacy_identifier: legacy::EvmToIcpTxIdentifierV0,
+        tx: legacy::EvmToIcpTxV0,
+        migration: &mut LegacyMigration,
+    ) {
+        let converted = EvmToIcpTxIdentifier::try_from(legacy_identifier.clone())
+            .and_then(|identifier| Ok((identifier, EvmToIcpTx::try_from(tx)?)));
+        // A user submission and the minter record of the same deposit may only differ in how
+        // the hash was written, the verified one is kept. Deposits submitted with a malformed
+        // hash can never be verified.
+        let kept = converted.ok().filter(|(identifier, tx)| {
+            !self
+                .evm_to_icp_txs
+                .get(identifier)
+                .is_some_and(|existing| existing.verified || !tx.verified)
+        });
+
+        // Logged under the key it was stored with, unless that key does not change
+        if kept.as_ref().map_or(true, |(identifier, _tx)| {
+            identifier.transaction_hash().to_string() != legacy_identifier.0
+        }) {
+            self.record_logger_event(
+                ChangeSource::Migration,
+                StateChange::RemovedEvmToIcp {
+                    transaction_hash: legacy_identifier.0,
+                    chain_id: legacy_identifier.1.into(),
+                },
+            );
+        }
+        match kept {
+            Some((identifier, tx)) => {
+                // The first release kept no volume, completed deposits are counted as they move
+                if tx.status == EvmToIcpStatus::Minted {
+                    if let Some(timestamp) = tx.accepted_at.or(tx.submitted_at) {
+                        self.record_bridge_volume(
+                            BridgeDirection::EvmToIcp,
+                            tx.chain_id,
+                            tx.erc20_contract_address,
+                            tx.operator,
+                            None,
+                            tx.value,
+                            timestamp,
+                        );
+                    }
+                }
+                self.insert_evm_to_icp(identifier, tx, ChangeSource::Migration);
+                migration.deposits += 1;
+            }
+            None => migration.dropped += 1,
+        }
+    }
+
+    fn migrate_legacy_withdrawal(
+        &mut self,
+        identifier: IcpToEvmIdentifier,
+        tx: legacy::IcpToEvmTxV0,
+        migration: &mut LegacyMigration,
+    ) {
+        match IcpToEvmTx::try_from(tx) {
+            Ok(tx) => {
+                // The time a withdrawal was signed was not kept, it waits from the time it was
+                // accepted until its next status change
+                if matches!(
+                    tx.status,
+                    IcpToEvmStatus::SignedTransaction | IcpToEvmStatus::ReplacedTransaction
+                ) {
+                    self.set_waiting_since(AlertKey::IcpToEvm(identifier.clone()), tx.accepted_at);
+                }
+                if tx.status == IcpToEvmStatus::Successful {
+                    if let Some(timestamp) = tx.accepted_at.or(tx.submitted_at) {
+                        self.record_bridge_volume(
+                            BridgeDirection::IcpToEvm,
+                            tx.chain_id,
+                            tx.erc20_contract_address,
+                            tx.operator,
+                            None,
+                            tx.withdrawal_amount,
+                            timestamp,
+                        );
+                    }
+                }
+                self.insert_icp_to_evm(identifier, tx, ChangeSource::Migration);
+                migration.withdrawals += 1;
+            }
+            // The receipt hash is the only field that can fail to convert
+            Err(_) => {
+                self.record_logger_event(
+                    ChangeSource::Migration,
+                    StateChange::RemovedIcpToEvm {
+                        native_ledger_burn_index: identifier
+                            .native_ledger_burn_index()
+                            .get()
+                            .into(),
+                        chain_id: identifier.chain_id().into(),
+                    },
+                );
+                migration.dropped += 1;
+            }
+        }
+    }
+
+    // Whether transactions stored by the first release are still being moved
+    pub fn is_migrating_legacy_transactions(&self) -> bool {
+        !self
+            .completed_backfills
+            .contains_key(&LEGACY_TRANSACTIONS_MIGRATION.to_string())
+    }
+
+    // Gets a page of the transactions of a token, newest first, starting after `cursor`
+    pub fn get_token_transactions(
+        &self,
+        token: TokenIndex,
+        limit: usize,
+        cursor: Option<RecentActivityKey>,
+        filters: &TokenTransactionsFilters,
+    ) -> TokenTransactions {
+        let limit = limit.min(MAX_TOKEN_TRANSACTIONS);
+        let start = match &cursor {
+            Some(cursor) => TokenActivityKey {
+                token: token.clone(),
+                activity: cursor.clone(),
+            },
+            None => {
+                TokenActivityKey::first_at(token.clone(), filters.to_timestamp.unwrap_or(u64::MAX))
+            }
+        };
+
+        let mut last_key = None;
+        let mut scanned = 0;
+        let transactions: Vec<Transaction> = self
+            .token_activity
+            .range(start..)
+            .map(|(key, ())| key)
+            .take_while(|key| key.token == token && !filters.is_before_range(&key.activity))
+            .map(|key| key.activity)
+            .filter(|key| Some(key) != cursor.as_ref())
+            .take(MAX_SCANNED_TOKEN_ACTIVITY)
+            .inspect(|key| {
+                last_key = Some(key.clone());
+                scanned += 1;
+            })
+            .filter(|key| filters.matches_key(key))
+            .filter_map(|key| match &key.id {
+                TransactionId::EvmToIcp(identifier) => self
+                    .evm_to_icp_txs
+                    .get(identifier)
+                    .filter(|tx| filters.matches_evm_to_icp_status(&tx.status))
+                    .map(|tx| Transaction::from(CandidEvmToIcp::from(tx))),
+                TransactionId::IcpToEvm(identifier) => self
+                    .icp_to_evm_txs
+                    .get(identifier)
+                    .filter(|tx| filters.matches_icp_to_evm_status(&tx.status))
+                    .map(|tx| Transaction::from(CandidIcpToEvm::from(tx))),
+            })
+            .take(limit)
+            .collect();
+
+        // A full page may be followed by more transactions, a page that reached the scan limit
+        // resumes after the last walked entry
+        let next_cursor = last_key
+            .filter(|_| transactions.len() == limit || scanned == MAX_SCANNED_TOKEN_ACTIVITY)
+            .map(|key| key.to_string());
+
+        TokenTransactions {
+            transactions,
+            next_cursor,
+        }
+    }
+
+    // Gets a page of the latest transactions across all users and chains, newest first,
+    // starting after `cursor`
+    pub fn get_recent_transactions(
+        &self,
+        limit: usize,
+        cursor: Option<RecentActivityKey>,
+        filters: &RecentTransactionsFilters,
+    ) -> RecentTransactions {
+        let limit = limit.min(MAX_RECENT_TRANSACTIONS);
+        let entries = match &cursor {
+            Some(cursor) => self.recent_activity.range(cursor.clone()..),
+            None => self.recent_activity.range(..),
+        };
+
+        let mut last_key = None;
+        let mut scanned = 0;
+        let transactions: Vec<Transaction> = entries
+            .map(|(key, ())| key)
+            .filter(|key| Some(key) != cursor.as_ref())
+            .take(MAX_SCANNED_RECENT_ACTIVITY)
+            .inspect(|key| {
+                last_key = Some(key.clone());
+                scanned += 1;
+            })
+            .filter(|key| filters.matches_key(key))
+            .filter_map(|key| match &key.id {
+                TransactionId::EvmToIcp(identifier) => self
+                    .evm_to_icp_txs
+                    .get(identifier)
+                    .filter(|tx| filters.matches_operator(tx.operator))
+                    .map(|tx| Transaction::from(CandidEvmToIcp::from(tx))),
+                TransactionId::IcpToEvm(identifier) => self
+                    .icp_to_evm_txs
+                    .get(identifier)
+                    .filter(|tx| filters.matches_operator(tx.operator))
+                    .map(|tx| Transaction::from(CandidIcpToEvm::from(tx))),
+            })
+            .take(limit)
+            .map(|transaction| {
+                if filters.redact {
+                    redact(transaction)
+                } else {
+                    transaction
+                }
+            })
+            .collect();
+
+        // A full page may be followed by more transactions, a page that reached the scan limit
+        // resumes after the last walked entry
+        let next_cursor = last_key
+            .filter(|_| transactions.len() == limit || scanned == MAX_SCANNED_RECENT_ACTIVITY)
+            .map(|key| key.to_string());
+
+        RecentTransactions {
+            transactions,
+            next_cursor,
+        }
     }
 
     // Gets all the transaction history for an evm address
@@ -695,22 +2389,355 @@ impl State {
 
     // Gets all the transaction history for a principal
     pub fn get_transaction_for_principal(&self, principal_id: Principal) -> Vec<Transaction> {
+        self.get_owned_transactions(|owner, _subaccount| owner == principal_id)
+    }
+
+    // Gets the transaction history of an ICRC-1 account.
+    // Transactions without a subaccount belong to the default subaccount.
+    pub fn get_transaction_for_account(&self, account: Account) -> Vec<Transaction> {
+        let subaccount = *account.effective_subaccount();
+        self.get_owned_transactions(|owner, tx_subaccount| {
+            owner == account.owner && tx_subaccount.unwrap_or_default() == subaccount
+        })
+    }
+
+    // Gets the transactions whose ICP side is owned according to `is_owner`,
+    // with the expected completion time of the pending ones
+    fn get_owned_transactions(
+        &self,
+        is_owner: impl Fn(Principal, Option<[u8; 32]>) -> bool,
+    ) -> Vec<Transaction> {
         let result: Vec<Transaction> = self
             .evm_to_icp_txs
             .iter()
-            .filter(|(_id, tx)| tx.principal == principal_id)
-            .map(|(_id, tx)| Transaction::from(CandidEvmToIcp::from(tx)))
+            .filter(|(_id, tx)| is_owner(tx.principal, tx.subaccount))
+            .map(|(_id, tx)| self.evm_to_icp_with_estimate(tx))
             .chain(
                 self.icp_to_evm_txs
                     .iter()
-                    .filter(|(_id, tx)| tx.from == principal_id)
-                    .map(|(_id, tx)| Transaction::from(CandidIcpToEvm::from(tx))),
+                    .filter(|(_id, tx)| is_owner(tx.from, tx.from_subaccount))
+                    .map(|(_id, tx)| self.icp_to_evm_with_estimate(tx)),
             )
             .collect();
 
         result
     }
 
+    // Aggregates the whole bridge history of a principal per chain, minter and token
+    pub fn get_account_summary(&self, principal: Principal) -> AccountSummary {
+        let mut totals: std::collections::BTreeMap<AccountTokenKey, AccountTokenTotals> =
+            std::collections::BTreeMap::new();
+        // The statement index holds the transactions of a principal next to each other, so
+        // only those are read
+        let ids = self
+            .statement_index
+            .range(StatementKey::first_of(principal)..)
+            .map(|(key, ())| key)
+            .take_while(|key| key.principal == principal)
+            .map(|key| key.id);
+        for id in ids {
+            match id {
+                TransactionId::EvmToIcp(identifier) => {
+                    if let Some(tx) = self.evm_to_icp_txs.get(&identifier) {
+                        let key = AccountTokenKey {
+                            chain_id: tx.chain_id,
+                            operator: tx.operator,
+                            token: tx.erc20_contract_address,
+                        };
+                        totals.entry(key).or_default().add_deposit(&tx);
+                    }
+                }
+                TransactionId::IcpToEvm(identifier) => {
+                    if let Some(tx) = self.icp_to_evm_txs.get(&identifier) {
+                        let key = AccountTokenKey {
+                            chain_id: tx.chain_id,
+                            operator: tx.operator,
+                            token: tx.erc20_contract_address,
+                        };
+                        totals.entry(key).or_default().add_withdrawal(&tx);
+                    }
+                }
+            }
+        }
+
+        let tokens = totals
+            .into_iter()
+            .map(|(key, totals)| {
+                let token_ledger_id = totals.icrc_ledger_id.or_else(|| {
+                    self.get_icrc_twin_for_erc20(
+                        &Erc20Identifier(key.token, key.chain_id),
+                        &key.operator,
+                    )
+                });
+                let native_ledger_id = self.get_icrc_twin_for_erc20(
+                    &Erc20Identifier(native_token_address(), key.chain_id),
+                    &key.operator,
+                );
+                AccountTokenSummary::new(
+                    key,
+                    totals,
+                    |amount| {
+                        token_ledger_id.and_then(|ledger_id| self.get_usd_value(&ledger_id, amount))
+                    },
+                    |amount| {
+                        native_ledger_id
+                            .and_then(|ledger_id| self.get_usd_value(&ledger_id, amount))
+                    },
+                )
+            })
+            .collect();
+
+        AccountSummary::new(principal, tokens)
+    }
+
+    // Exports a page of one of the stored maps, in key order.
+    // Fails if the page is longer than `MAX_EXPORT_PAGE_LENGTH` or if the cursor does not decode
+    // to a key of the collection.
+    pub fn export(&self, args: &ExportArgs) -> Result<ExportPage, LoggerQueryError> {
+        if args.length > MAX_EXPORT_PAGE_LENGTH {
+            return Err(LoggerQueryError::InvalidLength);
+        }
+        let cursor = args.cursor.as_ref().map(|cursor| cursor.as_slice());
+        let (data, entry_count, next_cursor) = match args.collection {
+            ExportCollection::EvmToIcpTxs => {
+                export_map(&self.evm_to_icp_txs, cursor, args.length, args.format)
+            }
+            ExportCollection::IcpToEvmTxs => {
+                export_map(&self.icp_to_evm_txs, cursor, args.length, args.format)
+            }
+            ExportCollection::IcpTokens => {
+                export_map(&self.icp_token_list, cursor, args.length, args.format)
+            }
+            ExportCollection::EvmTokens => {
+                export_map(&self.evm_token_list, cursor, args.length, args.format)
+            }
+            ExportCollection::CkErc20BridgePairs => export_map(
+                &self.supported_ckerc20_tokens,
+                cursor,
+                args.length,
+                args.format,
+            ),
+            ExportCollection::AppicBridgePairs => export_map(
+                &self.supported_twin_appic_tokens,
+                cursor,
+                args.length,
+                args.format,
+            ),
+        }?;
+
+        Ok(ExportPage {
+            sequence: self.state_version(),
+            entry_count,
+            data: serde_bytes::ByteBuf::from(data),
+            next_cursor,
+        })
+    }
+
+    // Gets up to `length` rows of the statement of a principal, oldest first, starting after
+    // `cursor`, along with whether more rows follow. Only the rows of the page are read.
+    pub fn get_statement_page(
+        &self,
+        principal: Principal,
+        cursor: Option<&StatementKey>,
+        length: usize,
+    ) -> (Vec<(StatementKey, StatementRow)>, bool) {
+        let start = cursor
+            .cloned()
+            .unwrap_or_else(|| StatementKey::first_of(principal));
+        let mut keys = self
+            .statement_index
+            .range(start..)
+            .map(|(key, ())| key)
+            .filter(|key| Some(key) != cursor)
+            .take_while(|key| key.principal == principal);
+        let rows = keys
+            .by_ref()
+            .take(length)
+            .filter_map(|key| {
+                let row = match &key.id {
+                    TransactionId::EvmToIcp(identifier) => self
+                        .evm_to_icp_txs
+                        .get(identifier)
+                        .map(|tx| self.deposit_statement_row(tx)),
+                    TransactionId::IcpToEvm(identifier) => self
+                        .icp_to_evm_txs
+                        .get(identifier)
+                        .map(|tx| self.withdrawal_statement_row(tx)),
+                }?;
+                Some((key, row))
+            })
+            .collect();
+        (rows, keys.next().is_some())
+    }
+
+    fn deposit_statement_row(&self, tx: EvmToIcpTx) -> StatementRow {
+        let (token_symbol, decimals) = self.token_symbol_and_decimals(
+            tx.chain_id,
+            tx.erc20_contract_address,
+            tx.icrc_ledger_id,
+        );
+        // Only native deposits are charged a fee, deducted from what was received
+        let fee = tx
+            .actual_received
+            .filter(|_| is_native_token(&tx.erc20_contract_address))
+            .and_then(|actual_received| tx.value.checked_sub(actual_received));
+
+        StatementRow {
+            id: TransactionId::from(&tx).to_string(),
+            direction: BridgeDirection::EvmToIcp,
+            status: <&str>::from(&tx.status).to_string(),
+            chain_id: tx.chain_id.0,
+            operator: tx.operator,
+            token_symbol,
+            token_address: tx.erc20_contract_address.to_string(),
+            icrc_ledger_id: tx.icrc_ledger_id.map(|ledger_id| ledger_id.to_text()),
+            submitted_at: tx.submitted_at,
+            accepted_at: tx.accepted_at,
+            completed_at: tx.minted_at,
+            amount: format_amount(tx.value, decimals),
+            received: tx
+                .actual_received
+                .map(|actual_received| format_amount(actual_received, decimals)),
+            fee: fee.map(|fee| format_amount(fee, decimals)),
+            usd_price: tx.accepted_usd_price,
+            usd_value: tx.accepted_usd_value,
+            transaction_hash: Some(tx.transaction_hash.to_string()),
+            ledger_burn_index: None,
+            ledger_mint_index: tx.ledger_mint_index.map(|index| index.get()),
+        }
+    }
+
+    fn withdrawal_statement_row(&self, tx: IcpToEvmTx) -> StatementRow {
+        let (token_symbol, decimals) = self.token_symbol_and_decimals(
+            tx.chain_id,
+            tx.erc20_contract_address,
+            tx.icrc_ledger_id,
+        );
+        let is_erc20_withdrawal = !is_native_token(&tx.erc20_contract_address);
+        // ERC-20 withdrawals pay with a separate native burn, minus what was reimbursed of it
+        let fee = if is_erc20_withdrawal {
+            tx.max_transaction_fee.map(|max_transaction_fee| {
+                let reimbursed = tx
+                    .native_reimbursement
+                    .as_ref()
+                    .map_or(Erc20TokenAmount::ZERO, |reimbursement| reimbursement.amount);
+                max_transaction_fee
+                    .checked_sub(reimbursed)
+                    .unwrap_or(Erc20TokenAmount::ZERO)
+            })
+        } else {
+            tx.total_gas_spent
+        };
+        let (_native_symbol, native_decimals) =
+            self.token_symbol_and_decimals(tx.chain_id, native_token_address(), None);
+        let ledger_burn_index = tx
+            .erc20_ledger_burn_index
+            .filter(|_| is_erc20_withdrawal)
+            .unwrap_or(tx.native_ledger_burn_index);
+        // Block of the tokens minted back after a failed withdrawal
+        let reimbursed_in_block = tx
+            .erc20_reimbursement
+            .as_ref()
+            .or(tx.native_reimbursement.as_ref())
+            .and_then(|reimbursement| reimbursement.reimbursed_in_block);
+
+        StatementRow {
+            id: TransactionId::from(&tx).to_string(),
+            direction: BridgeDirection::IcpToEvm,
+            status: <&str>::from(&tx.status).to_string(),
+            chain_id: tx.chain_id.0,
+            operator: tx.operator,
+            token_symbol,
+            token_address: tx.erc20_contract_address.to_string(),
+            icrc_ledger_id: tx.icrc_ledger_id.map(|ledger_id| ledger_id.to_text()),
+            submitted_at: tx.submitted_at,
+            accepted_at: tx.accepted_at,
+            completed_at: tx.finalized_at,
+            amount: format_amount(tx.withdrawal_amount, decimals),
+            received: tx
+                .actual_received
+                .map(|actual_received| format_amount(actual_received, decimals)),
+            fee: fee.map(|fee| format_amount(fee, native_decimals)),
+            usd_price: tx.accepted_usd_price,
+            usd_value: tx.accepted_usd_value,
+            transaction_hash: tx
+                .transaction_hash
+                .map(|transaction_hash| transaction_hash.to_string()),
+            ledger_burn_index: Some(ledger_burn_index.get()),
+            ledger_mint_index: reimbursed_in_block.map(|index| index.get()),
+        }
+    }
+
+    // Symbol and decimals of a bridged token, from its icrc twin or else from the evm token list
+    fn token_symbol_and_decimals(
+        &self,
+        chain_id: ChainId,
+        token: Address,
+        icrc_ledger_id: Option<Principal>,
+    ) -> (Option<String>, Option<u8>) {
+        if let Some(token) =
+            icrc_ledger_id.and_then(|ledger_id| self.icp_token_list.get(&ledger_id))
+        {
+            return (Some(token.symbol), Some(token.decimals));
+        }
+        match self.evm_token_list.get(&Erc20Identifier(token, chain_id)) {
+            Some(token) => (Some(token.symbol), Some(token.decimals)),
+            None => (None, None),
+        }
+    }
+
+    // Gets a single transaction by the id returned with it, in the direction the id encodes
+    pub fn get_transaction_by_id(&self, id: &TransactionId) -> Option<Transaction> {
+        match id {
+            TransactionId::EvmToIcp(identifier) => self
+                .evm_to_icp_txs
+                .get(identifier)
+                .map(|tx| self.evm_to_icp_with_estimate(tx)),
+            TransactionId::IcpToEvm(identifier) => self
+                .icp_to_evm_txs
+                .get(identifier)
+                .map(|tx| self.icp_to_evm_with_estimate(tx)),
+        }
+    }
+
+    // Converts a deposit with the expected completion time if it is still pending
+    fn evm_to_icp_with_estimate(&self, tx: EvmToIcpTx) -> Transaction {
+        let estimated_completion_at = match tx.status {
+            EvmToIcpStatus::Accepted => self.estimated_completion_at(
+                tx.chain_id,
+                tx.operator,
+                BridgeDirection::EvmToIcp,
+                tx.accepted_at,
+            ),
+            _ => None,
+        };
+        Transaction::from(CandidEvmToIcp {
+            estimated_completion_at,
+            ..CandidEvmToIcp::from(tx)
+        })
+    }
+
+    // Converts a withdrawal with the expected completion time if it is still pending
+    fn icp_to_evm_with_estimate(&self, tx: IcpToEvmTx) -> Transaction {
+        let estimated_completion_at = match tx.status {
+            IcpToEvmStatus::Accepted
+            | IcpToEvmStatus::Created
+            | IcpToEvmStatus::SignedTransaction
+            | IcpToEvmStatus::ReplacedTransaction
+            | IcpToEvmStatus::FinalizedTransaction => self.estimated_completion_at(
+                tx.chain_id,
+                tx.operator,
+                BridgeDirection::IcpToEvm,
+                tx.accepted_at,
+            ),
+            _ => None,
+        };
+        Transaction::from(CandidIcpToEvm {
+            estimated_completion_at,
+            ..CandidIcpToEvm::from(tx)
+        })
+    }
+
     // Gets supported twin token pairs for both Appic and Dfinity NNS Twin tokens
     pub fn get_suported_bridge_pairs(&self) -> Vec<TokenPair> {
         self.supported_ckerc20_tokens
@@ -750,12 +2777,89 @@ impl State {
             .collect()
     }
 
-    // Searches for a transaction by hash in both evm_to_icp and icp_to_evm
-    fn get_transaction_by_hash(&self, tx_hash: &String, chain_id: ChainId) -> Option<Transaction> {
-        let evm_to_icp_id = EvmToIcpTxIdentifier::new(tx_hash, chain_id);
-
-        self.evm_to_icp_txs
-            .get(&evm_to_icp_id)
+    // Searches transactions, tokens and bridge pairs of every chain and operator
+    pub fn search(&self, query: &SearchQuery) -> SearchResults {
+        let mut results = SearchResults::new(query.kind());
+        match query {
+            SearchQuery::TransactionHash(transaction_hash) => {
+                results.transactions = self
+                    .evm_to_icp_txs
+                    .range(EvmToIcpTxIdentifier::new(transaction_hash, ChainId(0))..)
+                    .take_while(|(identifier, _tx)| {
+                        identifier.transaction_hash() == transaction_hash
+                    })
+                    .map(|(_id, tx)| Transaction::from(CandidEvmToIcp::from(tx)))
+                    .chain(
+                        self.icp_to_evm_txs
+                            .values()
+                            .filter(|tx| tx.transaction_hash.as_ref() == Some(transaction_hash))
+                            .map(|tx| Transaction::from(CandidIcpToEvm::from(tx))),
+                    )
+                    .collect();
+            }
+            SearchQuery::Address(address) => {
+                results.transactions = self.get_transaction_for_address(*address);
+                results.evm_tokens = self
+                    .evm_token_list
+                    .iter()
+                    .filter(|(identifier, _token)| identifier.0 == *address)
+                    .map(|(_id, token)| CandidEvmToken::from(token))
+                    .collect();
+                results.bridge_pairs = self
+                    .get_suported_bridge_pairs()
+                    .into_iter()
+                    .filter(|pair| pair.evm_token.erc20_contract_address == address.to_string())
+                    .collect();
+            }
+            SearchQuery::Principal(principal) => {
+                results.transactions = self.get_transaction_for_principal(*principal);
+                results.icp_tokens = self
+                    .get_icp_token_by_principal(principal)
+                    .map(CandidIcpToken::from)
+                    .into_iter()
+                    .collect();
+                results.bridge_pairs = self
+                    .get_suported_bridge_pairs()
+                    .into_iter()
+                    .filter(|pair| pair.icp_token.ledger_id == *principal)
+                    .collect();
+            }
+            SearchQuery::Account(account) => {
+                results.transactions = self.get_transaction_for_account(*account);
+            }
+            SearchQuery::Index(index) => {
+                results.transactions = self
+                    .icp_to_evm_txs
+                    .values()
+                    .filter(|tx| {
+                        tx.native_ledger_burn_index.get() == *index
+                            || tx.erc20_ledger_burn_index.map(|index| index.get()) == Some(*index)
+                    })
+                    .map(|tx| Transaction::from(CandidIcpToEvm::from(tx)))
+                    .chain(
+                        self.evm_to_icp_txs
+                            .values()
+                            .filter(|tx| {
+                                tx.ledger_mint_index.map(|index| index.get()) == Some(*index)
+                            })
+                            .map(|tx| Transaction::from(CandidEvmToIcp::from(tx))),
+                    )
+                    .collect();
+            }
+        }
+        results
+    }
+
+    // Searches for a transaction by hash in both evm_to_icp and icp_to_evm
+    fn get_transaction_by_hash(
+        &self,
+        tx_hash: &TransactionHash,
+        chain_id: ChainId,
+    ) -> Option<Transaction> {
+        let evm_to_icp_id = EvmToIcpTxIdentifier::new(tx_hash, chain_id);
+
+        self.evm_to_icp_txs
+            .get(&evm_to_icp_id)
             .map(|tx| Transaction::from(CandidEvmToIcp::from(tx)))
             .or_else(|| {
                 self.icp_to_evm_txs
@@ -800,9 +2904,10 @@ impl State {
         chain_id: ChainId,
     ) -> Option<Transaction> {
         let search_result = match search_param {
-            TransactionSearchParam::TxHash(tx_hash) => {
-                self.get_transaction_by_hash(&tx_hash, chain_id)
-            }
+            // A malformed hash can not match any transaction
+            TransactionSearchParam::TxHash(tx_hash) => TransactionHash::from_str(&tx_hash)
+                .ok()
+                .and_then(|tx_hash| self.get_transaction_by_hash(&tx_hash, chain_id)),
 
             TransactionSearchParam::TxWithdrawalId(withdrawal_id) => self
                 .get_transaction_by_burn_index(nat_to_ledger_burn_index(&withdrawal_id), chain_id),
@@ -817,29 +2922,79 @@ impl State {
 
     // Records a single evm token
     pub fn record_evm_token(&mut self, identifier: Erc20Identifier, token: EvmToken) {
+        self.record_logger_event(
+            ChangeSource::TokenUpdate,
+            StateChange::RecordedEvmToken(CandidEvmToken::from(token.clone())),
+        );
         self.evm_token_list.insert(identifier, token);
+        self.bump_state_version();
     }
 
     // Records all evm_tokens in bulk
     pub fn record_evm_tokens_bulk(&mut self, tokens: Vec<EvmToken>) {
         tokens.into_iter().for_each(|token| {
-            self.evm_token_list
-                .insert(Erc20Identifier::from(&token), token);
+            self.record_evm_token(Erc20Identifier::from(&token), token);
         });
     }
 
-    // Records a single icp token
+    // Records a single icp token. Only changes to fields other than the USD price are logged,
+    // like `update_icp_token_usd_price` prices are not.
     pub fn record_icp_token(&mut self, ledger_id: Principal, token: IcpToken) {
+        let candid_token = CandidIcpToken::from(token.clone());
+        // `IcpToken` only compares ledger ids, so compare every field through the candid form
+        let without_price = |token: CandidIcpToken| CandidIcpToken {
+            usd_price: String::new(),
+            ..token
+        };
+        match self
+            .icp_token_list
+            .get(&ledger_id)
+            .map(CandidIcpToken::from)
+        {
+            Some(existing) if existing == candid_token => return,
+            Some(existing) if without_price(existing) == without_price(candid_token.clone()) => {}
+            _ => self.record_logger_event(
+                ChangeSource::TokenUpdate,
+                StateChange::RecordedIcpToken(candid_token),
+            ),
+        }
         self.icp_token_list.insert(ledger_id, token);
+        self.bump_state_version();
     }
 
     // Records all icp_tokens in bulk
     pub fn record_icp_tokens_bulk(&mut self, tokens: Vec<IcpToken>) {
         tokens.into_iter().for_each(|token| {
-            self.icp_token_list.insert(token.ledger_id, token);
+            self.record_icp_token(token.ledger_id, token);
         });
     }
 
+    // Records a new bridge pair supported by `operator`
+    pub fn record_bridge_pair(
+        &mut self,
+        operator: Operator,
+        identifier: Erc20Identifier,
+        bridge_pair: BridgePair,
+    ) {
+        self.record_logger_event(
+            ChangeSource::TokenUpdate,
+            StateChange::RecordedBridgePair(TokenPair {
+                evm_token: CandidEvmToken::from(bridge_pair.evm_token.clone()),
+                icp_token: CandidIcpToken::from(bridge_pair.icp_token.clone()),
+                operator,
+            }),
+        );
+        match operator {
+            Operator::DfinityCkEthMinter => self
+                .supported_ckerc20_tokens
+                .insert(identifier, bridge_pair),
+            Operator::AppicMinter => self
+                .supported_twin_appic_tokens
+                .insert(identifier, bridge_pair),
+        };
+        self.bump_state_version();
+    }
+
     pub fn get_evm_token_by_identifier(&self, identifier: &Erc20Identifier) -> Option<EvmToken> {
         self.evm_token_list.get(identifier)
     }
@@ -858,12 +3013,54 @@ impl State {
             .map(|token| token.usd_price)
     }
 
+    // USD value of `amount` of an icp token, if its price is known
+    pub fn get_usd_value(&self, ledger_id: &Principal, amount: Erc20TokenAmount) -> Option<f64> {
+        self.icp_token_list
+            .get(ledger_id)
+            .and_then(|token| usd_value(amount, token.decimals, &token.usd_price))
+    }
+
+    // Current USD price of an icp token and the USD value of `amount` at that price, for an
+    // event at `timestamp`. Prices are refreshed every `UPDATE_USD_PRICE`, so the current
+    // price is only a fair snapshot of events within that interval. Older events, e.g. ones
+    // scraped after a backlog or an outage, are left unpriced rather than valued at today's
+    // price.
+    pub fn get_usd_snapshot(
+        &self,
+        ledger_id: Option<Principal>,
+        amount: Erc20TokenAmount,
+        timestamp: u64,
+    ) -> (Option<String>, Option<String>) {
+        if current_time().abs_diff(timestamp) > UPDATE_USD_PRICE.as_nanos() as u64 {
+            return (None, None);
+        }
+        let Some(token) = ledger_id.and_then(|ledger_id| self.icp_token_list.get(&ledger_id))
+        else {
+            return (None, None);
+        };
+        match usd_value(amount, token.decimals, &token.usd_price) {
+            Some(value) => (Some(token.usd_price), Some(value.to_string())),
+            None => (None, None),
+        }
+    }
+
     pub fn remove_icp_token(&mut self, ledger_id: &Principal) {
-        self.icp_token_list.remove(ledger_id);
+        if self.icp_token_list.remove(ledger_id).is_some() {
+            self.bump_state_version();
+            self.record_logger_event(
+                ChangeSource::Cleanup,
+                StateChange::RemovedIcpToken {
+                    ledger_id: *ledger_id,
+                },
+            );
+        }
     }
 
     pub fn update_icp_token_usd_price(&mut self, ledger_id: Principal, new_usd_price: String) {
         if let Some(token) = self.icp_token_list.get(&ledger_id) {
+            if token.usd_price == new_usd_price {
+                return;
+            }
             self.icp_token_list.insert(
                 ledger_id,
                 IcpToken {
@@ -871,13 +3068,34 @@ impl State {
                     ..token
                 },
             );
+            self.bump_state_version();
         };
     }
 }
 
 pub fn is_native_token(address: &Address) -> bool {
-    address
-        == &Address::from_str(NATIVE_ERC20_ADDRESS).expect("Should not fail converintg to address")
+    address == &native_token_address()
+}
+
+pub fn token_kind(address: &Address) -> TokenKind {
+    if is_native_token(address) {
+        TokenKind::Native
+    } else {
+        TokenKind::Erc20
+    }
+}
+
+pub fn native_token_address() -> Address {
+    Address::from_str(NATIVE_ERC20_ADDRESS).expect("Should not fail converintg to address")
+}
+
+// Converts an amount in the token's smallest denomination to USD.
+// A price of zero means the price is not known.
+pub fn usd_value(amount: Erc20TokenAmount, decimals: u8, usd_price: &str) -> Option<f64> {
+    let price = f64::from_str(usd_price)
+        .ok()
+        .filter(|price| price.is_finite() && *price > 0.0)?;
+    Some(amount.as_f64() / 10_f64.powi(decimals as i32) * price)
 }
 
 impl From<&Nat> for ChainId {
@@ -951,6 +3169,7 @@ pub fn read_state<R>(f: impl FnOnce(&State) -> R) -> R {
 }
 
 // / Mutates (part of) the current state using `f`.
+// / Certifies the new tip of the logger's event log if `f` changed it.
 // /
 // / Panics if there is no state.
 pub fn mutate_state<F, R>(f: F) -> R
@@ -958,10 +3177,14 @@ where
     F: FnOnce(&mut State) -> R,
 {
     STATE.with(|cell| {
-        f(cell
-            .borrow_mut()
-            .as_mut()
-            .expect("BUG: state is not initialized"))
+        let mut cell = cell.borrow_mut();
+        let state = cell.as_mut().expect("BUG: state is not initialized");
+        let events_before = state.logger_events.len();
+        let result = f(state);
+        if state.logger_events.len() != events_before {
+            certify_logger_events_tip(state.logger_events_tip_hash());
+        }
+        result
     })
 }
 
@@ -972,12 +3195,32 @@ thread_local! {
     pub static STATE: RefCell<Option<State>> = RefCell::new(
         Some(State {
                 minters: BTreeMap::init(minter_memory()),
+                minter_activity: BTreeMap::init(minter_activity_memory()),
                 evm_to_icp_txs: BTreeMap::init(evm_to_icp_memory()),
                 icp_to_evm_txs: BTreeMap::init(icp_to_evm_memory()),
                 supported_ckerc20_tokens: BTreeMap::init(supported_ckerc20_tokens_memory_id()),
                 supported_twin_appic_tokens:BTreeMap::init(supported_appic_tokens_memory_id()),
                 evm_token_list:BTreeMap::init(evm_token_list_id()),
-                icp_token_list:BTreeMap::init(icp_token_list_id())
+                icp_token_list:BTreeMap::init(icp_token_list_id()),
+                logger_events: StableLog::init(logger_events_index_memory(), logger_events_data_memory())
+                    .expect("failed to initialize logger events log"),
+                bridge_stats: BTreeMap::init(bridge_stats_memory()),
+                fee_revenue: BTreeMap::init(fee_revenue_memory()),
+                fee_schedules: BTreeMap::init(fee_schedules_memory()),
+                latency_samples: BTreeMap::init(latency_samples_memory()),
+                stuck_thresholds: BTreeMap::init(stuck_thresholds_memory()),
+                stuck_alerts: BTreeMap::init(stuck_alerts_memory()),
+                gas_costs: BTreeMap::init(gas_costs_memory()),
+                deposit_submissions: BTreeMap::init(deposit_submissions_memory()),
+                deposit_discrepancies: BTreeMap::init(deposit_discrepancies_memory()),
+                recent_activity: BTreeMap::init(recent_activity_memory()),
+                token_activity: BTreeMap::init(token_activity_memory()),
+                completed_backfills: BTreeMap::init(completed_backfills_memory()),
+                waiting_txs: BTreeMap::init(waiting_txs_memory()),
+                statement_index: BTreeMap::init(statement_index_memory()),
+                state_version: StableCell::init(state_version_memory(), 0)
+                    .expect("failed to initialize state version"),
+                tx_counts: BTreeMap::init(tx_counts_memory()),
 
             })
     );
@@ -985,6 +3228,7 @@ thread_local! {
 
 mod storage_config {
     use super::*;
+    use ic_stable_structures::Memory;
 
     thread_local! {
         static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(
@@ -993,46 +3237,244 @@ mod storage_config {
 
     }
 
-    const MINTERS_MEMORY_ID: MemoryId = MemoryId::new(0);
+    const MINTERS_MEMORY_ID: u8 = 0;
 
     pub fn minter_memory() -> StableMemory {
-        MEMORY_MANAGER.with(|m| m.borrow().get(MINTERS_MEMORY_ID))
+        memory(MINTERS_MEMORY_ID)
     }
 
-    const EVM_TO_ICP_MEMORY_ID: MemoryId = MemoryId::new(1);
+    // Transactions stored by the first release, emptied on upgrade
+    const LEGACY_EVM_TO_ICP_MEMORY_ID: u8 = 1;
 
-    pub fn evm_to_icp_memory() -> StableMemory {
-        MEMORY_MANAGER.with(|m| m.borrow().get(EVM_TO_ICP_MEMORY_ID))
+    pub fn legacy_evm_to_icp_memory() -> StableMemory {
+        memory(LEGACY_EVM_TO_ICP_MEMORY_ID)
     }
 
-    const ICP_TO_EVM_MEMORY_ID: MemoryId = MemoryId::new(2);
+    const LEGACY_ICP_TO_EVM_MEMORY_ID: u8 = 2;
 
-    pub fn icp_to_evm_memory() -> StableMemory {
-        MEMORY_MANAGER.with(|m| m.borrow().get(ICP_TO_EVM_MEMORY_ID))
+    pub fn legacy_icp_to_evm_memory() -> StableMemory {
+        memory(LEGACY_ICP_TO_EVM_MEMORY_ID)
     }
 
-    const SUPPORTED_CK_MEMORY_ID: MemoryId = MemoryId::new(3);
+    const SUPPORTED_CK_MEMORY_ID: u8 = 3;
 
     pub fn supported_ckerc20_tokens_memory_id() -> StableMemory {
-        MEMORY_MANAGER.with(|m| m.borrow().get(SUPPORTED_CK_MEMORY_ID))
+        memory(SUPPORTED_CK_MEMORY_ID)
     }
 
-    const SUPPORTED_APPIC_MEMORY_ID: MemoryId = MemoryId::new(4);
+    const SUPPORTED_APPIC_MEMORY_ID: u8 = 4;
 
     pub fn supported_appic_tokens_memory_id() -> StableMemory {
-        MEMORY_MANAGER.with(|m| m.borrow().get(SUPPORTED_APPIC_MEMORY_ID))
+        memory(SUPPORTED_APPIC_MEMORY_ID)
     }
 
-    const EVM_TOKEN_LIST: MemoryId = MemoryId::new(5);
+    const EVM_TOKEN_LIST: u8 = 5;
 
     pub fn evm_token_list_id() -> StableMemory {
-        MEMORY_MANAGER.with(|m| m.borrow().get(EVM_TOKEN_LIST))
+        memory(EVM_TOKEN_LIST)
     }
 
-    const ICP_TOKEN_LIST: MemoryId = MemoryId::new(6);
+    const ICP_TOKEN_LIST: u8 = 6;
 
     pub fn icp_token_list_id() -> StableMemory {
-        MEMORY_MANAGER.with(|m| m.borrow().get(ICP_TOKEN_LIST))
+        memory(ICP_TOKEN_LIST)
+    }
+
+    const MINTER_ACTIVITY_MEMORY_ID: u8 = 7;
+
+    pub fn minter_activity_memory() -> StableMemory {
+        memory(MINTER_ACTIVITY_MEMORY_ID)
+    }
+
+    const LOGGER_EVENTS_INDEX_MEMORY_ID: u8 = 8;
+
+    pub fn logger_events_index_memory() -> StableMemory {
+        memory(LOGGER_EVENTS_INDEX_MEMORY_ID)
+    }
+
+    const LOGGER_EVENTS_DATA_MEMORY_ID: u8 = 9;
+
+    pub fn logger_events_data_memory() -> StableMemory {
+        memory(LOGGER_EVENTS_DATA_MEMORY_ID)
+    }
+
+    const BRIDGE_STATS_MEMORY_ID: u8 = 10;
+
+    pub fn bridge_stats_memory() -> StableMemory {
+        memory(BRIDGE_STATS_MEMORY_ID)
+    }
+
+    const FEE_REVENUE_MEMORY_ID: u8 = 11;
+
+    pub fn fee_revenue_memory() -> StableMemory {
+        memory(FEE_REVENUE_MEMORY_ID)
+    }
+
+    const FEE_SCHEDULES_MEMORY_ID: u8 = 12;
+
+    pub fn fee_schedules_memory() -> StableMemory {
+        memory(FEE_SCHEDULES_MEMORY_ID)
+    }
+
+    const LATENCY_SAMPLES_MEMORY_ID: u8 = 13;
+
+    pub fn latency_samples_memory() -> StableMemory {
+        memory(LATENCY_SAMPLES_MEMORY_ID)
+    }
+
+    const STUCK_THRESHOLDS_MEMORY_ID: u8 = 14;
+
+    pub fn stuck_thresholds_memory() -> StableMemory {
+        memory(STUCK_THRESHOLDS_MEMORY_ID)
+    }
+
+    const STUCK_ALERTS_MEMORY_ID: u8 = 15;
+
+    pub fn stuck_alerts_memory() -> StableMemory {
+        memory(STUCK_ALERTS_MEMORY_ID)
+    }
+
+    const GAS_COSTS_MEMORY_ID: u8 = 16;
+
+    pub fn gas_costs_memory() -> StableMemory {
+        memory(GAS_COSTS_MEMORY_ID)
+    }
+
+    const EVM_TO_ICP_MEMORY_ID: u8 = 17;
+
+    pub fn evm_to_icp_memory() -> StableMemory {
+        memory(EVM_TO_ICP_MEMORY_ID)
+    }
+
+    const ICP_TO_EVM_MEMORY_ID: u8 = 18;
+
+    pub fn icp_to_evm_memory() -> StableMemory {
+        memory(ICP_TO_EVM_MEMORY_ID)
+    }
+
+    const DEPOSIT_SUBMISSIONS_MEMORY_ID: u8 = 19;
+
+    pub fn deposit_submissions_memory() -> StableMemory {
+        memory(DEPOSIT_SUBMISSIONS_MEMORY_ID)
+    }
+
+    const DEPOSIT_DISCREPANCIES_MEMORY_ID: u8 = 20;
+
+    pub fn deposit_discrepancies_memory() -> StableMemory {
+        memory(DEPOSIT_DISCREPANCIES_MEMORY_ID)
+    }
+
+    const RECENT_ACTIVITY_MEMORY_ID: u8 = 21;
+
+    pub fn recent_activity_memory() -> StableMemory {
+        memory(RECENT_ACTIVITY_MEMORY_ID)
+    }
+
+    const TOKEN_ACTIVITY_MEMORY_ID: u8 = 22;
+
+    pub fn token_activity_memory() -> StableMemory {
+        memory(TOKEN_ACTIVITY_MEMORY_ID)
+    }
+
+    const TASK_RUNS_MEMORY_ID: u8 = 23;
+
+    pub fn task_runs_memory() -> StableMemory {
+        memory(TASK_RUNS_MEMORY_ID)
+    }
+
+    const COMPLETED_BACKFILLS_MEMORY_ID: u8 = 24;
+
+    pub fn completed_backfills_memory() -> StableMemory {
+        memory(COMPLETED_BACKFILLS_MEMORY_ID)
+    }
+
+    const WAITING_TXS_MEMORY_ID: u8 = 25;
+
+    pub fn waiting_txs_memory() -> StableMemory {
+        memory(WAITING_TXS_MEMORY_ID)
+    }
+
+    const STATEMENT_INDEX_MEMORY_ID: u8 = 26;
+
+    pub fn statement_index_memory() -> StableMemory {
+        memory(STATEMENT_INDEX_MEMORY_ID)
+    }
+
+    const STATE_VERSION_MEMORY_ID: u8 = 27;
+
+    pub fn state_version_memory() -> StableMemory {
+        memory(STATE_VERSION_MEMORY_ID)
+    }
+
+    const TX_COUNTS_MEMORY_ID: u8 = 28;
+
+    pub fn tx_counts_memory() -> StableMemory {
+        memory(TX_COUNTS_MEMORY_ID)
+    }
+
+    // Id and metrics name of every virtual memory. Memories are only handed out by `memory`,
+    // which refuses ids missing here, so a new memory can not be left out of the metrics.
+    // Ids 1 and 2 held the transactions of the first release. They are emptied by the legacy
+    // migration and are retired for good: stale pages of the old layout may remain, so they
+    // must never be reused for another map.
+    const MEMORIES: &[(u8, &str)] = &[
+        (MINTERS_MEMORY_ID, "minters"),
+        (LEGACY_EVM_TO_ICP_MEMORY_ID, "legacy_evm_to_icp_txs"),
+        (LEGACY_ICP_TO_EVM_MEMORY_ID, "legacy_icp_to_evm_txs"),
+        (SUPPORTED_CK_MEMORY_ID, "supported_ckerc20_tokens"),
+        (SUPPORTED_APPIC_MEMORY_ID, "supported_twin_appic_tokens"),
+        (EVM_TOKEN_LIST, "evm_token_list"),
+        (ICP_TOKEN_LIST, "icp_token_list"),
+        (MINTER_ACTIVITY_MEMORY_ID, "minter_activity"),
+        (LOGGER_EVENTS_INDEX_MEMORY_ID, "logger_events_index"),
+        (LOGGER_EVENTS_DATA_MEMORY_ID, "logger_events_data"),
+        (BRIDGE_STATS_MEMORY_ID, "bridge_stats"),
+        (FEE_REVENUE_MEMORY_ID, "fee_revenue"),
+        (FEE_SCHEDULES_MEMORY_ID, "fee_schedules"),
+        (LATENCY_SAMPLES_MEMORY_ID, "latency_samples"),
+        (STUCK_THRESHOLDS_MEMORY_ID, "stuck_thresholds"),
+        (STUCK_ALERTS_MEMORY_ID, "stuck_alerts"),
+        (GAS_COSTS_MEMORY_ID, "gas_costs"),
+        (EVM_TO_ICP_MEMORY_ID, "evm_to_icp_txs"),
+        (ICP_TO_EVM_MEMORY_ID, "icp_to_evm_txs"),
+        (DEPOSIT_SUBMISSIONS_MEMORY_ID, "deposit_submissions"),
+        (DEPOSIT_DISCREPANCIES_MEMORY_ID, "deposit_discrepancies"),
+        (RECENT_ACTIVITY_MEMORY_ID, "recent_activity"),
+        (TOKEN_ACTIVITY_MEMORY_ID, "token_activity"),
+        (TASK_RUNS_MEMORY_ID, "task_runs"),
+        (COMPLETED_BACKFILLS_MEMORY_ID, "completed_backfills"),
+        (WAITING_TXS_MEMORY_ID, "waiting_txs"),
+        (STATEMENT_INDEX_MEMORY_ID, "statement_index"),
+        (STATE_VERSION_MEMORY_ID, "state_version"),
+        (TX_COUNTS_MEMORY_ID, "tx_counts"),
+    ];
+
+    fn memory(id: u8) -> StableMemory {
+        assert!(
+            MEMORIES.iter().any(|(memory_id, _)| *memory_id == id),
+            "BUG: memory {id} is missing from MEMORIES"
+        );
+        MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(id)))
+    }
+
+    /// Returns the id, name and size in WASM pages of every virtual memory.
+    pub fn stable_memory_sizes() -> Vec<(u8, &'static str, u64)> {
+        MEMORIES
+            .iter()
+            .map(|(id, name)| (*id, *name, memory(*id).size()))
+            .collect()
+    }
+
+    impl Storable for TaskType {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
     }
 
     impl Storable for MinterKey {
@@ -1059,6 +3501,18 @@ mod storage_config {
         const BOUND: Bound = Bound::Unbounded;
     }
 
+    impl Storable for MinterActivity {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
     impl Storable for EvmToIcpTxIdentifier {
         fn to_bytes(&self) -> Cow<[u8]> {
             encode(self)
@@ -1131,7 +3585,7 @@ mod storage_config {
         const BOUND: Bound = Bound::Unbounded;
     }
 
-    impl Storable for Erc20Identifier {
+    impl Storable for LatencyKey {
         fn to_bytes(&self) -> Cow<[u8]> {
             encode(self)
         }
@@ -1143,7 +3597,7 @@ mod storage_config {
         const BOUND: Bound = Bound::Unbounded;
     }
 
-    impl Storable for EvmToken {
+    impl Storable for LatencySamples {
         fn to_bytes(&self) -> Cow<[u8]> {
             encode(self)
         }
@@ -1155,7 +3609,7 @@ mod storage_config {
         const BOUND: Bound = Bound::Unbounded;
     }
 
-    impl Storable for IcpToken {
+    impl Storable for ChainId {
         fn to_bytes(&self) -> Cow<[u8]> {
             encode(self)
         }
@@ -1167,7 +3621,7 @@ mod storage_config {
         const BOUND: Bound = Bound::Unbounded;
     }
 
-    impl Storable for IcpTokenType {
+    impl Storable for StuckThresholds {
         fn to_bytes(&self) -> Cow<[u8]> {
             encode(self)
         }
@@ -1179,7 +3633,7 @@ mod storage_config {
         const BOUND: Bound = Bound::Unbounded;
     }
 
-    impl Storable for BridgePair {
+    impl Storable for AlertKey {
         fn to_bytes(&self) -> Cow<[u8]> {
             encode(self)
         }
@@ -1191,27 +3645,1681 @@ mod storage_config {
         const BOUND: Bound = Bound::Unbounded;
     }
 
-    fn encode<T: ?Sized + serde::Serialize>(value: &T) -> Cow<[u8]> {
-        let bytes = bincode::serialize(value).expect("failed to encode");
-        Cow::Owned(bytes)
+    impl Storable for Alert {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
     }
 
-    fn decode<T: for<'a> serde::Deserialize<'a>>(bytes: Cow<[u8]>) -> T {
-        bincode::deserialize(bytes.as_ref())
-            .unwrap_or_else(|e| panic!("failed to decode bytes {}: {e}", hex::encode(bytes)))
+    impl Storable for GasCostKey {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
     }
-}
 
-// Testing which state serialization is faster
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::time::Instant;
+    impl Storable for GasCostSamples {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
 
-    #[test]
-    fn compare_bincode_and_ciborium() {
-        let tx_identifier: EvmToIcpTxIdentifier = EvmToIcpTxIdentifier(
-            "0x8218f324b45a8cd36f38586b062e3884588d926035f08e1dcd3605160b3ebd42".to_string(),
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for DepositSubmission {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for DepositDiscrepancy {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for RecentActivityKey {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for TxCountKey {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for StatementKey {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for TokenActivityKey {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for legacy::EvmToIcpTxV0 {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for legacy::IcpToEvmTxV0 {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for legacy::EvmToIcpTxIdentifierV0 {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for Erc20Identifier {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for EvmToken {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for IcpToken {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for IcpTokenType {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for BridgePair {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for BridgeStatsKey {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for BridgeStats {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for FeeRevenueKey {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for FeeRevenue {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for FeeScheduleKey {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for FeeScheduleEntry {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    impl Storable for LoggerEvent {
+        fn to_bytes(&self) -> Cow<[u8]> {
+            encode(self)
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            decode(bytes)
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    fn encode<T: ?Sized + serde::Serialize>(value: &T) -> Cow<[u8]> {
+        let bytes = bincode::serialize(value).expect("failed to encode");
+        Cow::Owned(bytes)
+    }
+
+    fn decode<T: for<'a> serde::Deserialize<'a>>(bytes: Cow<[u8]>) -> T {
+        bincode::deserialize(bytes.as_ref())
+            .unwrap_or_else(|e| panic!("failed to decode bytes {}: {e}", hex::encode(bytes)))
+    }
+}
+
+// Testing which state serialization is faster
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deposit_verification::DepositField;
+    use crate::export::ExportFormat;
+    use crate::logger_events::LoggerEventPayload;
+    use crate::token_activity::StatusFilter;
+    use std::time::Instant;
+
+    #[test]
+    fn should_compute_usd_value() {
+        let amount = Erc20TokenAmount::from(1_500_000_u64);
+
+        assert_eq!(usd_value(amount, 6, "2"), Some(3.0));
+        assert_eq!(usd_value(amount, 6, "0"), None);
+        assert_eq!(usd_value(amount, 6, "not a price"), None);
+    }
+
+    #[test]
+    fn should_only_snapshot_usd_price_of_recent_events() {
+        let ledger_id = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        let amount = Erc20TokenAmount::from(1_500_000_u64);
+
+        mutate_state(|s| {
+            s.record_icp_token(
+                ledger_id,
+                IcpToken {
+                    ledger_id,
+                    name: "Token".to_string(),
+                    decimals: 6,
+                    symbol: "TKN".to_string(),
+                    usd_price: "2".to_string(),
+                    logo: String::new(),
+                    fee: Erc20TokenAmount::ZERO,
+                    token_type: IcpTokenType::ICRC1,
+                    rank: None,
+                },
+            )
+        });
+
+        let now = current_time();
+        let stale = now - UPDATE_USD_PRICE.as_nanos() as u64 - 1;
+        read_state(|s| {
+            assert_eq!(
+                s.get_usd_snapshot(Some(ledger_id), amount, now),
+                (Some("2".to_string()), Some("3".to_string()))
+            );
+            assert_eq!(
+                s.get_usd_snapshot(Some(ledger_id), amount, stale),
+                (None, None)
+            );
+        });
+    }
+
+    #[test]
+    fn should_not_log_usd_price_changes() {
+        let ledger_id = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        let token = |usd_price: &str, rank: Option<u32>| IcpToken {
+            ledger_id,
+            name: "Token".to_string(),
+            decimals: 6,
+            symbol: "TKN".to_string(),
+            usd_price: usd_price.to_string(),
+            logo: String::new(),
+            fee: Erc20TokenAmount::ZERO,
+            token_type: IcpTokenType::ICRC1,
+            rank,
+        };
+
+        mutate_state(|s| {
+            s.record_icp_token(ledger_id, token("2", None));
+            assert_eq!(s.logger_events.len(), 1);
+
+            s.update_icp_token_usd_price(ledger_id, "3".to_string());
+            s.record_icp_token(ledger_id, token("4", None));
+            assert_eq!(s.logger_events.len(), 1);
+            assert_eq!(s.icp_token_list.get(&ledger_id).unwrap().usd_price, "4");
+
+            s.record_icp_token(ledger_id, token("4", Some(1)));
+            assert_eq!(s.logger_events.len(), 2);
+        });
+    }
+
+    #[test]
+    fn should_report_every_memory_once() {
+        let ids: Vec<u8> = stable_memory_sizes()
+            .into_iter()
+            .map(|(id, _name, _size)| id)
+            .collect();
+
+        assert_eq!(ids, (0..ids.len() as u8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_parse_transaction_hashes_to_canonical_form() {
+        let canonical = "0x8218f324b45a8cd36f38586b062e3884588d926035f08e1dcd3605160b3ebd42";
+
+        for spelling in [
+            canonical.to_string(),
+            canonical[2..].to_string(),
+            canonical.to_uppercase().replacen("0X", "0x", 1),
+            canonical.to_uppercase(),
+        ] {
+            let hash = TransactionHash::from_str(&spelling).unwrap();
+            assert_eq!(hash.to_string(), canonical);
+        }
+
+        assert!(TransactionHash::from_str("0x").is_err());
+        assert!(TransactionHash::from_str(&canonical[..65]).is_err());
+        assert!(TransactionHash::from_str(&canonical.replace('8', "g")).is_err());
+
+        // Stored like the strings hashes were stored as before they were validated
+        let hash = TransactionHash::from_str(canonical).unwrap();
+        assert_eq!(
+            bincode::serialize(&hash).unwrap(),
+            bincode::serialize(&canonical.to_string()).unwrap()
+        );
+        assert_eq!(
+            bincode::deserialize::<TransactionHash>(&bincode::serialize(&hash).unwrap()).unwrap(),
+            hash
+        );
+    }
+
+    #[test]
+    fn should_decode_transactions_stored_with_previous_layout() {
+        let legacy_tx = legacy::EvmToIcpTxV0 {
+            from_address: Address::from_str("0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54").unwrap(),
+            transaction_hash: "0x8218f324b45a8cd36f38586b062e3884588d926035f08e1dcd3605160b3ebd42"
+                .to_string(),
+            value: Erc20TokenAmount::from(1_000_u64),
+            ledger_mint_index: None,
+            block_number: None,
+            actual_received: None,
+            principal: Principal::anonymous(),
+            subaccount: None,
+            chain_id: ChainId(56),
+            total_gas_spent: None,
+            erc20_contract_address: Address::from_str(NATIVE_ERC20_ADDRESS).unwrap(),
+            icrc_ledger_id: None,
+            status: EvmToIcpStatus::PendingVerification,
+            verified: false,
+            time: 1,
+            operator: Operator::AppicMinter,
+        };
+        let bytes = bincode::serialize(&legacy_tx).unwrap();
+
+        let tx = EvmToIcpTx::try_from(legacy::EvmToIcpTxV0::from_bytes(Cow::Owned(bytes))).unwrap();
+        assert_eq!(tx.accepted_usd_price, None);
+        // The submission time of an unverified deposit is kept
+        assert_eq!(tx.submitted_at, Some(1));
+        assert_eq!(tx.accepted_at, None);
+
+        // Records in the current layout round trip unchanged
+        assert_eq!(EvmToIcpTx::from_bytes(tx.to_bytes()), tx);
+    }
+
+    #[test]
+    fn should_migrate_legacy_transactions() {
+        let hash = "0x8218f324b45a8cd36f38586b062e3884588d926035f08e1dcd3605160b3ebd42";
+        let identifier =
+            EvmToIcpTxIdentifier::new(&TransactionHash::from_str(hash).unwrap(), ChainId(56));
+        let legacy_tx = legacy::EvmToIcpTxV0 {
+            from_address: Address::from_str("0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54").unwrap(),
+            transaction_hash: hash.to_string(),
+            value: Erc20TokenAmount::from(1_000_u64),
+            ledger_mint_index: None,
+            block_number: None,
+            actual_received: None,
+            principal: Principal::anonymous(),
+            subaccount: None,
+            chain_id: ChainId(56),
+            total_gas_spent: None,
+            erc20_contract_address: Address::from_str(NATIVE_ERC20_ADDRESS).unwrap(),
+            icrc_ledger_id: None,
+            status: EvmToIcpStatus::Accepted,
+            verified: true,
+            time: 5,
+            operator: Operator::AppicMinter,
+        };
+        let legacy_txs =
+            || -> BTreeMap<legacy::EvmToIcpTxIdentifierV0, legacy::EvmToIcpTxV0, StableMemory> {
+                BTreeMap::init(storage_config::legacy_evm_to_icp_memory())
+            };
+        legacy_txs().insert(
+            legacy::EvmToIcpTxIdentifierV0(hash.to_string(), ChainId(56)),
+            legacy_tx.clone(),
+        );
+
+        // A user submitted the same deposit with its hash in upper case and without prefix,
+        // another one submitted a hash that is not a hash
+        for submitted_hash in [hash[2..].to_uppercase(), "0x1234".to_string()] {
+            let submission = legacy::EvmToIcpTxV0 {
+                transaction_hash: submitted_hash.clone(),
+                status: EvmToIcpStatus::PendingVerification,
+                verified: false,
+                time: 3,
+                ..legacy_tx.clone()
+            };
+            legacy_txs().insert(
+                legacy::EvmToIcpTxIdentifierV0(submitted_hash, ChainId(56)),
+                submission,
+            );
+        }
+
+        // A withdrawal signed by the minter
+        let withdrawal = IcpToEvmIdentifier::new(LedgerBurnIndex::new(7), ChainId(56));
+        let mut legacy_withdrawals: BTreeMap<
+            IcpToEvmIdentifier,
+            legacy::IcpToEvmTxV0,
+            StableMemory,
+        > = BTreeMap::init(storage_config::legacy_icp_to_evm_memory());
+        legacy_withdrawals.insert(
+            withdrawal.clone(),
+            legacy::IcpToEvmTxV0 {
+                transaction_hash: None,
+                native_ledger_burn_index: LedgerBurnIndex::new(7),
+                withdrawal_amount: Erc20TokenAmount::from(1_000_u64),
+                actual_received: None,
+                destination: Address::from_str("0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54")
+                    .unwrap(),
+                from: Principal::anonymous(),
+                chain_id: ChainId(56),
+                from_subaccount: None,
+                time: 4,
+                max_transaction_fee: None,
+                effective_gas_price: None,
+                gas_used: None,
+                total_gas_spent: None,
+                erc20_ledger_burn_index: None,
+                erc20_contract_address: Address::from_str(NATIVE_ERC20_ADDRESS).unwrap(),
+                icrc_ledger_id: None,
+                verified: true,
+                status: IcpToEvmStatus::SignedTransaction,
+                operator: Operator::AppicMinter,
+            },
+        );
+
+        // Batches are bounded, the next one resumes where the previous one stopped
+        assert!(read_state(|s| s.is_migrating_legacy_transactions()));
+        assert_eq!(
+            mutate_state(|s| s.migrate_legacy_transactions(2)),
+            LegacyMigration {
+                deposits: 1,
+                dropped: 1,
+                remaining: true,
+                ..Default::default()
+            }
+        );
+        assert!(read_state(|s| s.is_migrating_legacy_transactions()));
+        assert_eq!(
+            mutate_state(|s| s.migrate_legacy_transactions(2)),
+            LegacyMigration {
+                withdrawals: 1,
+                dropped: 1,
+                ..Default::default()
+            }
+        );
+        assert!(!read_state(|s| s.is_migrating_legacy_transactions()));
+        assert!(legacy_txs().is_empty());
+        assert!(legacy_withdrawals.is_empty());
+
+        // Both wait for the minter from the time they were accepted
+        read_state(|s| {
+            assert_eq!(
+                s.waiting_txs.get(&AlertKey::EvmToIcp(identifier.clone())),
+                Some(5)
+            );
+            assert_eq!(s.waiting_txs.get(&AlertKey::IcpToEvm(withdrawal)), Some(4));
+        });
+
+        let tx = read_state(|s| s.evm_to_icp_txs.get(&identifier)).unwrap();
+        assert!(tx.verified);
+        assert_eq!(tx.transaction_hash.to_string(), hash);
+        assert_eq!(tx.submitted_at, None);
+        assert_eq!(tx.accepted_at, Some(5));
+        assert_eq!(tx.minted_at, None);
+        assert_eq!(read_state(|s| s.evm_to_icp_txs.len()), 1);
+
+        // The kept deposit is recorded and both dropped submissions are removed under the key
+        // they were stored with
+        let events: Vec<(String, LoggerEventPayload)> = read_state(|s| {
+            (0..s.logger_events.len())
+                .filter_map(|index| s.logger_events.get(index))
+                .map(|event| {
+                    let payload = candid::decode_one(&event.payload).unwrap();
+                    (event.kind, payload)
+                })
+                .collect()
+        });
+        assert_eq!(
+            events
+                .iter()
+                .map(|(kind, _payload)| kind.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "evm_to_icp_removed",
+                "evm_to_icp",
+                "evm_to_icp_removed",
+                "icp_to_evm"
+            ]
+        );
+        assert!(events
+            .iter()
+            .all(|(_kind, payload)| payload.source == ChangeSource::Migration));
+        assert_eq!(
+            events[2].1.change,
+            StateChange::RemovedEvmToIcp {
+                transaction_hash: hash[2..].to_uppercase(),
+                chain_id: Nat::from(56_u64),
+            }
+        );
+
+        // Nothing is left to migrate on the next upgrade
+        assert_eq!(
+            mutate_state(|s| s.migrate_legacy_transactions(2)),
+            LegacyMigration::default()
+        );
+    }
+
+    #[test]
+    fn should_apply_fees_in_effect_at_event_time() {
+        let minter_key = MinterKey(ChainId(56), Operator::AppicMinter);
+        let fee = |amount: u64| Erc20TokenAmount::from(amount);
+
+        mutate_state(|s| {
+            s.record_minter(Minter {
+                id: Principal::anonymous(),
+                last_observed_event: 0,
+                last_scraped_event: 0,
+                operator: Operator::AppicMinter,
+                evm_to_icp_fee: fee(10),
+                icp_to_evm_fee: fee(20),
+                chain_id: ChainId(56),
+            });
+            // Minters without a schedule never changed their fees
+            assert_eq!(
+                s.get_minter_fees_at(&minter_key, 0),
+                Some((fee(10), fee(20)))
+            );
+
+            s.update_minter_fees(&minter_key, fee(30), fee(40), Some((5, 1_000)));
+            s.update_minter_fees(&minter_key, fee(50), fee(60), Some((9, 2_000)));
+
+            // The fees before the first change are not known
+            assert_eq!(s.get_minter_fees_at(&minter_key, 999), None);
+            assert_eq!(
+                s.get_minter_fees_at(&minter_key, 1_000),
+                Some((fee(30), fee(40)))
+            );
+            assert_eq!(
+                s.get_minter_fees_at(&minter_key, 2_500),
+                Some((fee(50), fee(60)))
+            );
+            let schedule = s.get_fee_schedule(&minter_key);
+            assert_eq!(schedule.len(), 2);
+            assert_eq!(schedule[0].effective_from_event, 5);
+        });
+    }
+
+    #[test]
+    fn should_track_native_fee_of_erc20_withdrawals() {
+        let amount = |amount: u64| Erc20TokenAmount::from(amount);
+        let identifier = IcpToEvmIdentifier::new(LedgerBurnIndex::new(7), ChainId(56));
+
+        mutate_state(|s| {
+            s.record_accepted_icp_to_evm(
+                identifier.clone(),
+                Some(Nat::from(1_000_u64)),
+                Nat::from(50_000_u64),
+                "0x55d398326f99059ff775485246999027b3197955".to_string(),
+                "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                Nat::from(7_u64),
+                Some(Nat::from(3_u64)),
+                Principal::anonymous(),
+                None,
+                Operator::AppicMinter,
+                ChainId(56),
+                1,
+            );
+            s.record_finalized_icp_to_evm(
+                identifier.clone(),
+                TransactionReceipt {
+                    block_hash: "0x".to_string(),
+                    block_number: Nat::from(1_u64),
+                    effective_gas_price: Nat::from(2_u64),
+                    gas_used: Nat::from(300_u64),
+                    status: TransactionStatus::Success,
+                    transaction_hash:
+                        "0x2a8d1b6ad0a3e4c8e5cbb4fe0e4f8e1d7c8f5b2b8a6e4c1d3f5a7b9c0d2e4f60"
+                            .to_string(),
+                },
+                Some(amount(100)),
+                2,
+            );
+            // The unused part of the native fee is reimbursed after the withdrawal succeeded
+            s.record_native_reimbursed_icp_to_evm(
+                identifier.clone(),
+                Nat::from(300_u64),
+                Some(LedgerMintIndex::new(9)),
+                3,
+            );
+        });
+
+        let tx = read_state(|s| s.icp_to_evm_txs.get(&identifier).unwrap());
+        assert_eq!(tx.status, IcpToEvmStatus::Successful);
+        assert_eq!(tx.actual_received, Some(amount(50_000)));
+        assert_eq!(tx.total_gas_spent, Some(amount(700)));
+
+        let tx = CandidIcpToEvm::from(tx);
+        assert_eq!(
+            tx.erc20_burn.map(|burn| (burn.burn_index, burn.amount)),
+            Some((Nat::from(3_u64), Nat::from(50_000_u64)))
+        );
+        assert_eq!(
+            tx.native_fee_burn
+                .map(|burn| (burn.burn_index, burn.amount)),
+            Some((Nat::from(7_u64), Nat::from(1_000_u64)))
+        );
+        assert_eq!(tx.native_fee_paid, Some(Nat::from(700_u64)));
+        assert_eq!(
+            tx.native_reimbursement.and_then(|r| r.reimbursed_in_block),
+            Some(Nat::from(9_u64))
+        );
+        assert_eq!(tx.erc20_reimbursement, None);
+    }
+
+    #[test]
+    fn should_report_and_drop_mismatching_deposit_submissions() {
+        let transaction_hash = TransactionHash::from_str(
+            "0x1d9eb0c4b66d9a1a77c3a1efc0ba19f3e3bc4d0f6bce3b3c4d8e8e2bda3c7e11",
+        )
+        .unwrap();
+        let identifier = EvmToIcpTxIdentifier::new(&transaction_hash, ChainId(56));
+        let from_address = "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54";
+        let caller = Principal::from_slice(&[1]);
+
+        mutate_state(|s| {
+            s.record_new_evm_to_icp(
+                identifier.clone(),
+                EvmToIcpTx {
+                    from_address: Address::from_str(from_address).unwrap(),
+                    transaction_hash,
+                    value: Erc20TokenAmount::from(5_000_u64),
+                    ledger_mint_index: None,
+                    block_number: None,
+                    actual_received: None,
+                    principal: Principal::anonymous(),
+                    subaccount: None,
+                    chain_id: ChainId(56),
+                    total_gas_spent: Some(Erc20TokenAmount::from(7_u64)),
+                    erc20_contract_address: Address::from_str(NATIVE_ERC20_ADDRESS).unwrap(),
+                    icrc_ledger_id: None,
+                    status: EvmToIcpStatus::PendingVerification,
+                    verified: false,
+                    submitted_at: Some(1),
+                    accepted_at: None,
+                    minted_at: None,
+                    operator: Operator::AppicMinter,
+                    accepted_usd_price: None,
+                    accepted_usd_value: None,
+                },
+                caller,
+            );
+            assert!(s.deposit_submissions.contains_key(&identifier));
+
+            s.record_accepted_evm_to_icp(
+                identifier.clone(),
+                Nat::from(100_u64),
+                from_address.to_string(),
+                Nat::from(4_000_u64),
+                Principal::anonymous(),
+                NATIVE_ERC20_ADDRESS.to_string(),
+                None,
+                ChainId(56),
+                Operator::AppicMinter,
+                2,
+            );
+
+            let tx = s.evm_to_icp_txs.get(&identifier).unwrap();
+            assert!(tx.verified);
+            assert_eq!(tx.value, Erc20TokenAmount::from(4_000_u64));
+            assert_eq!(tx.total_gas_spent, None);
+            assert!(!s.deposit_submissions.contains_key(&identifier));
+
+            let discrepancies = s.get_deposit_discrepancies();
+            assert_eq!(discrepancies.len(), 1);
+            assert_eq!(discrepancies[0].submitted_by, caller);
+            assert_eq!(discrepancies[0].detected_at, 2);
+            assert_eq!(
+                discrepancies[0]
+                    .mismatches
+                    .iter()
+                    .map(|mismatch| mismatch.field)
+                    .collect::<Vec<_>>(),
+                vec![DepositField::Value, DepositField::TotalGasSpent]
+            );
+            assert_eq!(
+                discrepancies[0].mismatches[0].submitted,
+                Some("5000".to_string())
+            );
+
+            let kinds: Vec<String> = (0..s.logger_events.len())
+                .filter_map(|index| s.logger_events.get(index))
+                .map(|event| event.kind)
+                .collect();
+            assert_eq!(
+                kinds,
+                vec![
+                    "deposit_submission",
+                    "evm_to_icp",
+                    "deposit_submission_removed",
+                    "deposit_discrepancy",
+                    "evm_to_icp",
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn should_search_across_chains() {
+        let hash = "0x8218f324b45a8cd36f38586b062e3884588d926035f08e1dcd3605160b3ebd42";
+        let transaction_hash = TransactionHash::from_str(hash).unwrap();
+
+        mutate_state(|s| {
+            for chain_id in [ChainId(1), ChainId(56)] {
+                s.record_accepted_evm_to_icp(
+                    EvmToIcpTxIdentifier::new(&transaction_hash, chain_id),
+                    Nat::from(100_u64),
+                    "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                    Nat::from(4_000_u64),
+                    Principal::anonymous(),
+                    NATIVE_ERC20_ADDRESS.to_string(),
+                    None,
+                    chain_id,
+                    Operator::AppicMinter,
+                    1,
+                );
+            }
+            s.record_minted_evm_to_icp(
+                EvmToIcpTxIdentifier::new(&transaction_hash, ChainId(56)),
+                Some(Erc20TokenAmount::ZERO),
+                LedgerMintIndex::new(7),
+                2,
+            );
+
+            let search = |query: &str| s.search(&SearchQuery::parse(query).unwrap());
+            assert_eq!(search(&hash.to_uppercase()).transactions.len(), 2);
+            assert_eq!(search("7").transactions.len(), 1);
+            assert_eq!(search("8").transactions.len(), 0);
+            assert_eq!(
+                search("0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54")
+                    .transactions
+                    .len(),
+                2
+            );
+        });
+    }
+
+    #[test]
+    fn should_tell_subaccounts_apart() {
+        let owner = Principal::from_slice(&[7]);
+        let deposit = |byte: u8, subaccount: Option<[u8; 32]>| {
+            let transaction_hash = TransactionHash::new([byte; 32]);
+            let identifier = EvmToIcpTxIdentifier::new(&transaction_hash, ChainId(56));
+            mutate_state(|s| {
+                s.record_accepted_evm_to_icp(
+                    identifier,
+                    Nat::from(100_u64),
+                    "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                    Nat::from(4_000_u64),
+                    owner,
+                    NATIVE_ERC20_ADDRESS.to_string(),
+                    subaccount,
+                    ChainId(56),
+                    Operator::AppicMinter,
+                    1,
+                )
+            });
+        };
+        deposit(1, None);
+        deposit(2, Some([0; 32]));
+        deposit(3, Some([1; 32]));
+
+        read_state(|s| {
+            assert_eq!(s.get_transaction_for_principal(owner).len(), 3);
+
+            let default_account = s.get_transaction_for_account(Account {
+                owner,
+                subaccount: None,
+            });
+            assert_eq!(default_account.len(), 2);
+
+            let account = Account {
+                owner,
+                subaccount: Some([1; 32]),
+            };
+            let transactions = s.get_transaction_for_account(account);
+            assert_eq!(transactions.len(), 1);
+            match &transactions[0] {
+                Transaction::EvmToIcp(tx) => assert_eq!(tx.account, account.to_string()),
+                Transaction::IcpToEvm(_) => panic!("expected a deposit"),
+            }
+        });
+    }
+
+    #[test]
+    fn should_get_transactions_by_their_id() {
+        let transaction_hash = TransactionHash::new([9; 32]);
+        let deposit = EvmToIcpTxIdentifier::new(&transaction_hash, ChainId(56));
+        let withdrawal = IcpToEvmIdentifier::new(LedgerBurnIndex::new(9), ChainId(56));
+
+        mutate_state(|s| {
+            s.record_accepted_evm_to_icp(
+                deposit.clone(),
+                Nat::from(100_u64),
+                "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                Nat::from(4_000_u64),
+                Principal::anonymous(),
+                NATIVE_ERC20_ADDRESS.to_string(),
+                None,
+                ChainId(56),
+                Operator::AppicMinter,
+                1,
+            );
+            s.record_accepted_icp_to_evm(
+                withdrawal.clone(),
+                None,
+                Nat::from(50_000_u64),
+                NATIVE_ERC20_ADDRESS.to_string(),
+                "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                Nat::from(9_u64),
+                None,
+                Principal::anonymous(),
+                None,
+                Operator::AppicMinter,
+                ChainId(56),
+                1,
+            );
+        });
+
+        // The burn index of the withdrawal must not be mistaken for the hash of the deposit
+        let deposit_id = TransactionId::EvmToIcp(deposit).to_string();
+        let withdrawal_id = TransactionId::IcpToEvm(withdrawal).to_string();
+        assert_ne!(deposit_id, withdrawal_id);
+
+        read_state(|s| {
+            for id in [&deposit_id, &withdrawal_id] {
+                let parsed = TransactionId::from_str(id).unwrap();
+                assert_eq!(parsed.to_string(), *id);
+
+                let returned_id = match s.get_transaction_by_id(&parsed).unwrap() {
+                    Transaction::EvmToIcp(tx) => tx.id,
+                    Transaction::IcpToEvm(tx) => tx.id,
+                };
+                assert_eq!(returned_id, *id);
+            }
+
+            let missing = TransactionId::IcpToEvm(IcpToEvmIdentifier::new(
+                LedgerBurnIndex::new(9),
+                ChainId(1),
+            ));
+            assert_eq!(s.get_transaction_by_id(&missing), None);
+        });
+
+        assert!(TransactionId::from_str("icp_to_evm:56").is_err());
+        assert!(TransactionId::from_str("evm_to_icp:56:9").is_err());
+        assert!(TransactionId::from_str("unknown:56:9").is_err());
+    }
+
+    #[test]
+    fn should_count_migrated_transactions_in_bridge_volume() {
+        let accepted_at = 5 * 24 * 60 * 60 * 1_000_000_000;
+        let legacy_tx = |hash: &str, status: EvmToIcpStatus| legacy::EvmToIcpTxV0 {
+            from_address: Address::from_str("0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54").unwrap(),
+            transaction_hash: hash.to_string(),
+            value: Erc20TokenAmount::from(4_000_u64),
+            ledger_mint_index: None,
+            block_number: None,
+            actual_received: None,
+            principal: Principal::anonymous(),
+            subaccount: None,
+            chain_id: ChainId(56),
+            total_gas_spent: None,
+            erc20_contract_address: Address::from_str(NATIVE_ERC20_ADDRESS).unwrap(),
+            icrc_ledger_id: None,
+            status,
+            verified: true,
+            time: accepted_at,
+            operator: Operator::AppicMinter,
+        };
+        let mut legacy_txs: BTreeMap<
+            legacy::EvmToIcpTxIdentifierV0,
+            legacy::EvmToIcpTxV0,
+            StableMemory,
+        > = BTreeMap::init(storage_config::legacy_evm_to_icp_memory());
+        for (hash, status) in [
+            (TransactionHash::new([3; 32]), EvmToIcpStatus::Minted),
+            (TransactionHash::new([4; 32]), EvmToIcpStatus::Accepted),
+        ] {
+            let hash = hash.to_string();
+            legacy_txs.insert(
+                legacy::EvmToIcpTxIdentifierV0(hash.clone(), ChainId(56)),
+                legacy_tx(&hash, status),
+            );
+        }
+
+        mutate_state(|s| s.migrate_legacy_transactions(LEGACY_MIGRATION_BATCH));
+
+        // Only the completed deposit is counted, on the day it was accepted and without a
+        // USD value since the first release kept no price
+        let stats = read_state(|s| {
+            s.get_bridge_stats(
+                BridgeStatsRange {
+                    from: 0,
+                    to: u64::MAX,
+                },
+                Granularity::Daily,
+                BridgeStatsFilters {
+                    chain_id: None,
+                    token: None,
+                    operator: None,
+                    direction: None,
+                },
+            )
+        });
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].count, 1);
+        assert_eq!(
+            stats[0].bucket_start,
+            Granularity::Daily.bucket_start(accepted_at)
+        );
+        assert_eq!(stats[0].usd_value, None);
+    }
+
+    #[test]
+    fn should_summarize_bridge_history_of_a_principal() {
+        let owner = Principal::from_slice(&[7]);
+        let deposit = EvmToIcpTxIdentifier::new(&TransactionHash::new([1; 32]), ChainId(56));
+        let receipt = |status: TransactionStatus, byte: u8| TransactionReceipt {
+            block_hash: "0x".to_string(),
+            block_number: Nat::from(1_u64),
+            effective_gas_price: Nat::from(2_u64),
+            gas_used: Nat::from(300_u64),
+            status,
+            transaction_hash: TransactionHash::new([byte; 32]).to_string(),
+        };
+
+        mutate_state(|s| {
+            s.record_accepted_evm_to_icp(
+                deposit.clone(),
+                Nat::from(100_u64),
+                "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                Nat::from(4_000_u64),
+                owner,
+                NATIVE_ERC20_ADDRESS.to_string(),
+                None,
+                ChainId(56),
+                Operator::AppicMinter,
+                1,
+            );
+            s.record_minted_evm_to_icp(
+                deposit,
+                Some(Erc20TokenAmount::from(100_u64)),
+                LedgerMintIndex::new(1),
+                2,
+            );
+            for (burn_index, status) in [
+                (1, TransactionStatus::Success),
+                (2, TransactionStatus::Failure),
+            ] {
+                let identifier =
+                    IcpToEvmIdentifier::new(LedgerBurnIndex::new(burn_index), ChainId(56));
+                s.record_accepted_icp_to_evm(
+                    identifier.clone(),
+                    None,
+                    Nat::from(50_000_u64),
+                    NATIVE_ERC20_ADDRESS.to_string(),
+                    "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                    Nat::from(burn_index),
+                    None,
+                    owner,
+                    None,
+                    Operator::AppicMinter,
+                    ChainId(56),
+                    1,
+                );
+                s.record_finalized_icp_to_evm(
+                    identifier,
+                    receipt(status, burn_index as u8 + 1),
+                    Some(Erc20TokenAmount::from(100_u64)),
+                    2,
+                );
+            }
+        });
+
+        let summary = read_state(|s| s.get_account_summary(owner));
+        assert_eq!(summary.tokens.len(), 1);
+        assert_eq!(summary.failed_withdrawals, 1);
+        assert_eq!(summary.reimbursed_withdrawals, 0);
+        // Token prices are not known
+        assert_eq!(summary.bridged_in_usd, 0.0);
+
+        let token = &summary.tokens[0];
+        assert_eq!(token.deposit_count, 1);
+        assert_eq!(token.withdrawal_count, 2);
+        assert_eq!(token.bridged_in, Nat::from(4_000_u64));
+        assert_eq!(token.bridged_out, Nat::from(50_000_u64));
+        assert_eq!(token.net_position, candid::Int::from(-46_000_i64));
+        // Fees and gas of the failed withdrawal are paid as well
+        assert_eq!(token.minter_fees, Nat::from(300_u64));
+        assert_eq!(token.gas_fees, Nat::from(1_200_u64));
+        assert_eq!(token.bridged_in_usd, None);
+
+        assert!(
+            read_state(|s| s.get_account_summary(Principal::anonymous()))
+                .tokens
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn should_count_transactions_as_they_change_status() {
+        let accepted =
+            |byte: u8| EvmToIcpTxIdentifier::new(&TransactionHash::new([byte; 32]), ChainId(56));
+        let count = |s: &State, status: &str| {
+            s.tx_counts
+                .get(&TxCountKey {
+                    direction: BridgeDirection::EvmToIcp,
+                    chain_id: ChainId(56),
+                    status: status.to_string(),
+                    verified: true,
+                })
+                .unwrap_or_default()
+        };
+
+        mutate_state(|s| {
+            for byte in [1, 2] {
+                s.record_accepted_evm_to_icp(
+                    identifier(byte),
+                    Nat::from(100_u64),
+                    "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                    Nat::from(4_000_u64),
+                    Principal::from_slice(&[byte]),
+                    NATIVE_ERC20_ADDRESS.to_string(),
+                    None,
+                    ChainId(56),
+                    Operator::AppicMinter,
+                    u64::from(byte),
+                );
+            }
+            assert_eq!(count(s, "Accepted"), 2);
+
+            s.record_minted_evm_to_icp(
+                identifier(1),
+                Some(Erc20TokenAmount::ZERO),
+                LedgerMintIndex::new(7),
+                3,
+            );
+            assert_eq!(count(s, "Accepted"), 1);
+            assert_eq!(count(s, "Minted"), 1);
+
+            s.record_quarantined_evm_to_icp(identifier(2));
+            assert_eq!(count(s, "Accepted"), 0);
+            assert_eq!(s.tx_counts.len(), 2);
+        });
+    }
+
+    #[test]
+    fn should_export_pages_at_the_state_version() {
+        mutate_state(|s| {
+            for byte in 1..=3_u8 {
+                s.record_accepted_evm_to_icp(
+                    EvmToIcpTxIdentifier::new(&TransactionHash::new([byte; 32]), ChainId(56)),
+                    Nat::from(100_u64),
+                    "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                    Nat::from(4_000_u64),
+                    Principal::from_slice(&[byte]),
+                    NATIVE_ERC20_ADDRESS.to_string(),
+                    None,
+                    ChainId(56),
+                    Operator::AppicMinter,
+                    u64::from(byte),
+                );
+            }
+        });
+        let args = |cursor: Option<serde_bytes::ByteBuf>| ExportArgs {
+            collection: ExportCollection::EvmToIcpTxs,
+            format: ExportFormat::Ndjson,
+            cursor,
+            length: 2,
+        };
+
+        let first_page = read_state(|s| s.export(&args(None))).unwrap();
+        assert_eq!(first_page.entry_count, 2);
+        let last_page = read_state(|s| s.export(&args(first_page.next_cursor.clone()))).unwrap();
+        assert_eq!(last_page.entry_count, 1);
+        assert_eq!(last_page.next_cursor, None);
+        assert_eq!(last_page.sequence, first_page.sequence);
+
+        // Only changes to the exported maps move the sequence
+        mutate_state(|_s| ());
+        assert_eq!(
+            read_state(|s| s.export(&args(None))).unwrap().sequence,
+            first_page.sequence
+        );
+        mutate_state(|s| {
+            s.record_invalid_evm_to_icp(
+                EvmToIcpTxIdentifier::new(&TransactionHash::new([1; 32]), ChainId(56)),
+                "wrong block".to_string(),
+            )
+        });
+        let page = read_state(|s| s.export(&args(None))).unwrap();
+        assert!(page.sequence > first_page.sequence);
+
+        assert_eq!(
+            read_state(|s| s.export(&args(Some(serde_bytes::ByteBuf::from(vec![1, 2]))))),
+            Err(LoggerQueryError::InvalidCursor)
+        );
+        assert_eq!(
+            read_state(|s| s.export(&ExportArgs {
+                length: MAX_EXPORT_PAGE_LENGTH + 1,
+                ..args(None)
+            })),
+            Err(LoggerQueryError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn should_page_statements_oldest_first() {
+        let owner = Principal::from_slice(&[7]);
+        let other = Principal::from_slice(&[8]);
+        mutate_state(|s| {
+            for (byte, principal) in [(3_u8, owner), (1, owner), (2, other), (4, owner)] {
+                s.record_accepted_evm_to_icp(
+                    EvmToIcpTxIdentifier::new(&TransactionHash::new([byte; 32]), ChainId(56)),
+                    Nat::from(100_u64),
+                    "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                    Nat::from(4_000_u64),
+                    principal,
+                    NATIVE_ERC20_ADDRESS.to_string(),
+                    None,
+                    ChainId(56),
+                    Operator::AppicMinter,
+                    u64::from(byte),
+                );
+            }
+        });
+        let accepted_at = |rows: &[(StatementKey, StatementRow)]| {
+            rows.iter()
+                .map(|(_key, row)| row.accepted_at)
+                .collect::<Vec<_>>()
+        };
+
+        read_state(|s| {
+            let (first_page, has_more) = s.get_statement_page(owner, None, 2);
+            assert_eq!(accepted_at(&first_page), vec![Some(1), Some(3)]);
+            assert!(has_more);
+
+            // The next page starts after the last key, the rows of other principals are skipped
+            let (last_page, has_more) = s.get_statement_page(owner, Some(&first_page[1].0), 2);
+            assert_eq!(accepted_at(&last_page), vec![Some(4)]);
+            assert!(!has_more);
+
+            assert_eq!(s.get_statement_page(other, None, 2).0.len(), 1);
+        });
+
+        // Moving to another principal moves the row to its statement
+        mutate_state(|s| {
+            s.record_accepted_evm_to_icp(
+                EvmToIcpTxIdentifier::new(&TransactionHash::new([4; 32]), ChainId(56)),
+                Nat::from(100_u64),
+                "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                Nat::from(4_000_u64),
+                other,
+                NATIVE_ERC20_ADDRESS.to_string(),
+                None,
+                ChainId(56),
+                Operator::AppicMinter,
+                4,
+            );
+            assert_eq!(s.get_statement_page(owner, None, 10).0.len(), 2);
+            assert_eq!(s.get_statement_page(other, None, 10).0.len(), 2);
+            assert_eq!(s.statement_index.len(), 4);
+        });
+    }
+
+    #[test]
+    fn should_page_recent_transactions_newest_first() {
+        let owner = Principal::from_slice(&[7]);
+        mutate_state(|s| {
+            for byte in 1..=3_u8 {
+                s.record_accepted_evm_to_icp(
+                    EvmToIcpTxIdentifier::new(&TransactionHash::new([byte; 32]), ChainId(56)),
+                    Nat::from(100_u64),
+                    "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                    Nat::from(4_000_u64),
+                    owner,
+                    NATIVE_ERC20_ADDRESS.to_string(),
+                    None,
+                    ChainId(56),
+                    Operator::AppicMinter,
+                    u64::from(byte),
+                );
+            }
+            s.record_accepted_icp_to_evm(
+                IcpToEvmIdentifier::new(LedgerBurnIndex::new(1), ChainId(1)),
+                None,
+                Nat::from(50_000_u64),
+                NATIVE_ERC20_ADDRESS.to_string(),
+                "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                Nat::from(1_u64),
+                None,
+                owner,
+                None,
+                Operator::DfinityCkEthMinter,
+                ChainId(1),
+                2,
+            );
+        });
+        let mut filters = RecentTransactionsFilters {
+            chain_id: None,
+            operator: None,
+            direction: None,
+            redact: false,
+        };
+        let accepted_at = |transaction: &Transaction| match transaction {
+            Transaction::EvmToIcp(tx) => tx.accepted_at,
+            Transaction::IcpToEvm(tx) => tx.accepted_at,
+        };
+
+        read_state(|s| {
+            let first_page = s.get_recent_transactions(3, None, &filters);
+            assert_eq!(
+                first_page
+                    .transactions
+                    .iter()
+                    .map(accepted_at)
+                    .collect::<Vec<_>>(),
+                vec![Some(3), Some(2), Some(2)]
+            );
+
+            let cursor = RecentActivityKey::from_str(&first_page.next_cursor.unwrap()).unwrap();
+            let last_page = s.get_recent_transactions(3, Some(cursor), &filters);
+            assert_eq!(last_page.transactions.len(), 1);
+            assert_eq!(accepted_at(&last_page.transactions[0]), Some(1));
+            assert_eq!(last_page.next_cursor, None);
+
+            filters.direction = Some(BridgeDirection::IcpToEvm);
+            filters.redact = true;
+            let withdrawals = s.get_recent_transactions(10, None, &filters);
+            assert_eq!(withdrawals.transactions.len(), 1);
+            match &withdrawals.transactions[0] {
+                Transaction::IcpToEvm(tx) => {
+                    assert_eq!(tx.from, Principal::anonymous());
+                    assert_eq!(tx.destination, crate::recent_activity::REDACTED);
+                }
+                Transaction::EvmToIcp(_) => panic!("expected a withdrawal"),
+            }
+        });
+
+        // Removed transactions leave the feed
+        mutate_state(|s| {
+            s.remove_unverified_icp_to_evm(&IcpToEvmIdentifier::new(
+                LedgerBurnIndex::new(1),
+                ChainId(1),
+            ));
+            assert_eq!(s.recent_activity.len(), 3);
+        });
+    }
+
+    #[test]
+    fn should_stop_recent_pages_at_the_scan_limit() {
+        let hash = |index: u64| {
+            let mut bytes = [0; 32];
+            bytes[..8].copy_from_slice(&index.to_be_bytes());
+            TransactionHash::new(bytes)
+        };
+        // The only deposit on chain 1 is older than a full scan of deposits on chain 56
+        mutate_state(|s| {
+            for index in 0..=MAX_SCANNED_RECENT_ACTIVITY as u64 {
+                let chain_id = if index == 0 { ChainId(1) } else { ChainId(56) };
+                s.record_accepted_evm_to_icp(
+                    EvmToIcpTxIdentifier::new(&hash(index), chain_id),
+                    Nat::from(100_u64),
+                    "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                    Nat::from(4_000_u64),
+                    Principal::anonymous(),
+                    NATIVE_ERC20_ADDRESS.to_string(),
+                    None,
+                    chain_id,
+                    Operator::AppicMinter,
+                    index + 1,
+                );
+            }
+        });
+        let filters = RecentTransactionsFilters {
+            chain_id: Some(Nat::from(1_u64)),
+            operator: None,
+            direction: None,
+            redact: false,
+        };
+
+        read_state(|s| {
+            let first_page = s.get_recent_transactions(10, None, &filters);
+            assert!(first_page.transactions.is_empty());
+            let cursor = RecentActivityKey::from_str(&first_page.next_cursor.unwrap()).unwrap();
+
+            let last_page = s.get_recent_transactions(10, Some(cursor), &filters);
+            assert_eq!(last_page.transactions.len(), 1);
+            assert_eq!(last_page.next_cursor, None);
+        });
+    }
+
+    #[test]
+    fn should_page_transactions_of_a_token() {
+        let owner = Principal::from_slice(&[8]);
+        let native_on_bsc = TokenIndex::Erc20(Erc20Identifier::new(
+            &Address::from_str(NATIVE_ERC20_ADDRESS).unwrap(),
+            ChainId(56),
+        ));
+        mutate_state(|s| {
+            for byte in 1..=3_u8 {
+                s.record_accepted_evm_to_icp(
+                    EvmToIcpTxIdentifier::new(&TransactionHash::new([byte; 32]), ChainId(56)),
+                    Nat::from(100_u64),
+                    "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                    Nat::from(4_000_u64),
+                    owner,
+                    NATIVE_ERC20_ADDRESS.to_string(),
+                    None,
+                    ChainId(56),
+                    Operator::AppicMinter,
+                    u64::from(byte),
+                );
+            }
+            s.record_accepted_icp_to_evm(
+                IcpToEvmIdentifier::new(LedgerBurnIndex::new(1), ChainId(1)),
+                None,
+                Nat::from(50_000_u64),
+                NATIVE_ERC20_ADDRESS.to_string(),
+                "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                Nat::from(1_u64),
+                None,
+                owner,
+                None,
+                Operator::DfinityCkEthMinter,
+                ChainId(1),
+                2,
+            );
+            s.record_invalid_evm_to_icp(
+                EvmToIcpTxIdentifier::new(&TransactionHash::new([1; 32]), ChainId(56)),
+                "wrong block".to_string(),
+            );
+        });
+        let mut filters = TokenTransactionsFilters {
+            direction: None,
+            status: None,
+            from_timestamp: None,
+            to_timestamp: None,
+        };
+        let accepted_at = |transactions: &[Transaction]| {
+            transactions
+                .iter()
+                .map(|transaction| match transaction {
+                    Transaction::EvmToIcp(tx) => tx.accepted_at,
+                    Transaction::IcpToEvm(tx) => tx.accepted_at,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        read_state(|s| {
+            let first_page = s.get_token_transactions(native_on_bsc.clone(), 2, None, &filters);
+            assert_eq!(
+                accepted_at(&first_page.transactions),
+                vec![Some(3), Some(2)]
+            );
+            let cursor = RecentActivityKey::from_str(&first_page.next_cursor.unwrap()).unwrap();
+            let last_page =
+                s.get_token_transactions(native_on_bsc.clone(), 2, Some(cursor), &filters);
+            assert_eq!(accepted_at(&last_page.transactions), vec![Some(1)]);
+            assert_eq!(last_page.next_cursor, None);
+
+            filters.from_timestamp = Some(2);
+            filters.to_timestamp = Some(2);
+            let in_range = s.get_token_transactions(native_on_bsc.clone(), 10, None, &filters);
+            assert_eq!(accepted_at(&in_range.transactions), vec![Some(2)]);
+
+            // Only the variant of a status is matched
+            filters.from_timestamp = None;
+            filters.to_timestamp = None;
+            filters.status = Some(StatusFilter::EvmToIcp(EvmToIcpStatus::Invalid(
+                String::new(),
+            )));
+            let invalid = s.get_token_transactions(native_on_bsc.clone(), 10, None, &filters);
+            assert_eq!(accepted_at(&invalid.transactions), vec![Some(1)]);
+
+            filters.status = Some(StatusFilter::IcpToEvm(IcpToEvmStatus::Accepted));
+            let withdrawals = s.get_token_transactions(native_on_bsc.clone(), 10, None, &filters);
+            assert!(withdrawals.transactions.is_empty());
+        });
+
+        // Removed transactions leave the index
+        mutate_state(|s| {
+            s.remove_unverified_evm_to_icp(&EvmToIcpTxIdentifier::new(
+                &TransactionHash::new([3; 32]),
+                ChainId(56),
+            ));
+            assert_eq!(s.token_activity.len(), 3);
+        });
+    }
+
+    #[test]
+    fn should_stop_token_pages_at_the_scan_limit() {
+        let native_on_bsc = TokenIndex::Erc20(Erc20Identifier::new(
+            &Address::from_str(NATIVE_ERC20_ADDRESS).unwrap(),
+            ChainId(56),
+        ));
+        let hash = |index: u64| {
+            let mut bytes = [0; 32];
+            bytes[..8].copy_from_slice(&index.to_be_bytes());
+            TransactionHash::new(bytes)
+        };
+        // The only invalid deposit is older than a full scan of accepted ones
+        mutate_state(|s| {
+            for index in 0..=MAX_SCANNED_TOKEN_ACTIVITY as u64 {
+                s.record_accepted_evm_to_icp(
+                    EvmToIcpTxIdentifier::new(&hash(index), ChainId(56)),
+                    Nat::from(100_u64),
+                    "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                    Nat::from(4_000_u64),
+                    Principal::anonymous(),
+                    NATIVE_ERC20_ADDRESS.to_string(),
+                    None,
+                    ChainId(56),
+                    Operator::AppicMinter,
+                    index + 1,
+                );
+            }
+            s.record_invalid_evm_to_icp(
+                EvmToIcpTxIdentifier::new(&hash(0), ChainId(56)),
+                "wrong block".to_string(),
+            );
+        });
+        let filters = TokenTransactionsFilters {
+            direction: None,
+            status: Some(StatusFilter::EvmToIcp(EvmToIcpStatus::Invalid(
+                String::new(),
+            ))),
+            from_timestamp: None,
+            to_timestamp: None,
+        };
+
+        read_state(|s| {
+            let first_page = s.get_token_transactions(native_on_bsc.clone(), 10, None, &filters);
+            assert!(first_page.transactions.is_empty());
+            let cursor = RecentActivityKey::from_str(&first_page.next_cursor.unwrap()).unwrap();
+
+            let last_page = s.get_token_transactions(native_on_bsc, 10, Some(cursor), &filters);
+            assert_eq!(last_page.transactions.len(), 1);
+            assert_eq!(last_page.next_cursor, None);
+        });
+    }
+
+    #[test]
+    fn should_raise_and_clear_stuck_alerts() {
+        const HOUR: u64 = 3_600_000_000_000;
+        let identifier = IcpToEvmIdentifier::new(LedgerBurnIndex::new(11), ChainId(1));
+
+        mutate_state(|s| {
+            s.record_accepted_icp_to_evm(
+                identifier.clone(),
+                None,
+                Nat::from(50_000_u64),
+                NATIVE_ERC20_ADDRESS.to_string(),
+                "0x5d737f982696fe2fe4ef1c7584e914c3a8e44d54".to_string(),
+                Nat::from(11_u64),
+                None,
+                Principal::anonymous(),
+                None,
+                Operator::DfinityCkEthMinter,
+                ChainId(1),
+                HOUR,
+            );
+            // Accepted withdrawals are not waiting for a finalization yet
+            assert!(s.waiting_txs.is_empty());
+            assert_eq!(s.update_stuck_alerts(10 * HOUR), (0, 0));
+
+            // The age is measured from the signature, not from the acceptance
+            s.record_signed_icp_to_evm(identifier.clone(), 2 * HOUR);
+            assert_eq!(s.update_stuck_alerts(4 * HOUR), (0, 0));
+            assert_eq!(s.update_stuck_alerts(5 * HOUR), (1, 0));
+            assert_eq!(s.update_stuck_alerts(6 * HOUR), (0, 0));
+
+            let alerts = s.get_alerts(6 * HOUR);
+            assert_eq!(alerts.len(), 1);
+            assert_eq!(alerts[0].reason, StuckReason::WithdrawalSignedNotFinalized);
+            assert_eq!(alerts[0].accepted_at, HOUR);
+            assert_eq!(alerts[0].waiting_since, 2 * HOUR);
+            assert_eq!(alerts[0].detected_at, 5 * HOUR);
+            assert_eq!(alerts[0].age_ns, 4 * HOUR);
+
+            // A replacement restarts the wait under another reason
+            s.record_replaced_icp_to_evm(identifier.clone(), 6 * HOUR);
+            assert_eq!(s.update_stuck_alerts(7 * HOUR), (0, 1));
+            assert_eq!(s.update_stuck_alerts(9 * HOUR), (1, 0));
+            assert_eq!(
+                s.get_alerts(9 * HOUR)[0].reason,
+                StuckReason::WithdrawalReplacedNotFinalized
+            );
+
+            // Progressing clears the alert and the index entry right away
+            s.record_native_reimbursed_icp_to_evm(
+                identifier.clone(),
+                Nat::from(50_000_u64),
+                None,
+                9 * HOUR,
+            );
+            assert!(s.get_alerts(9 * HOUR).is_empty());
+            assert!(s.waiting_txs.is_empty());
+            assert_eq!(s.update_stuck_alerts(10 * HOUR), (0, 0));
+        });
+    }
+
+    #[test]
+    fn minter_health() {
+        const MINUTE: u64 = 60 * 1_000_000_000;
+        let now = 60 * MINUTE;
+
+        let never_scraped = MinterActivity::default();
+        assert_eq!(never_scraped.health(0, now), MinterHealth::Lagging);
+
+        let caught_up = MinterActivity {
+            last_scrape_attempt_at: Some(now - MINUTE),
+            last_scrape_success_at: Some(now - MINUTE),
+            last_event_applied_at: Some(now - MINUTE),
+            consecutive_failures: 0,
+        };
+        assert_eq!(caught_up.health(0, now), MinterHealth::Healthy);
+        assert_eq!(
+            caught_up.health(MAX_HEALTHY_LAG + 1, now),
+            MinterHealth::Lagging
+        );
+        assert_eq!(
+            caught_up.health(0, now + 10 * MINUTE),
+            MinterHealth::Lagging
+        );
+
+        let failed = MinterActivity {
+            consecutive_failures: 1,
+            ..caught_up.clone()
+        };
+        assert_eq!(failed.health(0, now), MinterHealth::Failing);
+
+        // Started a scrape that never completed, e.g. because it trapped
+        let stalled = MinterActivity {
+            last_scrape_attempt_at: Some(now - 15 * MINUTE),
+            last_scrape_success_at: Some(now - 16 * MINUTE),
+            ..caught_up.clone()
+        };
+        assert_eq!(stalled.health(0, now), MinterHealth::Failing);
+
+        let in_progress = MinterActivity {
+            last_scrape_attempt_at: Some(now),
+            ..caught_up
+        };
+        assert_eq!(in_progress.health(0, now), MinterHealth::Healthy);
+    }
+
+    #[test]
+    fn compare_bincode_and_ciborium() {
+        let tx_identifier: EvmToIcpTxIdentifier = EvmToIcpTxIdentifier(
+            TransactionHash::from_str(
+                "0x8218f324b45a8cd36f38586b062e3884588d926035f08e1dcd3605160b3ebd42",
+            )
+            .unwrap(),
             ChainId(56),
         );
 
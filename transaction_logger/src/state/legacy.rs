@@ -0,0 +1,153 @@
+// Layout of the transactions stored by the first release of the logger.
+// Transactions in this layout are moved to the current maps in batches after an upgrade,
+// see `State::migrate_legacy_transactions`.
+// Hashes were stored as given, so the layout keeps them as strings.
+
+use super::{
+    BlockNumber, ChainId, Erc20TokenAmount, EvmToIcpStatus, EvmToIcpTx, EvmToIcpTxIdentifier,
+    IcpToEvmStatus, IcpToEvmTx, LedgerBurnIndex, LedgerMintIndex, Operator, TransactionHash,
+};
+use candid::Principal;
+use ic_ethereum_types::Address;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+fn parse_hash(transaction_hash: &str) -> Result<TransactionHash, String> {
+    TransactionHash::from_str(transaction_hash)
+        .map_err(|e| format!("invalid transaction hash {transaction_hash}: {e}"))
+}
+
+// `time` was the submission time of transactions that are still unverified.
+// For verified ones it is the closest known time to their acceptance.
+fn split_time(time: u64, verified: bool) -> (Option<u64>, Option<u64>) {
+    if verified {
+        (None, Some(time))
+    } else {
+        (Some(time), None)
+    }
+}
+
+// Key of deposits before hashes were validated
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub struct EvmToIcpTxIdentifierV0(pub String, pub ChainId);
+
+impl TryFrom<EvmToIcpTxIdentifierV0> for EvmToIcpTxIdentifier {
+    type Error = String;
+
+    fn try_from(value: EvmToIcpTxIdentifierV0) -> Result<Self, Self::Error> {
+        Ok(Self::new(&parse_hash(&value.0)?, value.1))
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct EvmToIcpTxV0 {
+    pub from_address: Address,
+    pub transaction_hash: String,
+    pub value: Erc20TokenAmount,
+    pub ledger_mint_index: Option<LedgerMintIndex>,
+    pub block_number: Option<BlockNumber>,
+    pub actual_received: Option<Erc20TokenAmount>,
+    pub principal: Principal,
+    pub subaccount: Option<[u8; 32]>,
+    pub chain_id: ChainId,
+    pub total_gas_spent: Option<Erc20TokenAmount>,
+    pub erc20_contract_address: Address,
+    pub icrc_ledger_id: Option<Principal>,
+    pub status: EvmToIcpStatus,
+    pub verified: bool,
+    pub time: u64,
+    pub operator: Operator,
+}
+
+impl TryFrom<EvmToIcpTxV0> for EvmToIcpTx {
+    type Error = String;
+
+    fn try_from(value: EvmToIcpTxV0) -> Result<Self, Self::Error> {
+        let (submitted_at, accepted_at) = split_time(value.time, value.verified);
+        Ok(Self {
+            from_address: value.from_address,
+            transaction_hash: parse_hash(&value.transaction_hash)?,
+            value: value.value,
+            ledger_mint_index: value.ledger_mint_index,
+            block_number: value.block_number,
+            actual_received: value.actual_received,
+            principal: value.principal,
+            subaccount: value.subaccount,
+            chain_id: value.chain_id,
+            total_gas_spent: value.total_gas_spent,
+            erc20_contract_address: value.erc20_contract_address,
+            icrc_ledger_id: value.icrc_ledger_id,
+            status: value.status,
+            verified: value.verified,
+            submitted_at,
+            accepted_at,
+            minted_at: None,
+            operator: value.operator,
+            accepted_usd_price: None,
+            accepted_usd_value: None,
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct IcpToEvmTxV0 {
+    pub transaction_hash: Option<String>,
+    pub native_ledger_burn_index: LedgerBurnIndex,
+    pub withdrawal_amount: Erc20TokenAmount,
+    pub actual_received: Option<Erc20TokenAmount>,
+    pub destination: Address,
+    pub from: Principal,
+    pub chain_id: ChainId,
+    pub from_subaccount: Option<[u8; 32]>,
+    pub time: u64,
+    pub max_transaction_fee: Option<Erc20TokenAmount>,
+    pub effective_gas_price: Option<Erc20TokenAmount>,
+    pub gas_used: Option<Erc20TokenAmount>,
+    pub total_gas_spent: Option<Erc20TokenAmount>,
+    pub erc20_ledger_burn_index: Option<LedgerBurnIndex>,
+    pub erc20_contract_address: Address,
+    pub icrc_ledger_id: Option<Principal>,
+    pub verified: bool,
+    pub status: IcpToEvmStatus,
+    pub operator: Operator,
+}
+
+impl TryFrom<IcpToEvmTxV0> for IcpToEvmTx {
+    type Error = String;
+
+    fn try_from(value: IcpToEvmTxV0) -> Result<Self, Self::Error> {
+        let (submitted_at, accepted_at) = split_time(value.time, value.verified);
+        Ok(Self {
+            transaction_hash: value
+                .transaction_hash
+                .as_deref()
+                .map(parse_hash)
+                .transpose()?,
+            native_ledger_burn_index: value.native_ledger_burn_index,
+            withdrawal_amount: value.withdrawal_amount,
+            actual_received: value.actual_received,
+            destination: value.destination,
+            from: value.from,
+            chain_id: value.chain_id,
+            from_subaccount: value.from_subaccount,
+            submitted_at,
+            accepted_at,
+            finalized_at: None,
+            reimbursed_at: None,
+            max_transaction_fee: value.max_transaction_fee,
+            effective_gas_price: value.effective_gas_price,
+            gas_used: value.gas_used,
+            total_gas_spent: value.total_gas_spent,
+            erc20_ledger_burn_index: value.erc20_ledger_burn_index,
+            erc20_contract_address: value.erc20_contract_address,
+            icrc_ledger_id: value.icrc_ledger_id,
+            verified: value.verified,
+            status: value.status,
+            operator: value.operator,
+            accepted_usd_price: None,
+            accepted_usd_value: None,
+            native_reimbursement: None,
+            erc20_reimbursement: None,
+        })
+    }
+}
@@ -0,0 +1,390 @@
+// Downloadable statements of the bridged transfers of a principal, served under
+// `/statement/<principal>.csv` and `/statement/<principal>.json`.
+// Long statements are split into pages that are streamed one after the other. Transfers are
+// indexed per principal, so a page only reads its own rows.
+
+use std::str::FromStr;
+
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::bridge_stats::BridgeDirection;
+use crate::numeric::Erc20TokenAmount;
+use crate::state::{
+    ChainId, EvmToIcpTx, EvmToIcpTxIdentifier, IcpToEvmTx, Operator, TransactionHash, TransactionId,
+};
+
+// Rows rendered per streamed page
+pub const STATEMENT_PAGE_SIZE: usize = 500;
+
+const STATEMENT_PATH_PREFIX: &str = "/statement/";
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatementFormat {
+    Csv,
+    Json,
+}
+
+impl StatementFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Json => "application/json; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+// Key of the statement index. The transfers of a principal are grouped together, oldest
+// first, ordered by the time they were accepted or else submitted.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub struct StatementKey {
+    pub principal: Principal,
+    pub timestamp: u64,
+    pub id: TransactionId,
+}
+
+impl StatementKey {
+    pub fn for_evm_to_icp(tx: &EvmToIcpTx) -> Self {
+        Self {
+            principal: tx.principal,
+            timestamp: tx.accepted_at.or(tx.submitted_at).unwrap_or_default(),
+            id: TransactionId::from(tx),
+        }
+    }
+
+    pub fn for_icp_to_evm(tx: &IcpToEvmTx) -> Self {
+        Self {
+            principal: tx.from,
+            timestamp: tx.accepted_at.or(tx.submitted_at).unwrap_or_default(),
+            id: TransactionId::from(tx),
+        }
+    }
+
+    // The first key of `principal`. No transaction id sorts before a deposit with a zero hash
+    // on chain zero, so no key is skipped.
+    pub fn first_of(principal: Principal) -> Self {
+        Self {
+            principal,
+            timestamp: 0,
+            id: TransactionId::EvmToIcp(EvmToIcpTxIdentifier::new(
+                &TransactionHash::new([0; 32]),
+                ChainId(0),
+            )),
+        }
+    }
+
+    // The textual form of the key within the statement of its principal
+    pub fn cursor(&self) -> String {
+        format!("{}:{}", self.timestamp, self.id)
+    }
+
+    pub fn from_cursor(principal: Principal, cursor: &str) -> Result<Self, String> {
+        let (timestamp, id) = cursor
+            .split_once(':')
+            .ok_or_else(|| format!("cursor should start with a timestamp, got {cursor}"))?;
+        let timestamp = timestamp
+            .parse::<u64>()
+            .map_err(|e| format!("invalid timestamp in cursor: {e}"))?;
+        Ok(Self {
+            principal,
+            timestamp,
+            id: TransactionId::from_str(id)?,
+        })
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StatementToken {
+    pub principal: Principal,
+    pub format: StatementFormat,
+    // Cursor of the last row of the previous page, none for the first page
+    pub cursor: Option<String>,
+}
+
+impl StatementToken {
+    // Renders a page of rows read after the cursor of this token, along with the token of the
+    // next page if more rows follow
+    pub fn render(
+        &self,
+        rows: &[(StatementKey, StatementRow)],
+        has_more: bool,
+    ) -> (Vec<u8>, Option<StatementToken>) {
+        let page: Vec<&StatementRow> = rows.iter().map(|(_key, row)| row).collect();
+        let body = render_statement_page(&page, self.format, self.cursor.is_none(), has_more);
+        let next = rows
+            .last()
+            .filter(|_| has_more)
+            .map(|(last_key, _row)| StatementToken {
+                cursor: Some(last_key.cursor()),
+                ..self.clone()
+            });
+        (body, next)
+    }
+}
+
+// Parses the principal and format of a statement path, none if `path` is not a statement
+pub fn parse_statement_path(path: &str) -> Option<Result<(Principal, StatementFormat), String>> {
+    let file = path.strip_prefix(STATEMENT_PATH_PREFIX)?;
+    let (principal, format) = match file.rsplit_once('.') {
+        Some((principal, "csv")) => (principal, StatementFormat::Csv),
+        Some((principal, "json")) => (principal, StatementFormat::Json),
+        _ => return Some(Err("statements are served as .csv or .json".to_string())),
+    };
+    Some(
+        Principal::from_text(principal)
+            .map(|principal| (principal, format))
+            .map_err(|e| format!("invalid principal: {e}")),
+    )
+}
+
+// A single bridged transfer. Amounts are formatted with the decimals of their token,
+// fees are paid in the native token of the chain.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct StatementRow {
+    pub id: String,
+    pub direction: BridgeDirection,
+    pub status: String,
+    pub chain_id: u64,
+    pub operator: Operator,
+    pub token_symbol: Option<String>,
+    pub token_address: String,
+    pub icrc_ledger_id: Option<String>,
+    // Nanoseconds since the epoch, completion is the mint of a deposit or the
+    // finalization of a withdrawal
+    pub submitted_at: Option<u64>,
+    pub accepted_at: Option<u64>,
+    pub completed_at: Option<u64>,
+    pub amount: String,
+    pub received: Option<String>,
+    pub fee: Option<String>,
+    // USD price of the token and USD value of `amount` when the transfer was accepted
+    pub usd_price: Option<String>,
+    pub usd_value: Option<String>,
+    pub transaction_hash: Option<String>,
+    pub ledger_burn_index: Option<u64>,
+    pub ledger_mint_index: Option<u64>,
+}
+
+const CSV_HEADER: &str = "id,direction,status,chain_id,operator,token_symbol,token_address,\
+icrc_ledger_id,submitted_at,accepted_at,completed_at,amount,received,fee,usd_price,usd_value,\
+transaction_hash,ledger_burn_index,ledger_mint_index";
+
+impl StatementRow {
+    fn to_csv(&self) -> String {
+        let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+        let optional_number =
+            |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+        [
+            self.id.clone(),
+            format!("{:?}", self.direction),
+            self.status.clone(),
+            self.chain_id.to_string(),
+            format!("{:?}", self.operator),
+            optional(&self.token_symbol),
+            self.token_address.clone(),
+            optional(&self.icrc_ledger_id),
+            optional_number(self.submitted_at),
+            optional_number(self.accepted_at),
+            optional_number(self.completed_at),
+            self.amount.clone(),
+            optional(&self.received),
+            optional(&self.fee),
+            optional(&self.usd_price),
+            optional(&self.usd_value),
+            optional(&self.transaction_hash),
+            optional_number(self.ledger_burn_index),
+            optional_number(self.ledger_mint_index),
+        ]
+        .iter()
+        .map(|field| escape_csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Formats an amount in the smallest denomination with the decimals of its token.
+// Amounts of tokens with unknown decimals are kept in the smallest denomination.
+pub fn format_amount(amount: Erc20TokenAmount, decimals: Option<u8>) -> String {
+    let digits = amount.to_string_inner();
+    let decimals = match decimals {
+        Some(decimals) if decimals > 0 => decimals as usize,
+        _ => return digits,
+    };
+    let digits = format!("{digits:0>width$}", width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{integer}.{fraction}")
+    }
+}
+
+// Renders one page of a statement. The pages concatenated form a single CSV file or JSON
+// array, so the first page opens it and the one without more pages closes it.
+pub fn render_statement_page(
+    rows: &[&StatementRow],
+    format: StatementFormat,
+    first_page: bool,
+    has_more: bool,
+) -> Vec<u8> {
+    let mut body = String::new();
+    match format {
+        StatementFormat::Csv => {
+            if first_page {
+                body.push_str(CSV_HEADER);
+                body.push('\n');
+            }
+            for row in rows {
+                body.push_str(&row.to_csv());
+                body.push('\n');
+            }
+        }
+        StatementFormat::Json => {
+            if first_page {
+                body.push('[');
+            }
+            for (index, row) in rows.iter().enumerate() {
+                if !first_page || index > 0 {
+                    body.push(',');
+                }
+                body.push_str(&serde_json::to_string(row).unwrap_or_default());
+            }
+            if !has_more {
+                body.push(']');
+            }
+        }
+    }
+
+    body.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: &str) -> StatementRow {
+        StatementRow {
+            id: id.to_string(),
+            direction: BridgeDirection::EvmToIcp,
+            status: "Minted".to_string(),
+            chain_id: 56,
+            operator: Operator::AppicMinter,
+            token_symbol: Some("USD, Tether".to_string()),
+            token_address: "0x55d398326f99059ff775485246999027b3197955".to_string(),
+            icrc_ledger_id: None,
+            submitted_at: None,
+            accepted_at: Some(1),
+            completed_at: Some(2),
+            amount: "1.5".to_string(),
+            received: Some("1.5".to_string()),
+            fee: None,
+            usd_price: None,
+            usd_value: None,
+            transaction_hash: None,
+            ledger_burn_index: None,
+            ledger_mint_index: Some(3),
+        }
+    }
+
+    #[test]
+    fn should_format_amounts_with_decimals() {
+        let amount = |amount: u64| Erc20TokenAmount::from(amount);
+
+        assert_eq!(format_amount(amount(1_500_000), Some(6)), "1.5");
+        assert_eq!(format_amount(amount(25), Some(6)), "0.000025");
+        assert_eq!(format_amount(amount(3_000_000), Some(6)), "3");
+        assert_eq!(format_amount(amount(0), Some(18)), "0");
+        assert_eq!(format_amount(amount(42), None), "42");
+    }
+
+    #[test]
+    fn should_parse_statement_paths() {
+        let principal = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+
+        assert_eq!(
+            parse_statement_path("/statement/ryjl3-tyaaa-aaaaa-aaaba-cai.csv"),
+            Some(Ok((principal, StatementFormat::Csv)))
+        );
+        assert_eq!(
+            parse_statement_path("/statement/ryjl3-tyaaa-aaaaa-aaaba-cai.json"),
+            Some(Ok((principal, StatementFormat::Json)))
+        );
+        assert!(matches!(
+            parse_statement_path("/statement/ryjl3-tyaaa-aaaaa-aaaba-cai.pdf"),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            parse_statement_path("/statement/not-a-principal.csv"),
+            Some(Err(_))
+        ));
+        assert_eq!(parse_statement_path("/logs"), None);
+    }
+
+    #[test]
+    fn should_split_statements_into_pages() {
+        let rows: Vec<StatementRow> = (0..STATEMENT_PAGE_SIZE + 1)
+            .map(|index| row(&index.to_string()))
+            .collect();
+        let pages: Vec<Vec<&StatementRow>> = rows
+            .chunks(STATEMENT_PAGE_SIZE)
+            .map(|page| page.iter().collect())
+            .collect();
+
+        let mut json = vec![];
+        let mut csv = vec![];
+        for (index, page) in pages.iter().enumerate() {
+            let (first_page, has_more) = (index == 0, index + 1 < pages.len());
+            json.extend(render_statement_page(
+                page,
+                StatementFormat::Json,
+                first_page,
+                has_more,
+            ));
+            csv.extend(render_statement_page(
+                page,
+                StatementFormat::Csv,
+                first_page,
+                has_more,
+            ));
+        }
+
+        let parsed: Vec<serde_json::Value> = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed.len(), rows.len());
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), rows.len() + 1);
+        assert!(csv.lines().nth(1).unwrap().contains("\"USD, Tether\""));
+
+        assert_eq!(
+            render_statement_page(&[], StatementFormat::Json, true, false),
+            b"[]".to_vec()
+        );
+    }
+
+    #[test]
+    fn should_round_trip_statement_cursors() {
+        let principal = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        let key = StatementKey {
+            timestamp: 42,
+            ..StatementKey::first_of(principal)
+        };
+
+        assert_eq!(StatementKey::from_cursor(principal, &key.cursor()), Ok(key));
+        assert!(StatementKey::from_cursor(principal, "not a cursor").is_err());
+    }
+}
@@ -0,0 +1,142 @@
+// Periodic detection of transactions that stopped progressing after they were accepted.
+// Deposits waiting for a mint and withdrawals waiting for their signed transaction to be
+// finalized raise an alert once they have been in that status longer than the threshold of
+// their chain.
+// Alerts are cleared as soon as the transaction moves on.
+
+use candid::{CandidType, Nat};
+use ic_canister_log::log;
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::CandidChainId;
+use crate::guard::{record_task_success, TaskType, TimerGuard};
+use crate::logs::INFO;
+use crate::state::{mutate_state, ChainId, EvmToIcpTxIdentifier, IcpToEvmIdentifier, Operator};
+
+const ONE_HOUR_IN_NS: u64 = 3_600_000_000_000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct StuckThresholds {
+    // Time in a waiting status before a transaction is reported as stuck
+    pub deposit_ns: u64,
+    pub withdrawal_ns: u64,
+}
+
+impl Default for StuckThresholds {
+    fn default() -> Self {
+        Self {
+            deposit_ns: ONE_HOUR_IN_NS,
+            withdrawal_ns: 3 * ONE_HOUR_IN_NS,
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct StuckThresholdArgs {
+    pub chain_id: CandidChainId,
+    pub deposit_threshold_ns: u64,
+    pub withdrawal_threshold_ns: u64,
+}
+
+impl From<&StuckThresholdArgs> for StuckThresholds {
+    fn from(value: &StuckThresholdArgs) -> Self {
+        Self {
+            deposit_ns: value.deposit_threshold_ns,
+            withdrawal_ns: value.withdrawal_threshold_ns,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub enum AlertKey {
+    EvmToIcp(EvmToIcpTxIdentifier),
+    IcpToEvm(IcpToEvmIdentifier),
+}
+
+#[derive(
+    Clone,
+    Copy,
+    CandidType,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Debug,
+    Deserialize,
+    Serialize,
+    strum_macros::IntoStaticStr,
+)]
+pub enum StuckReason {
+    DepositNotMinted,
+    WithdrawalSignedNotFinalized,
+    WithdrawalReplacedNotFinalized,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct Alert {
+    pub reason: StuckReason,
+    pub chain_id: ChainId,
+    pub operator: Operator,
+    pub accepted_at: u64,
+    pub detected_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum AlertTransaction {
+    EvmToIcp { transaction_hash: String },
+    IcpToEvm { native_ledger_burn_index: Nat },
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CandidAlert {
+    pub transaction: AlertTransaction,
+    pub chain_id: CandidChainId,
+    pub operator: Operator,
+    pub reason: StuckReason,
+    pub accepted_at: u64,
+    // Time the transaction entered the status it is stuck in
+    pub waiting_since: u64,
+    pub detected_at: u64,
+    // Time since the transaction entered the status it is stuck in
+    pub age_ns: u64,
+}
+
+impl CandidAlert {
+    pub fn new(key: AlertKey, alert: Alert, waiting_since: u64, now: u64) -> Self {
+        let transaction = match key {
+            AlertKey::EvmToIcp(identifier) => AlertTransaction::EvmToIcp {
+                transaction_hash: identifier.transaction_hash().to_string(),
+            },
+            AlertKey::IcpToEvm(identifier) => AlertTransaction::IcpToEvm {
+                native_ledger_burn_index: identifier.native_ledger_burn_index().get().into(),
+            },
+        };
+        Self {
+            transaction,
+            chain_id: alert.chain_id.into(),
+            operator: alert.operator,
+            reason: alert.reason,
+            accepted_at: alert.accepted_at,
+            waiting_since,
+            detected_at: alert.detected_at,
+            age_ns: now.saturating_sub(waiting_since),
+        }
+    }
+}
+
+pub fn detect_stuck_transactions() {
+    let _guard = match TimerGuard::new(TaskType::DetectStuckTransactions) {
+        Ok(guard) => guard,
+        Err(_) => return,
+    };
+
+    let (raised, cleared) = mutate_state(|s| s.update_stuck_alerts(ic_cdk::api::time()));
+    if raised > 0 || cleared > 0 {
+        log!(
+            INFO,
+            "[Detect Stuck Transactions] raised {raised} and cleared {cleared} alerts"
+        );
+    }
+
+    record_task_success(TaskType::DetectStuckTransactions);
+}
@@ -0,0 +1,144 @@
+// Transfers of a single token, newest first, looked up either by the icrc ledger of the token
+// or by its contract on an evm chain. Every transaction is indexed under both, so a page only
+// walks the transfers of the requested token.
+
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::bridge_stats::BridgeDirection;
+use crate::endpoints::Transaction;
+use crate::recent_activity::RecentActivityKey;
+use crate::state::{
+    ChainId, Erc20Identifier, EvmToIcpStatus, EvmToIcpTx, EvmToIcpTxIdentifier, IcpToEvmStatus,
+    IcpToEvmTx, TransactionHash, TransactionId,
+};
+
+pub const MAX_TOKEN_TRANSACTIONS: usize = 100;
+
+// Index entries a single page may walk. Statuses are only known once a transaction is read,
+// so a rare status could otherwise walk every transfer of the token.
+pub const MAX_SCANNED_TOKEN_ACTIVITY: usize = 1_000;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub enum TokenIndex {
+    IcrcLedger(Principal),
+    Erc20(Erc20Identifier),
+}
+
+// Transfers of a token are grouped together and ordered like the recent activity feed
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub struct TokenActivityKey {
+    pub token: TokenIndex,
+    pub activity: RecentActivityKey,
+}
+
+impl TokenActivityKey {
+    pub fn for_evm_to_icp(tx: &EvmToIcpTx) -> Vec<Self> {
+        Self::for_tokens(
+            RecentActivityKey::for_evm_to_icp(tx),
+            tx.icrc_ledger_id,
+            Erc20Identifier::new(&tx.erc20_contract_address, tx.chain_id),
+        )
+    }
+
+    pub fn for_icp_to_evm(tx: &IcpToEvmTx) -> Vec<Self> {
+        Self::for_tokens(
+            RecentActivityKey::for_icp_to_evm(tx),
+            tx.icrc_ledger_id,
+            Erc20Identifier::new(&tx.erc20_contract_address, tx.chain_id),
+        )
+    }
+
+    fn for_tokens(
+        activity: RecentActivityKey,
+        icrc_ledger_id: Option<Principal>,
+        erc20: Erc20Identifier,
+    ) -> Vec<Self> {
+        icrc_ledger_id
+            .map(TokenIndex::IcrcLedger)
+            .into_iter()
+            .chain([TokenIndex::Erc20(erc20)])
+            .map(|token| Self {
+                token,
+                activity: activity.clone(),
+            })
+            .collect()
+    }
+
+    // The first key of `token` at or before `timestamp`. No transaction id sorts before a
+    // deposit with a zero hash on chain zero, so no key of that time is skipped.
+    pub fn first_at(token: TokenIndex, timestamp: u64) -> Self {
+        Self {
+            token,
+            activity: RecentActivityKey {
+                timestamp,
+                id: TransactionId::EvmToIcp(EvmToIcpTxIdentifier::new(
+                    &TransactionHash::new([0; 32]),
+                    ChainId(0),
+                )),
+            },
+        }
+    }
+}
+
+// Status of either direction. Only the variant is compared, so `Invalid` matches any reason.
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum StatusFilter {
+    EvmToIcp(EvmToIcpStatus),
+    IcpToEvm(IcpToEvmStatus),
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenTransactionsFilters {
+    pub direction: Option<BridgeDirection>,
+    pub status: Option<StatusFilter>,
+    // Bounds in nanoseconds since the epoch, both inclusive, on the time a transfer first
+    // appeared like in the recent activity feed
+    pub from_timestamp: Option<u64>,
+    pub to_timestamp: Option<u64>,
+}
+
+impl TokenTransactionsFilters {
+    // Filters that only need the index key
+    pub fn matches_key(&self, key: &RecentActivityKey) -> bool {
+        let direction = match key.id {
+            TransactionId::EvmToIcp(_) => BridgeDirection::EvmToIcp,
+            TransactionId::IcpToEvm(_) => BridgeDirection::IcpToEvm,
+        };
+        let status_direction = match self.status {
+            Some(StatusFilter::EvmToIcp(_)) => Some(BridgeDirection::EvmToIcp),
+            Some(StatusFilter::IcpToEvm(_)) => Some(BridgeDirection::IcpToEvm),
+            None => None,
+        };
+        self.direction.map_or(true, |filter| filter == direction)
+            && status_direction.map_or(true, |filter| filter == direction)
+    }
+
+    pub fn matches_evm_to_icp_status(&self, status: &EvmToIcpStatus) -> bool {
+        match &self.status {
+            Some(StatusFilter::EvmToIcp(filter)) => <&str>::from(filter) == <&str>::from(status),
+            _ => true,
+        }
+    }
+
+    pub fn matches_icp_to_evm_status(&self, status: &IcpToEvmStatus) -> bool {
+        match &self.status {
+            Some(StatusFilter::IcpToEvm(filter)) => filter == status,
+            _ => true,
+        }
+    }
+
+    // Keys are ordered newest first, so the first key before the range ends the page
+    pub fn is_before_range(&self, key: &RecentActivityKey) -> bool {
+        self.from_timestamp
+            .map_or(false, |from_timestamp| key.timestamp < from_timestamp)
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenTransactions {
+    pub transactions: Vec<Transaction>,
+    // Passed back to get the next page, none once the transfers are exhausted. A page that
+    // walked `MAX_SCANNED_TOKEN_ACTIVITY` entries may hold fewer transfers than asked, or none.
+    pub next_cursor: Option<String>,
+}
@@ -2,7 +2,7 @@ use candid::Principal;
 use ic_canister_log::log;
 
 use crate::{
-    guard::TimerGuard,
+    guard::{record_task_success, TaskType, TimerGuard},
     ledger_manager_client::LsClient,
     logs::{DEBUG, INFO},
     state::{mutate_state, BridgePair},
@@ -15,7 +15,7 @@ const APPIC_LEDGER_MANAGER_ID: &str = "kmcdp-4yaaa-aaaag-ats3q-cai";
 /// If there are new twin tokens, they are added to the state.
 pub async fn update_bridge_pairs() {
     // Issue a timer gaurd
-    let _gaurd = match TimerGuard::new(crate::guard::TaskType::UpdateBridgePairs) {
+    let gaurd = match TimerGuard::new(TaskType::UpdateBridgePairs) {
         Ok(gaurd) => gaurd,
         Err(_) => return,
     };
@@ -42,7 +42,11 @@ pub async fn update_bridge_pairs() {
             source_name
         );
 
-        match client.get_erc20_list().await {
+        let erc20_list = client.get_erc20_list().await;
+        if gaurd.renew().is_err() {
+            return;
+        }
+        match erc20_list {
             Ok(bridge_pairs) => {
                 process_bridge_pairs(bridge_pairs.get_bridge_pairs_iter(), operator, source_name)
             }
@@ -56,6 +60,8 @@ pub async fn update_bridge_pairs() {
             }
         }
     }
+
+    record_task_success(TaskType::UpdateBridgePairs);
 }
 
 /// Processes bridge pairs, checking if they exist and adding them to the state if they do not.
@@ -82,18 +88,7 @@ where
                             erc20_identifier,
                             source_name
                         );
-                        match operator {
-                            crate::state::Operator::DfinityCkEthMinter => {
-                                state
-                                    .supported_ckerc20_tokens
-                                    .insert(erc20_identifier, bridge_pair);
-                            }
-                            crate::state::Operator::AppicMinter => {
-                                state
-                                    .supported_twin_appic_tokens
-                                    .insert(erc20_identifier, bridge_pair);
-                            }
-                        }
+                        state.record_bridge_pair(operator, erc20_identifier, bridge_pair);
                     }
                 }
             }
@@ -1,5 +1,5 @@
 use crate::{
-    guard::TimerGuard,
+    guard::{record_task_success, TaskType, TimerGuard, TimerGuardError},
     icp_tokens_service::TokenService,
     logs::INFO,
     state::{mutate_state, read_state, IcpToken},
@@ -13,17 +13,18 @@ const VALIDATION_BATCH_SIZE: usize = 5;
 
 pub async fn update_icp_tokens() {
     // Issue a timer gaurd
-    let _gaurd = match TimerGuard::new(crate::guard::TaskType::UpdateIcpTokens) {
+    let gaurd = match TimerGuard::new(TaskType::UpdateIcpTokens) {
         Ok(gaurd) => gaurd,
         Err(_) => return,
     };
 
     // While upgrading icp token, it is recommended to prevent usd price
     // updates.
-    let _usd_price_gaurd = match TimerGuard::new(crate::guard::TaskType::UpdateUsdPrice) {
+    let usd_price_gaurd = match TimerGuard::new(TaskType::UpdateUsdPrice) {
         Ok(gaurd) => gaurd,
         Err(_) => return,
     };
+    let gaurds = [&gaurd, &usd_price_gaurd];
 
     let token_service = TokenService::new();
 
@@ -32,6 +33,9 @@ pub async fn update_icp_tokens() {
         token_service.get_icp_swap_tokens().await,
         token_service.get_sonic_tokens().await,
     );
+    if renew_all(&gaurds).is_err() {
+        return;
+    }
 
     let mut unique_tokens = HashSet::with_capacity(icp_swap_tokens.len() + sonic_swap_tokens.len());
 
@@ -58,7 +62,10 @@ pub async fn update_icp_tokens() {
     let icp_tokens: Vec<IcpToken> = unique_tokens.into_iter().collect();
 
     let validated_token =
-        validate_tokens_in_batch(&icp_tokens, VALIDATION_BATCH_SIZE, &token_service).await;
+        validate_tokens_in_batch(&icp_tokens, VALIDATION_BATCH_SIZE, &token_service, &gaurds).await;
+    if renew_all(&gaurds).is_err() {
+        return;
+    }
 
     // Record new ICP tokens
     log!(
@@ -71,21 +78,34 @@ pub async fn update_icp_tokens() {
             s.record_icp_token(token.ledger_id, token.clone());
         }
     });
+
+    record_task_success(TaskType::UpdateIcpTokens);
 }
 
 // Runs Intervaly to update usd price of icp tokens
 pub async fn update_usd_price() {
-    let _gaurd = match TimerGuard::new(crate::guard::TaskType::UpdateUsdPrice) {
+    let gaurd = match TimerGuard::new(TaskType::UpdateUsdPrice) {
         Ok(gaurd) => gaurd,
         Err(_) => return,
     };
 
     let token_service = TokenService::new();
 
-    let icp_token_with_usd_price = token_service
-        .get_icp_swap_tokens_with_usd_price()
-        .await
-        .expect("Failed to get icp tokens with their price, will retry in next iteration");
+    let icp_token_with_usd_price = token_service.get_icp_swap_tokens_with_usd_price().await;
+    if gaurd.renew().is_err() {
+        return;
+    }
+    let icp_token_with_usd_price = match icp_token_with_usd_price {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            log!(
+                INFO,
+                "[Update Usd Price] Failed to get icp tokens with their price, will retry in next iteration: {}",
+                e
+            );
+            return;
+        }
+    };
 
     icp_token_with_usd_price
         .iter()
@@ -98,12 +118,14 @@ pub async fn update_usd_price() {
                 );
             })
         });
+
+    record_task_success(TaskType::UpdateUsdPrice);
 }
 
 // Runs intervaly to remove invalid tokens
 pub async fn validate_tokens() {
     // Issue a timer gaurd
-    let _gaurd = match TimerGuard::new(crate::guard::TaskType::RemoveInvalidTokens) {
+    let gaurd = match TimerGuard::new(TaskType::RemoveInvalidTokens) {
         Ok(gaurd) => gaurd,
         Err(_) => return,
     };
@@ -120,6 +142,9 @@ pub async fn validate_tokens() {
             .validate_token(token.ledger_id, &token.token_type)
             .await
             .is_ok();
+        if gaurd.renew().is_err() {
+            return;
+        }
 
         if is_valid {
             valid_tokens += 1;
@@ -139,12 +164,21 @@ pub async fn validate_tokens() {
         valid_tokens,
         tokens.len() - valid_tokens
     );
+
+    record_task_success(TaskType::RemoveInvalidTokens);
 }
 
+// Renews every guard held by a task, failing if any of them was taken over
+fn renew_all(gaurds: &[&TimerGuard]) -> Result<(), TimerGuardError> {
+    gaurds.iter().try_for_each(|gaurd| gaurd.renew())
+}
+
+// Stops early if a lease of `gaurds` is taken over, the caller then stops at its own renewal
 async fn validate_tokens_in_batch<'a>(
     icp_tokens: &'a [IcpToken], // Borrow tokens as a slice to avoid ownership transfer
     batch_size: usize,
     token_service: &TokenService,
+    gaurds: &[&TimerGuard],
 ) -> Vec<&'a IcpToken> {
     // Return references to the valid tokens
     let mut valid_tokens = Vec::new();
@@ -181,6 +215,9 @@ async fn validate_tokens_in_batch<'a>(
 
         // Execute all futures in the batch concurrently
         let results: Vec<Option<&IcpToken>> = join_all(futures).await;
+        if renew_all(gaurds).is_err() {
+            break;
+        }
 
         // Collect valid tokens from the results
         valid_tokens.extend(results.into_iter().flatten());